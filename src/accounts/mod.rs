@@ -0,0 +1,445 @@
+use borsh::BorshDeserialize;
+use solana_sdk::pubkey::Pubkey;
+use std::fmt;
+
+use crate::state::bonding_curve::GlobalParams;
+use crate::state::pool::FeeConfig;
+use crate::transaction::token_parser::is_token_program;
+
+pub mod snapshot;
+
+// Anchor账户discriminator (sha256("account:<账户名>")前8字节)
+pub const GLOBAL_DISCRIMINATOR: [u8; 8] = [167, 232, 232, 177, 200, 108, 114, 127];
+pub const BONDING_CURVE_DISCRIMINATOR: [u8; 8] = [23, 183, 248, 55, 96, 216, 172, 96];
+pub const GLOBAL_CONFIG_DISCRIMINATOR: [u8; 8] = [149, 8, 156, 202, 160, 252, 176, 217];
+pub const POOL_DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+
+// creator fee更新后追加在账户末尾的创建者地址偏移 (含discriminator)
+const BONDING_CURVE_CREATOR_OFFSET: usize = 8 + 8 * 5 + 1;
+const POOL_COIN_CREATOR_OFFSET: usize = 8 + 1 + 2 + 32 * 6 + 8;
+// fee_basis_points之后依次是withdraw_authority, enable_migrate, pool_migration_fee, creator_fee_basis_points
+const GLOBAL_CREATOR_FEE_OFFSET: usize = 8 + 1 + 32 * 2 + 8 * 5 + 32 + 1 + 8;
+// protocol_fee_recipients之后的coin_creator_fee_basis_points
+const GLOBAL_CONFIG_COIN_CREATOR_FEE_OFFSET: usize = 8 + 32 + 8 * 2 + 1 + 32 * 8;
+
+// SPL Token账户的固定长度，Token-2022带扩展时会更长
+const TOKEN_ACCOUNT_LEN: usize = 165;
+
+// 账户解码失败原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountError {
+    // 数据短于discriminator
+    TooShort(usize),
+    // discriminator与期望的账户类型不一致
+    Discriminator([u8; 8]),
+    // Borsh布局不匹配
+    Layout(String),
+}
+
+impl fmt::Display for AccountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountError::TooShort(len) => write!(f, "账户数据长度不足: {}字节", len),
+            AccountError::Discriminator(found) => write!(f, "账户discriminator不匹配: {:?}", found),
+            AccountError::Layout(error) => write!(f, "账户布局解析失败: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for AccountError {}
+
+/// Pump `Global` 账户
+#[derive(Debug, Clone)]
+pub struct GlobalAccount {
+    pub initialized: bool,
+    pub authority: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub params: GlobalParams,
+}
+
+/// Pump `BondingCurve` 账户，账户本身不包含mint
+#[derive(Debug, Clone, Copy)]
+pub struct BondingCurveAccount {
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub token_total_supply: u64,
+    pub complete: bool,
+    // 更新前创建、尚未扩容的曲线账户没有这个字段
+    pub creator: Option<Pubkey>,
+}
+
+/// Pump AMM `GlobalConfig` 账户
+#[derive(Debug, Clone)]
+pub struct GlobalConfigAccount {
+    pub admin: Pubkey,
+    pub fees: FeeConfig,
+    // bit 0~4 分别禁用 create_pool/deposit/withdraw/buy/sell
+    pub disable_flags: u8,
+    pub protocol_fee_recipients: Vec<Pubkey>,
+}
+
+/// Pump AMM `Pool` 账户，储备在两个代币金库中，不在池账户里
+#[derive(Debug, Clone)]
+pub struct PoolAccount {
+    pub pool_bump: u8,
+    pub index: u16,
+    pub creator: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub pool_base_token_account: Pubkey,
+    pub pool_quote_token_account: Pubkey,
+    pub lp_supply: u64,
+    // 更新前创建、尚未扩容的池账户没有这个字段
+    pub coin_creator: Option<Pubkey>,
+}
+
+/// SPL Token账户的公共前缀
+#[derive(Debug, Clone, Copy)]
+pub struct TokenAccount {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+// 按账户类型识别出的账户
+#[derive(Debug, Clone)]
+pub enum DecodedAccount {
+    Global(GlobalAccount),
+    BondingCurve(BondingCurveAccount),
+    GlobalConfig(GlobalConfigAccount),
+    Pool(PoolAccount),
+    Token(TokenAccount),
+}
+
+// Borsh反序列化结构体，账户可能在末尾追加新字段或留有空间，只按前缀解析
+#[derive(BorshDeserialize, Debug)]
+struct GlobalLayout {
+    initialized: bool,
+    authority: [u8; 32],
+    fee_recipient: [u8; 32],
+    initial_virtual_token_reserves: u64,
+    initial_virtual_sol_reserves: u64,
+    initial_real_token_reserves: u64,
+    token_total_supply: u64,
+    fee_basis_points: u64,
+}
+
+#[derive(BorshDeserialize, Debug)]
+struct BondingCurveLayout {
+    virtual_token_reserves: u64,
+    virtual_sol_reserves: u64,
+    real_token_reserves: u64,
+    real_sol_reserves: u64,
+    token_total_supply: u64,
+    complete: bool,
+}
+
+#[derive(BorshDeserialize, Debug)]
+struct GlobalConfigLayout {
+    admin: [u8; 32],
+    lp_fee_basis_points: u64,
+    protocol_fee_basis_points: u64,
+    disable_flags: u8,
+    protocol_fee_recipients: [[u8; 32]; 8],
+}
+
+#[derive(BorshDeserialize, Debug)]
+struct PoolLayout {
+    pool_bump: u8,
+    index: u16,
+    creator: [u8; 32],
+    base_mint: [u8; 32],
+    quote_mint: [u8; 32],
+    lp_mint: [u8; 32],
+    pool_base_token_account: [u8; 32],
+    pool_quote_token_account: [u8; 32],
+    lp_supply: u64,
+}
+
+#[derive(BorshDeserialize, Debug)]
+struct TokenAccountLayout {
+    mint: [u8; 32],
+    owner: [u8; 32],
+    amount: u64,
+}
+
+// 校验discriminator并按前缀反序列化账户主体
+fn decode_anchor<T: BorshDeserialize>(data: &[u8], discriminator: &[u8; 8]) -> Result<T, AccountError> {
+    if data.len() < 8 {
+        return Err(AccountError::TooShort(data.len()));
+    }
+    let (found, mut body) = data.split_at(8);
+    if found != discriminator {
+        return Err(AccountError::Discriminator(found.try_into().expect("8字节")));
+    }
+    T::deserialize(&mut body).map_err(|e| AccountError::Layout(e.to_string()))
+}
+
+// 读取前缀布局之后追加的费率字段，数据不够长时按0处理
+fn trailing_u64(data: &[u8], offset: usize) -> u64 {
+    data.get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().expect("8字节")))
+        .unwrap_or(0)
+}
+
+// 读取前缀布局之后追加的公钥字段，数据不够长时为None
+fn trailing_pubkey(data: &[u8], offset: usize) -> Option<Pubkey> {
+    let bytes: [u8; 32] = data.get(offset..offset + 32)?.try_into().ok()?;
+    Some(Pubkey::new_from_array(bytes))
+}
+
+pub fn decode_global(data: &[u8]) -> Result<GlobalAccount, AccountError> {
+    let layout: GlobalLayout = decode_anchor(data, &GLOBAL_DISCRIMINATOR)?;
+    Ok(GlobalAccount {
+        initialized: layout.initialized,
+        authority: Pubkey::new_from_array(layout.authority),
+        fee_recipient: Pubkey::new_from_array(layout.fee_recipient),
+        params: GlobalParams {
+            initial_virtual_token_reserves: layout.initial_virtual_token_reserves,
+            initial_virtual_sol_reserves: layout.initial_virtual_sol_reserves,
+            initial_real_token_reserves: layout.initial_real_token_reserves,
+            token_total_supply: layout.token_total_supply,
+            fee_basis_points: layout.fee_basis_points,
+            creator_fee_basis_points: trailing_u64(data, GLOBAL_CREATOR_FEE_OFFSET),
+        },
+    })
+}
+
+pub fn decode_bonding_curve(data: &[u8]) -> Result<BondingCurveAccount, AccountError> {
+    let layout: BondingCurveLayout = decode_anchor(data, &BONDING_CURVE_DISCRIMINATOR)?;
+    Ok(BondingCurveAccount {
+        virtual_token_reserves: layout.virtual_token_reserves,
+        virtual_sol_reserves: layout.virtual_sol_reserves,
+        real_token_reserves: layout.real_token_reserves,
+        real_sol_reserves: layout.real_sol_reserves,
+        token_total_supply: layout.token_total_supply,
+        complete: layout.complete,
+        creator: trailing_pubkey(data, BONDING_CURVE_CREATOR_OFFSET),
+    })
+}
+
+pub fn decode_global_config(data: &[u8]) -> Result<GlobalConfigAccount, AccountError> {
+    let layout: GlobalConfigLayout = decode_anchor(data, &GLOBAL_CONFIG_DISCRIMINATOR)?;
+    Ok(GlobalConfigAccount {
+        admin: Pubkey::new_from_array(layout.admin),
+        fees: FeeConfig {
+            lp_fee_basis_points: layout.lp_fee_basis_points,
+            protocol_fee_basis_points: layout.protocol_fee_basis_points,
+            coin_creator_fee_basis_points: trailing_u64(data, GLOBAL_CONFIG_COIN_CREATOR_FEE_OFFSET),
+        },
+        disable_flags: layout.disable_flags,
+        protocol_fee_recipients: layout
+            .protocol_fee_recipients
+            .iter()
+            .map(|key| Pubkey::new_from_array(*key))
+            .collect(),
+    })
+}
+
+pub fn decode_pool(data: &[u8]) -> Result<PoolAccount, AccountError> {
+    let layout: PoolLayout = decode_anchor(data, &POOL_DISCRIMINATOR)?;
+    Ok(PoolAccount {
+        pool_bump: layout.pool_bump,
+        index: layout.index,
+        creator: Pubkey::new_from_array(layout.creator),
+        base_mint: Pubkey::new_from_array(layout.base_mint),
+        quote_mint: Pubkey::new_from_array(layout.quote_mint),
+        lp_mint: Pubkey::new_from_array(layout.lp_mint),
+        pool_base_token_account: Pubkey::new_from_array(layout.pool_base_token_account),
+        pool_quote_token_account: Pubkey::new_from_array(layout.pool_quote_token_account),
+        lp_supply: layout.lp_supply,
+        coin_creator: trailing_pubkey(data, POOL_COIN_CREATOR_OFFSET),
+    })
+}
+
+/// 解析SPL Token/Token-2022账户的 mint、owner、amount
+pub fn decode_token_account(data: &[u8]) -> Result<TokenAccount, AccountError> {
+    if data.len() < TOKEN_ACCOUNT_LEN {
+        return Err(AccountError::TooShort(data.len()));
+    }
+    let layout = TokenAccountLayout::deserialize(&mut &data[..]).map_err(|e| AccountError::Layout(e.to_string()))?;
+    Ok(TokenAccount {
+        mint: Pubkey::new_from_array(layout.mint),
+        owner: Pubkey::new_from_array(layout.owner),
+        amount: layout.amount,
+    })
+}
+
+/// 按discriminator识别账户类型并解码
+///
+/// 代币账户没有discriminator，已知owner时按owner判断，否则按165字节的固定长度判断。
+/// 无法识别的账户返回None。
+pub fn decode_account(owner: Option<&Pubkey>, data: &[u8]) -> Option<Result<DecodedAccount, AccountError>> {
+    let discriminator = data.get(..8)?;
+    let decoded = if discriminator == GLOBAL_DISCRIMINATOR {
+        decode_global(data).map(DecodedAccount::Global)
+    } else if discriminator == BONDING_CURVE_DISCRIMINATOR {
+        decode_bonding_curve(data).map(DecodedAccount::BondingCurve)
+    } else if discriminator == GLOBAL_CONFIG_DISCRIMINATOR {
+        decode_global_config(data).map(DecodedAccount::GlobalConfig)
+    } else if discriminator == POOL_DISCRIMINATOR {
+        decode_pool(data).map(DecodedAccount::Pool)
+    } else {
+        let is_token_account = match owner {
+            Some(owner) => is_token_program(owner),
+            None => data.len() == TOKEN_ACCOUNT_LEN,
+        };
+        if !is_token_account {
+            return None;
+        }
+        decode_token_account(data).map(DecodedAccount::Token)
+    };
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Global账户到fee_basis_points为止的前缀
+    fn global_prefix(fee_basis_points: u64) -> Vec<u8> {
+        let mut data = GLOBAL_DISCRIMINATOR.to_vec();
+        data.push(1);
+        data.extend_from_slice(Pubkey::new_unique().as_ref());
+        data.extend_from_slice(Pubkey::new_unique().as_ref());
+        for value in [1_073_000_000_000_000u64, 30_000_000_000, 793_100_000_000_000, 1_000_000_000_000_000] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&fee_basis_points.to_le_bytes());
+        data
+    }
+
+    // GlobalConfig账户到protocol_fee_recipients为止的前缀
+    fn global_config_prefix() -> Vec<u8> {
+        let mut data = GLOBAL_CONFIG_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(Pubkey::new_unique().as_ref());
+        data.extend_from_slice(&20u64.to_le_bytes());
+        data.extend_from_slice(&5u64.to_le_bytes());
+        data.push(0);
+        for _ in 0..8 {
+            data.extend_from_slice(Pubkey::new_unique().as_ref());
+        }
+        data
+    }
+
+    #[test]
+    fn global_creator_fee() {
+        let mut data = global_prefix(95);
+        assert_eq!(data.len(), 113);
+        let global = decode_global(&data).expect("Global");
+        assert_eq!(global.params.fee_basis_points, 95);
+        assert_eq!(global.params.creator_fee_basis_points, 0);
+
+        // withdraw_authority, enable_migrate, pool_migration_fee, creator_fee_basis_points
+        data.extend_from_slice(Pubkey::new_unique().as_ref());
+        data.push(1);
+        data.extend_from_slice(&15_000_001u64.to_le_bytes());
+        assert_eq!(data.len(), GLOBAL_CREATOR_FEE_OFFSET);
+        data.extend_from_slice(&5u64.to_le_bytes());
+        data.extend_from_slice(&[0u8; 32]);
+        let global = decode_global(&data).expect("Global");
+        assert_eq!(global.params.fee_basis_points, 95);
+        assert_eq!(global.params.creator_fee_basis_points, 5);
+    }
+
+    #[test]
+    fn global_config_coin_creator_fee() {
+        let mut data = global_config_prefix();
+        assert_eq!(data.len(), GLOBAL_CONFIG_COIN_CREATOR_FEE_OFFSET);
+        let config = decode_global_config(&data).expect("GlobalConfig");
+        assert_eq!(config.fees.coin_creator_fee_basis_points, 0);
+        assert_eq!(config.protocol_fee_recipients.len(), 8);
+
+        data.extend_from_slice(&5u64.to_le_bytes());
+        let config = decode_global_config(&data).expect("GlobalConfig");
+        assert_eq!(
+            config.fees,
+            FeeConfig { lp_fee_basis_points: 20, protocol_fee_basis_points: 5, coin_creator_fee_basis_points: 5 }
+        );
+        assert_eq!(config.fees.total_basis_points(), 30);
+    }
+
+    fn bonding_curve_data(creator: Option<&Pubkey>) -> Vec<u8> {
+        let mut data = BONDING_CURVE_DISCRIMINATOR.to_vec();
+        for value in [1_072_000_000_000_000u64, 30_027_985_075, 792_100_000_000_000, 27_985_075, 1_000_000_000_000_000] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.push(0);
+        if let Some(creator) = creator {
+            data.extend_from_slice(creator.as_ref());
+        }
+        data
+    }
+
+    #[test]
+    fn bonding_curve_with_and_without_creator() {
+        let legacy = bonding_curve_data(None);
+        assert_eq!(legacy.len(), BONDING_CURVE_CREATOR_OFFSET);
+        let curve = decode_bonding_curve(&legacy).expect("BondingCurve");
+        assert_eq!(
+            (curve.virtual_token_reserves, curve.virtual_sol_reserves, curve.real_token_reserves, curve.real_sol_reserves),
+            (1_072_000_000_000_000, 30_027_985_075, 792_100_000_000_000, 27_985_075)
+        );
+        assert_eq!(curve.token_total_supply, 1_000_000_000_000_000);
+        assert!(!curve.complete);
+        assert_eq!(curve.creator, None);
+
+        let creator = Pubkey::new_unique();
+        let mut data = bonding_curve_data(Some(&creator));
+        // 扩容后的账户在creator之后留有空间
+        data.extend_from_slice(&[0u8; 32]);
+        assert_eq!(decode_bonding_curve(&data).expect("BondingCurve").creator, Some(creator));
+    }
+
+    #[test]
+    fn pool_with_coin_creator() {
+        let keys: Vec<Pubkey> = (0..6).map(|_| Pubkey::new_unique()).collect();
+        let mut data = POOL_DISCRIMINATOR.to_vec();
+        data.push(254);
+        data.extend_from_slice(&3u16.to_le_bytes());
+        for key in &keys {
+            data.extend_from_slice(key.as_ref());
+        }
+        data.extend_from_slice(&3_999_999_999_900u64.to_le_bytes());
+        assert_eq!(data.len(), POOL_COIN_CREATOR_OFFSET);
+
+        let pool = decode_pool(&data).expect("Pool");
+        assert_eq!((pool.pool_bump, pool.index, pool.lp_supply), (254, 3, 3_999_999_999_900));
+        assert_eq!(
+            [pool.creator, pool.base_mint, pool.quote_mint, pool.lp_mint, pool.pool_base_token_account, pool.pool_quote_token_account],
+            keys.as_slice()
+        );
+        assert_eq!(pool.coin_creator, None);
+
+        let coin_creator = Pubkey::new_unique();
+        data.extend_from_slice(coin_creator.as_ref());
+        let pool = decode_pool(&data).expect("Pool");
+        assert_eq!(pool.coin_creator, Some(coin_creator));
+    }
+
+    #[test]
+    fn discriminator_and_length_errors() {
+        let data = bonding_curve_data(None);
+        assert_eq!(decode_pool(&data).err(), Some(AccountError::Discriminator(BONDING_CURVE_DISCRIMINATOR)));
+        assert_eq!(decode_bonding_curve(&data[..4]).err(), Some(AccountError::TooShort(4)));
+        assert!(matches!(decode_bonding_curve(&data[..20]), Err(AccountError::Layout(_))));
+        assert!(matches!(decode_account(None, &data), Some(Ok(DecodedAccount::BondingCurve(_)))));
+    }
+
+    #[test]
+    fn token_account_prefix() {
+        let (mint, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = mint.to_bytes().to_vec();
+        data.extend_from_slice(owner.as_ref());
+        data.extend_from_slice(&42u64.to_le_bytes());
+        data.resize(TOKEN_ACCOUNT_LEN, 0);
+        let Some(Ok(DecodedAccount::Token(account))) = decode_account(None, &data) else {
+            panic!("期望代币账户");
+        };
+        assert_eq!((account.mint, account.owner, account.amount), (mint, owner, 42));
+        assert_eq!(decode_token_account(&data[..100]).err(), Some(AccountError::TooShort(100)));
+    }
+}
@@ -0,0 +1,172 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::warn;
+
+// 快照加载失败原因
+#[derive(Debug)]
+pub enum SnapshotError {
+    Io(PathBuf, std::io::Error),
+    Json(PathBuf, String),
+    Base64(PathBuf, String),
+    // 文件名不是账户地址，且内容中也没有地址
+    MissingPubkey(PathBuf),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::Io(path, error) => write!(f, "读取{}失败: {}", path.display(), error),
+            SnapshotError::Json(path, error) => write!(f, "{}不是合法的JSON: {}", path.display(), error),
+            SnapshotError::Base64(path, error) => write!(f, "{}的base64数据非法: {}", path.display(), error),
+            SnapshotError::MissingPubkey(path) => write!(f, "无法确定{}对应的账户地址", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+// 快照中的单个账户
+#[derive(Debug, Clone)]
+pub struct SnapshotAccount {
+    pub pubkey: Pubkey,
+    // JSON格式带有owner，base64/二进制文件没有
+    pub owner: Option<Pubkey>,
+    pub data: Vec<u8>,
+}
+
+/// 从文件加载的账户快照
+#[derive(Debug, Default)]
+pub struct AccountSnapshot {
+    pub accounts: Vec<SnapshotAccount>,
+    // 无法识别地址或编码而跳过的条目
+    pub skipped: usize,
+}
+
+// 快照文件的扩展名
+const SNAPSHOT_EXTENSIONS: [&str; 4] = ["json", "b64", "base64", "account"];
+
+impl AccountSnapshot {
+    /// 加载单个文件或目录下的所有快照文件
+    ///
+    /// 支持的格式:
+    /// - `.json`: `solana account --output json` 的输出(与 `solana-test-validator --account-dir` 相同)、
+    ///   这种对象的数组，或 `getProgramAccounts`/`getAccountInfo` 的JSON-RPC响应，数据必须是base64编码
+    /// - `.b64`/`.base64`: base64编码的账户数据，文件名为账户地址
+    /// - `.account`: 原始账户数据，文件名为账户地址；不用 `.bin`，避免和 `RECORD_DIR` 中录制的批次文件混淆
+    ///
+    /// 目录中只加载这些扩展名的文件。
+    pub fn load(path: &Path) -> Result<Self, SnapshotError> {
+        let mut snapshot = AccountSnapshot::default();
+
+        if !path.is_dir() {
+            snapshot.load_file(path)?;
+            return Ok(snapshot);
+        }
+
+        let mut files: Vec<PathBuf> = fs::read_dir(path)
+            .map_err(|e| SnapshotError::Io(path.to_path_buf(), e))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.is_file()
+                    && p.extension()
+                        .and_then(|ext| ext.to_str())
+                        .is_some_and(|ext| SNAPSHOT_EXTENSIONS.contains(&ext))
+            })
+            .collect();
+        files.sort();
+
+        for file in files {
+            if let Err(e) = snapshot.load_file(&file) {
+                warn!(error = %e, "跳过无法加载的快照文件");
+                snapshot.skipped += 1;
+            }
+        }
+        Ok(snapshot)
+    }
+
+    fn load_file(&mut self, path: &Path) -> Result<(), SnapshotError> {
+        let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+        let stem_pubkey = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| Pubkey::from_str(stem).ok());
+
+        match extension {
+            "json" => {
+                let text = fs::read_to_string(path).map_err(|e| SnapshotError::Io(path.to_path_buf(), e))?;
+                let value: Value = serde_json::from_str(&text).map_err(|e| SnapshotError::Json(path.to_path_buf(), e.to_string()))?;
+                self.collect_json(&value, stem_pubkey);
+            }
+            "b64" | "base64" => {
+                let pubkey = stem_pubkey.ok_or_else(|| SnapshotError::MissingPubkey(path.to_path_buf()))?;
+                let text = fs::read_to_string(path).map_err(|e| SnapshotError::Io(path.to_path_buf(), e))?;
+                let data = STANDARD
+                    .decode(text.trim())
+                    .map_err(|e| SnapshotError::Base64(path.to_path_buf(), e.to_string()))?;
+                self.accounts.push(SnapshotAccount { pubkey, owner: None, data });
+            }
+            "account" => {
+                let pubkey = stem_pubkey.ok_or_else(|| SnapshotError::MissingPubkey(path.to_path_buf()))?;
+                let data = fs::read(path).map_err(|e| SnapshotError::Io(path.to_path_buf(), e))?;
+                self.accounts.push(SnapshotAccount { pubkey, owner: None, data });
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // 递归展开JSON-RPC响应、数组和账户对象
+    fn collect_json(&mut self, value: &Value, fallback_pubkey: Option<Pubkey>) {
+        match value {
+            Value::Array(items) => {
+                for item in items {
+                    self.collect_json(item, None);
+                }
+            }
+            Value::Object(object) => {
+                if let Some(result) = object.get("result") {
+                    self.collect_json(result, fallback_pubkey);
+                } else if let Some(inner) = object.get("value") {
+                    // getAccountInfo的响应不带地址，账户不存在时value为null
+                    if !inner.is_null() {
+                        self.collect_json(inner, fallback_pubkey);
+                    }
+                } else if let Some(account) = object.get("account") {
+                    let pubkey = object
+                        .get("pubkey")
+                        .and_then(Value::as_str)
+                        .and_then(|key| Pubkey::from_str(key).ok())
+                        .or(fallback_pubkey);
+                    self.push_json_account(pubkey, account);
+                } else if object.contains_key("data") {
+                    self.push_json_account(fallback_pubkey, value);
+                } else {
+                    self.skipped += 1;
+                }
+            }
+            _ => self.skipped += 1,
+        }
+    }
+
+    fn push_json_account(&mut self, pubkey: Option<Pubkey>, account: &Value) {
+        let owner = account
+            .get("owner")
+            .and_then(Value::as_str)
+            .and_then(|owner| Pubkey::from_str(owner).ok());
+        // 只支持 ["<数据>", "base64"] 形式
+        let data = match account.get("data").and_then(Value::as_array).map(Vec::as_slice) {
+            Some([Value::String(data), Value::String(encoding)]) if encoding == "base64" => STANDARD.decode(data).ok(),
+            _ => None,
+        };
+
+        match (pubkey, data) {
+            (Some(pubkey), Some(data)) => self.accounts.push(SnapshotAccount { pubkey, owner, data }),
+            _ => self.skipped += 1,
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use solana_entry::entry::Entry;
+use solana_sdk::pubkey::Pubkey;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::scanner::filter_transactions;
+use crate::transaction::{group_by_accounts, group_transactions_by_accounts};
+
+// 单个过滤路径的耗时统计
+#[derive(Debug, Default)]
+struct PathTiming {
+    total: Duration,
+    matched: usize,
+}
+
+// 收集录制文件，参数可以是单个文件或目录
+pub fn collect_batch_files(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files: Vec<PathBuf> = fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "bin"))
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// 对比完整bincode解码和快速扫描两种过滤路径的耗时
+///
+/// 用法: `jito-shredstream-client bench-scan <录制文件或目录> [迭代次数]`，
+/// 录制文件由 `RECORD_DIR` 生成，目标账户沿用运行时配置。
+pub fn run_scan_benchmark(args: &[String], target_accounts: &[Pubkey]) -> Result<(), Box<dyn std::error::Error>> {
+    let path = args.first().ok_or("缺少录制文件或目录参数")?;
+    let iterations: u32 = match args.get(1) {
+        Some(value) => value.parse()?,
+        None => 100,
+    };
+
+    let batches = collect_batch_files(Path::new(path))?
+        .iter()
+        .map(fs::read)
+        .collect::<Result<Vec<_>, _>>()?;
+    if batches.is_empty() {
+        return Err(format!("{} 中没有录制的批次", path).into());
+    }
+
+    let total_bytes: usize = batches.iter().map(|b| b.len()).sum();
+    println!("批次数: {}, 总字节: {}, 迭代次数: {}", batches.len(), total_bytes, iterations);
+
+    let mut full = PathTiming::default();
+    let mut lazy = PathTiming::default();
+    let mut scan_failures = 0usize;
+
+    for _ in 0..iterations {
+        for data in &batches {
+            let start = Instant::now();
+            if let Ok(entries) = bincode::deserialize::<Vec<Entry>>(data) {
+                let grouped = group_transactions_by_accounts(&entries, target_accounts);
+                full.matched += grouped.values().map(Vec::len).sum::<usize>();
+            }
+            full.total += start.elapsed();
+
+            let start = Instant::now();
+            match filter_transactions(data, target_accounts) {
+                Ok(transactions) => {
+                    let grouped = group_by_accounts(&transactions, target_accounts);
+                    lazy.matched += grouped.values().map(Vec::len).sum::<usize>();
+                }
+                Err(_) => scan_failures += 1,
+            }
+            lazy.total += start.elapsed();
+        }
+    }
+
+    let runs = (batches.len() as u32 * iterations).max(1);
+    println!(
+        "完整解码: 总耗时 {:?}, 每批次 {:?}, 命中 {}",
+        full.total,
+        full.total / runs,
+        full.matched
+    );
+    println!(
+        "快速扫描: 总耗时 {:?}, 每批次 {:?}, 命中 {}, 扫描失败 {}",
+        lazy.total,
+        lazy.total / runs,
+        lazy.matched,
+        scan_failures
+    );
+    if !lazy.total.is_zero() {
+        println!("加速比: {:.2}x", full.total.as_secs_f64() / lazy.total.as_secs_f64());
+    }
+    if full.matched != lazy.matched {
+        println!("警告: 两种路径的命中数不一致");
+    }
+
+    Ok(())
+}
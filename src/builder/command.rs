@@ -0,0 +1,218 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use solana_sdk::{
+    hash::Hash,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Keypair},
+    signer::Signer,
+    transaction::VersionedTransaction,
+};
+use std::str::FromStr;
+use tracing::{info, warn};
+
+use crate::accounts::snapshot::AccountSnapshot;
+use crate::accounts::{decode_account, DecodedAccount, GlobalConfigAccount, PoolAccount};
+use crate::config::Config;
+use crate::transaction::pda::{bonding_curve, pump_global, pumpamm_global_config, pumpamm_lp_mint, pumpamm_pool};
+use crate::transaction::registry::DecoderRegistry;
+use crate::transaction::token_parser::TOKEN_PROGRAM_ID;
+use crate::transaction::write_transaction_info;
+
+use super::pump::PumpTradeBuilder;
+use super::pump_amm::PoolSwapBuilder;
+use super::{BuildError, ComputeBudgetPrefix, TransactionPrefix};
+
+const USAGE: &str = "用法: build <pump-buy | pump-sell> <mint> <代币数量> <SOL限额(lamports)> <blockhash> | build <amm-buy | amm-sell> <池地址> <基础代币数量> <报价代币限额> <blockhash>";
+
+fn parse_pubkey(value: &str, name: &str) -> Result<Pubkey, BuildError> {
+    Pubkey::from_str(value).map_err(|_| BuildError::InvalidArgument(format!("非法的{}: {}", name, value)))
+}
+
+fn parse_amount(value: &str, name: &str) -> Result<u64, BuildError> {
+    value
+        .parse()
+        .map_err(|_| BuildError::InvalidArgument(format!("非法的{}: {}", name, value)))
+}
+
+// ACCOUNT_SNAPSHOT中识别出的账户
+fn snapshot_accounts(config: &Config) -> Result<Vec<(Pubkey, DecodedAccount)>, Box<dyn std::error::Error>> {
+    let Some(path) = &config.account_snapshot else {
+        return Ok(Vec::new());
+    };
+    let snapshot = AccountSnapshot::load(path)?;
+    Ok(snapshot
+        .accounts
+        .iter()
+        .filter_map(|account| {
+            let decoded = decode_account(account.owner.as_ref(), &account.data)?.ok()?;
+            Some((account.pubkey, decoded))
+        })
+        .collect())
+}
+
+/// 构造并签名Pump/Pump AMM买卖交易
+///
+/// 用法: `jito-shredstream-client build <子命令> ...`，签名账户取自 `PAYER_KEYPAIR`，
+/// 输出一行base64编码的交易，可以直接写进 `bundle send` 的交易文件。
+/// Pump需要 `ACCOUNT_SNAPSHOT` 中有mint的曲线账户，手续费接收账户优先取快照中的Global账户；
+/// Pump AMM需要快照中有池账户和按PDA推导出的GlobalConfig账户。
+pub fn run_build_command(args: &[String], config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let [command, target, amount, limit, blockhash] = args else {
+        return Err(USAGE.into());
+    };
+    let keypair_path = config
+        .payer_keypair
+        .as_ref()
+        .ok_or_else(|| BuildError::MissingAccount("PAYER_KEYPAIR未设置".to_string()))?;
+    let payer = read_keypair_file(keypair_path)
+        .map_err(|e| BuildError::InvalidArgument(format!("无法读取{}: {}", keypair_path.display(), e)))?;
+    let recent_blockhash =
+        Hash::from_str(blockhash).map_err(|_| BuildError::InvalidArgument(format!("非法的blockhash: {}", blockhash)))?;
+    let accounts = snapshot_accounts(config)?;
+    let token_program: Pubkey = TOKEN_PROGRAM_ID.parse().expect("合法的Token程序ID");
+
+    let mut prefix = TransactionPrefix {
+        compute_budget: ComputeBudgetPrefix {
+            unit_limit: config.compute_unit_limit,
+            unit_price: config.compute_unit_price,
+        },
+        associated_accounts: Vec::new(),
+        tip: None,
+    };
+    if config.bundle_tip_lamports > 0 {
+        let tip_account = config
+            .jito_tip_accounts
+            .first()
+            .ok_or_else(|| BuildError::MissingAccount("JITO_TIP_ACCOUNTS为空，无法添加小费".to_string()))?;
+        prefix.tip = Some((*tip_account, config.bundle_tip_lamports));
+    }
+
+    let transaction = match command.as_str() {
+        "pump-buy" | "pump-sell" => {
+            let fee_recipient = accounts
+                .iter()
+                .find_map(|(pubkey, account)| match account {
+                    DecodedAccount::Global(global) if *pubkey == pump_global() => Some(global.fee_recipient),
+                    _ => None,
+                })
+                .unwrap_or(config.pump_fee_recipient);
+            let mint = parse_pubkey(target, "mint")?;
+            let builder = PumpTradeBuilder {
+                mint,
+                user: payer.pubkey(),
+                fee_recipient,
+                creator: curve_creator(&accounts, &mint)?,
+                token_program,
+                is_buy: command == "pump-buy",
+                token_amount: parse_amount(amount, "代币数量")?,
+                sol_limit: parse_amount(limit, "SOL限额")?,
+            };
+            // 买入前确保用户的代币账户存在
+            if builder.is_buy {
+                prefix.associated_accounts.push((builder.user, builder.mint, builder.token_program));
+            }
+            let transaction = prefix.build(&payer, builder.instruction(), recent_blockhash);
+            builder.verify(&transaction)?;
+            transaction
+        }
+        "amm-buy" | "amm-sell" => {
+            let pool = parse_pubkey(target, "池地址")?;
+            let builder = PoolSwapBuilder {
+                is_buy: command == "amm-buy",
+                base_amount: parse_amount(amount, "基础代币数量")?,
+                quote_limit: parse_amount(limit, "报价代币限额")?,
+                ..pool_swap_builder(&accounts, pool, &payer, token_program)?
+            };
+            // 买入时接收基础代币，卖出时接收报价代币
+            if builder.is_buy {
+                prefix.associated_accounts.push((builder.user, builder.base_mint, builder.base_token_program));
+            } else {
+                prefix.associated_accounts.push((builder.user, builder.quote_mint, builder.quote_token_program));
+            }
+            let transaction = prefix.build(&payer, builder.instruction(), recent_blockhash);
+            builder.verify(&transaction)?;
+            transaction
+        }
+        _ => return Err(USAGE.into()),
+    };
+    prefix.verify(&transaction)?;
+
+    print_transaction(&transaction)
+}
+
+// 从快照中的池账户和GlobalConfig账户准备兑换参数
+fn pool_swap_builder(
+    accounts: &[(Pubkey, DecodedAccount)],
+    pool: Pubkey,
+    payer: &Keypair,
+    token_program: Pubkey,
+) -> Result<PoolSwapBuilder, BuildError> {
+    let pool_account: &PoolAccount = accounts
+        .iter()
+        .find_map(|(pubkey, account)| match account {
+            DecodedAccount::Pool(account) if *pubkey == pool => Some(account),
+            _ => None,
+        })
+        .ok_or_else(|| BuildError::MissingAccount(format!("ACCOUNT_SNAPSHOT中没有池账户{}", pool)))?;
+    let global_config = pumpamm_global_config();
+    let config_account: &GlobalConfigAccount = accounts
+        .iter()
+        .find_map(|(pubkey, account)| match account {
+            DecodedAccount::GlobalConfig(account) if *pubkey == global_config => Some(account),
+            _ => None,
+        })
+        .ok_or_else(|| BuildError::MissingAccount(format!("ACCOUNT_SNAPSHOT中没有Pump AMM GlobalConfig账户{}", global_config)))?;
+    let protocol_fee_recipient = config_account
+        .protocol_fee_recipients
+        .iter()
+        .copied()
+        .find(|recipient| *recipient != Pubkey::default())
+        .ok_or_else(|| BuildError::MissingAccount("GlobalConfig中没有协议费接收地址".to_string()))?;
+
+    let derived = pumpamm_pool(
+        pool_account.index,
+        &pool_account.creator,
+        &pool_account.base_mint,
+        &pool_account.quote_mint,
+    );
+    if derived != pool || pool_account.lp_mint != pumpamm_lp_mint(&pool) {
+        warn!(pool = %pool, derived = %derived, "池地址或LP mint和按池账户内容推导的PDA不一致");
+    }
+
+    PoolSwapBuilder::from_pool(
+        pool,
+        pool_account,
+        global_config,
+        protocol_fee_recipient,
+        payer.pubkey(),
+        token_program,
+    )
+}
+
+// 快照中mint曲线账户记录的创建者，买卖都要用它推导创建者手续费金库
+fn curve_creator(accounts: &[(Pubkey, DecodedAccount)], mint: &Pubkey) -> Result<Pubkey, BuildError> {
+    let curve = bonding_curve(mint);
+    let account = accounts
+        .iter()
+        .find_map(|(pubkey, account)| match account {
+            DecodedAccount::BondingCurve(account) if *pubkey == curve => Some(account),
+            _ => None,
+        })
+        .ok_or_else(|| BuildError::MissingAccount(format!("ACCOUNT_SNAPSHOT中没有曲线账户{}", curve)))?;
+    account
+        .creator
+        .ok_or_else(|| BuildError::MissingAccount(format!("曲线账户{}缺少creator字段", curve)))
+}
+
+// stdout输出base64交易，stderr输出解析结果
+fn print_transaction(transaction: &VersionedTransaction) -> Result<(), Box<dyn std::error::Error>> {
+    let mut decoded = String::new();
+    write_transaction_info(&mut decoded, transaction, &DecoderRegistry::with_builtin())?;
+    eprint!("{}", decoded);
+    info!(
+        signature = %transaction.signatures[0],
+        instructions = transaction.message.instructions().len(),
+        "交易已构造并通过解析器校验"
+    );
+    println!("{}", STANDARD.encode(bincode::serialize(transaction)?));
+    Ok(())
+}
@@ -0,0 +1,206 @@
+// 构造Pump/Pump AMM买卖交易
+// 构造出的交易先经过本crate自己的解析器还原，和构造参数不一致时拒绝输出
+
+use solana_sdk::{
+    compute_budget::ComputeBudgetInstruction,
+    hash::Hash,
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    signature::Keypair,
+    signer::Signer,
+    system_instruction, system_program,
+    transaction::{Transaction, VersionedTransaction},
+};
+use std::fmt;
+
+use crate::transaction::ata_parser::{decode_ata_actions, ATA_PROGRAM_ID, CREATE_IDEMPOTENT_IX};
+use crate::transaction::compute_budget::ComputeBudget;
+use crate::transaction::pda::associated_token_address;
+use crate::transaction::tip::find_tips;
+
+pub mod command;
+pub mod pump;
+pub mod pump_amm;
+
+// 交易构造或校验失败原因
+#[derive(Debug)]
+pub enum BuildError {
+    // 缺少构造交易需要的账户
+    MissingAccount(String),
+    InvalidArgument(String),
+    // 解析器还原出的内容和构造参数不一致
+    RoundTrip(String),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::MissingAccount(account) => write!(f, "缺少账户: {}", account),
+            BuildError::InvalidArgument(error) => write!(f, "参数错误: {}", error),
+            BuildError::RoundTrip(error) => write!(f, "交易校验失败: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// 放在交易最前面的计算预算指令
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComputeBudgetPrefix {
+    pub unit_limit: Option<u32>,
+    // micro-lamports / 计算单元
+    pub unit_price: Option<u64>,
+}
+
+impl ComputeBudgetPrefix {
+    pub fn instructions(&self) -> Vec<Instruction> {
+        let mut instructions = Vec::new();
+        if let Some(units) = self.unit_limit {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+        }
+        if let Some(price) = self.unit_price {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+        }
+        instructions
+    }
+}
+
+/// 幂等创建关联代币账户，账户已存在时不报错
+///
+/// 账户顺序: funder, associatedAccount, wallet, mint, systemProgram, tokenProgram。
+pub fn create_associated_token_account_idempotent(
+    funder: &Pubkey,
+    wallet: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: ATA_PROGRAM_ID.parse().expect("合法的ATA程序ID"),
+        accounts: vec![
+            AccountMeta::new(*funder, true),
+            AccountMeta::new(associated_token_address(wallet, mint, token_program), false),
+            AccountMeta::new_readonly(*wallet, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        data: vec![CREATE_IDEMPOTENT_IX],
+    }
+}
+
+/// Anchor指令数据: discriminator + 两个小端u64参数
+pub fn anchor_instruction_data(discriminator: &[u8; 8], first: u64, second: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(24);
+    data.extend_from_slice(discriminator);
+    data.extend_from_slice(&first.to_le_bytes());
+    data.extend_from_slice(&second.to_le_bytes());
+    data
+}
+
+/// 确认交易中恰好有一条 `expected` 程序的顶层指令，且账户列表和 `expected` 逐个相同
+pub fn verify_instruction_accounts(transaction: &VersionedTransaction, expected: &Instruction) -> Result<(), BuildError> {
+    let static_keys = transaction.message.static_account_keys();
+    let mut matching = transaction
+        .message
+        .instructions()
+        .iter()
+        .filter(|instruction| instruction.program_id(static_keys) == &expected.program_id);
+    let (Some(instruction), None) = (matching.next(), matching.next()) else {
+        return Err(BuildError::RoundTrip(format!("交易中{}的指令不是恰好一条", expected.program_id)));
+    };
+
+    let actual: Vec<Option<Pubkey>> = instruction
+        .accounts
+        .iter()
+        .map(|index| static_keys.get(*index as usize).copied())
+        .collect();
+    let expected_keys: Vec<Option<Pubkey>> = expected.accounts.iter().map(|meta| Some(meta.pubkey)).collect();
+    if actual != expected_keys {
+        return Err(BuildError::RoundTrip(format!(
+            "指令账户列表不一致: 实际{}个 {:?}，期望{}个 {:?}",
+            actual.len(),
+            actual,
+            expected_keys.len(),
+            expected_keys
+        )));
+    }
+    Ok(())
+}
+
+/// 交易中除主指令外的部分: 计算预算、需要创建的关联代币账户和Jito小费
+#[derive(Debug, Clone, Default)]
+pub struct TransactionPrefix {
+    pub compute_budget: ComputeBudgetPrefix,
+    // 需要创建的关联代币账户 (钱包, mint, 代币程序)
+    pub associated_accounts: Vec<(Pubkey, Pubkey, Pubkey)>,
+    // 转给Jito小费账户的lamports，放在交易最后
+    pub tip: Option<(Pubkey, u64)>,
+}
+
+impl TransactionPrefix {
+    /// 按 计算预算 → 创建关联代币账户 → 主指令 → 小费 的顺序签名交易
+    pub fn build(
+        &self,
+        payer: &Keypair,
+        instruction: Instruction,
+        recent_blockhash: Hash,
+    ) -> VersionedTransaction {
+        let payer_pubkey = payer.pubkey();
+        let mut instructions = self.compute_budget.instructions();
+        for (wallet, mint, token_program) in &self.associated_accounts {
+            instructions.push(create_associated_token_account_idempotent(&payer_pubkey, wallet, mint, token_program));
+        }
+        instructions.push(instruction);
+        if let Some((tip_account, lamports)) = self.tip {
+            instructions.push(system_instruction::transfer(&payer_pubkey, &tip_account, lamports));
+        }
+
+        let message = Message::new_with_blockhash(&instructions, Some(&payer_pubkey), &recent_blockhash);
+        VersionedTransaction::from(Transaction::new(&[payer], message, recent_blockhash))
+    }
+
+    /// 用计算预算、ATA和小费解析器还原交易，确认和构造参数一致
+    pub fn verify(&self, transaction: &VersionedTransaction) -> Result<(), BuildError> {
+        let budget = ComputeBudget::from_transaction(transaction);
+        if budget.unit_limit != self.compute_budget.unit_limit
+            || budget.unit_price_micro_lamports != self.compute_budget.unit_price
+        {
+            return Err(BuildError::RoundTrip(format!(
+                "计算预算为 {:?}/{:?}，期望 {:?}/{:?}",
+                budget.unit_limit,
+                budget.unit_price_micro_lamports,
+                self.compute_budget.unit_limit,
+                self.compute_budget.unit_price
+            )));
+        }
+
+        let created = decode_ata_actions(transaction);
+        if created.len() != self.associated_accounts.len() {
+            return Err(BuildError::RoundTrip(format!(
+                "解析出{}条ATA创建指令，期望{}条",
+                created.len(),
+                self.associated_accounts.len()
+            )));
+        }
+        for (create, (wallet, mint, token_program)) in created.iter().zip(&self.associated_accounts) {
+            let expected = associated_token_address(wallet, mint, token_program);
+            if !create.idempotent
+                || create.associated_account != expected
+                || create.wallet != *wallet
+                || create.mint != *mint
+                || create.token_program != *token_program
+            {
+                return Err(BuildError::RoundTrip(format!("ATA创建指令不一致: {}", create)));
+            }
+        }
+
+        if let Some((tip_account, lamports)) = self.tip {
+            let tips = find_tips(transaction, &[tip_account]);
+            if tips.len() != 1 || tips[0].lamports != lamports {
+                return Err(BuildError::RoundTrip(format!("小费转账不一致: {:?}", tips)));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+    transaction::VersionedTransaction,
+};
+
+use crate::transaction::pda::{
+    associated_bonding_curve, associated_token_address, bonding_curve, event_authority, fee_config,
+    global_volume_accumulator, pump_creator_vault, pump_fee_program_id, pump_global, pump_program_id,
+    user_volume_accumulator,
+};
+use crate::transaction::pump_parser::{decode_pump_actions, PumpAction, BUY_DISCRIMINATOR, SELL_DISCRIMINATOR};
+
+use super::{anchor_instruction_data, verify_instruction_accounts, BuildError};
+
+/// Pump曲线上的一笔买入或卖出
+#[derive(Debug, Clone)]
+pub struct PumpTradeBuilder {
+    pub mint: Pubkey,
+    pub user: Pubkey,
+    // Global账户中的fee_recipient
+    pub fee_recipient: Pubkey,
+    // BondingCurve账户中的creator，决定创建者手续费金库
+    pub creator: Pubkey,
+    pub token_program: Pubkey,
+    pub is_buy: bool,
+    // 买入时为期望得到的代币数量，卖出时为卖出的代币数量
+    pub token_amount: u64,
+    // 买入时为最大SOL成本，卖出时为最小SOL收益 (lamports)
+    pub sol_limit: u64,
+}
+
+impl PumpTradeBuilder {
+    /// 用户持有该代币的关联代币账户
+    pub fn associated_user(&self) -> Pubkey {
+        associated_token_address(&self.user, &self.mint, &self.token_program)
+    }
+
+    pub fn creator_vault(&self) -> Pubkey {
+        pump_creator_vault(&self.creator)
+    }
+
+    /// 账户顺序和当前IDL一致，buy共16个账户:
+    /// global, feeRecipient, mint, bondingCurve, associatedBondingCurve, associatedUser, user, systemProgram,
+    /// tokenProgram, creatorVault, eventAuthority, program, globalVolumeAccumulator, userVolumeAccumulator,
+    /// feeConfig, feeProgram；sell共14个账户，creatorVault在tokenProgram之前，没有两个交易量累计账户。
+    pub fn instruction(&self) -> Instruction {
+        let program_id = pump_program_id();
+        let mut accounts = vec![
+            AccountMeta::new_readonly(pump_global(), false),
+            AccountMeta::new(self.fee_recipient, false),
+            AccountMeta::new_readonly(self.mint, false),
+            AccountMeta::new(bonding_curve(&self.mint), false),
+            AccountMeta::new(associated_bonding_curve(&self.mint, &self.token_program), false),
+            AccountMeta::new(self.associated_user(), false),
+            AccountMeta::new(self.user, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        if self.is_buy {
+            accounts.push(AccountMeta::new_readonly(self.token_program, false));
+            accounts.push(AccountMeta::new(self.creator_vault(), false));
+        } else {
+            accounts.push(AccountMeta::new(self.creator_vault(), false));
+            accounts.push(AccountMeta::new_readonly(self.token_program, false));
+        }
+        accounts.push(AccountMeta::new_readonly(event_authority(&program_id), false));
+        accounts.push(AccountMeta::new_readonly(program_id, false));
+        if self.is_buy {
+            accounts.push(AccountMeta::new(global_volume_accumulator(&program_id), false));
+            accounts.push(AccountMeta::new(user_volume_accumulator(&program_id, &self.user), false));
+        }
+        accounts.push(AccountMeta::new_readonly(fee_config(&program_id), false));
+        accounts.push(AccountMeta::new_readonly(pump_fee_program_id(), false));
+
+        let discriminator = if self.is_buy { &BUY_DISCRIMINATOR } else { &SELL_DISCRIMINATOR };
+        Instruction {
+            program_id,
+            accounts,
+            data: anchor_instruction_data(discriminator, self.token_amount, self.sol_limit),
+        }
+    }
+
+    /// 用 `decode_pump_actions` 还原交易，确认只有这一笔交易、参数和账户列表一致
+    pub fn verify(&self, transaction: &VersionedTransaction) -> Result<(), BuildError> {
+        verify_instruction_accounts(transaction, &self.instruction())?;
+        let actions = decode_pump_actions(transaction);
+        let [PumpAction::Trade(trade)] = actions.as_slice() else {
+            return Err(BuildError::RoundTrip(format!("解析出的Pump操作为 {:?}", actions)));
+        };
+        if trade.mint != self.mint
+            || trade.bonding_curve != bonding_curve(&self.mint)
+            || trade.user != self.user
+            || trade.creator_vault != Some(self.creator_vault())
+            || trade.is_buy != self.is_buy
+            || trade.token_amount != self.token_amount
+            || trade.sol_limit != self.sol_limit
+        {
+            return Err(BuildError::RoundTrip(format!("Pump交易不一致: {:?}", trade)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::TransactionPrefix;
+    use crate::transaction::token_parser::TOKEN_PROGRAM_ID;
+    use solana_sdk::{hash::Hash, signature::Keypair, signer::Signer};
+
+    fn builder(user: Pubkey, is_buy: bool) -> PumpTradeBuilder {
+        PumpTradeBuilder {
+            mint: Pubkey::new_unique(),
+            user,
+            fee_recipient: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            token_program: TOKEN_PROGRAM_ID.parse().expect("合法的Token程序ID"),
+            is_buy,
+            token_amount: 1_000_000,
+            sol_limit: 50_000_000,
+        }
+    }
+
+    #[test]
+    fn buy_round_trip() {
+        let payer = Keypair::new();
+        let builder = builder(payer.pubkey(), true);
+        let instruction = builder.instruction();
+        assert_eq!(instruction.accounts.len(), 16);
+        let program_id = pump_program_id();
+        assert_eq!(instruction.accounts[12], AccountMeta::new(global_volume_accumulator(&program_id), false));
+        assert_eq!(
+            instruction.accounts[13],
+            AccountMeta::new(user_volume_accumulator(&program_id, &payer.pubkey()), false)
+        );
+        assert_eq!(
+            instruction.accounts[14].pubkey.to_string(),
+            "8Wf5TiAheLUqBrKXeYg2JtAFFMWtKdG2BSFgqUcPVwTt"
+        );
+        assert_eq!(instruction.accounts[15].pubkey, pump_fee_program_id());
+
+        let prefix = TransactionPrefix::default();
+        let transaction = prefix.build(&payer, instruction, Hash::default());
+        prefix.verify(&transaction).expect("前缀校验");
+        builder.verify(&transaction).expect("买入校验");
+    }
+
+    #[test]
+    fn sell_round_trip() {
+        let payer = Keypair::new();
+        let builder = builder(payer.pubkey(), false);
+        let instruction = builder.instruction();
+        assert_eq!(instruction.accounts.len(), 14);
+        assert_eq!(instruction.accounts[8], AccountMeta::new(builder.creator_vault(), false));
+        assert_eq!(instruction.accounts[12].pubkey, fee_config(&pump_program_id()));
+        assert_eq!(instruction.accounts[13].pubkey, pump_fee_program_id());
+
+        let transaction = TransactionPrefix::default().build(&payer, instruction, Hash::default());
+        builder.verify(&transaction).expect("卖出校验");
+    }
+
+    #[test]
+    fn verify_rejects_missing_fee_accounts() {
+        let payer = Keypair::new();
+        let builder = builder(payer.pubkey(), true);
+        let mut instruction = builder.instruction();
+        instruction.accounts.truncate(12);
+
+        let transaction = TransactionPrefix::default().build(&payer, instruction, Hash::default());
+        // 解析器只看前12个账户，账户列表比对能发现缺少的账户
+        assert!(matches!(builder.verify(&transaction), Err(BuildError::RoundTrip(_))));
+    }
+}
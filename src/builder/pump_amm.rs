@@ -0,0 +1,239 @@
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+    transaction::VersionedTransaction,
+};
+
+use crate::accounts::PoolAccount;
+use crate::transaction::ata_parser::ATA_PROGRAM_ID;
+use crate::transaction::pda::{
+    associated_token_address, event_authority, fee_config, global_volume_accumulator, pump_fee_program_id,
+    pumpamm_coin_creator_vault_authority, pumpamm_program_id, user_volume_accumulator,
+};
+use crate::transaction::pumpamm_parser::{decode_pumpamm_actions, PumpAmmAction, BUY_IX, SELL_IX};
+
+use super::{anchor_instruction_data, verify_instruction_accounts, BuildError};
+
+/// Pump AMM池中的一笔买入或卖出基础代币
+#[derive(Debug, Clone)]
+pub struct PoolSwapBuilder {
+    pub pool: Pubkey,
+    pub global_config: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub pool_base_token_account: Pubkey,
+    pub pool_quote_token_account: Pubkey,
+    // Pool账户中的coin_creator，决定创建者手续费金库
+    pub coin_creator: Pubkey,
+    pub user: Pubkey,
+    // GlobalConfig中的协议费接收地址之一
+    pub protocol_fee_recipient: Pubkey,
+    pub base_token_program: Pubkey,
+    pub quote_token_program: Pubkey,
+    pub is_buy: bool,
+    // 买入时为期望得到的基础代币数量，卖出时为卖出的基础代币数量
+    pub base_amount: u64,
+    // 买入时为最大报价代币输入，卖出时为最小报价代币输出
+    pub quote_limit: u64,
+}
+
+impl PoolSwapBuilder {
+    /// 从池账户取mint、金库和代币创建者，代币程序需要调用方确认
+    ///
+    /// creator fee更新前的池账户没有coin_creator，无法推导创建者手续费金库。
+    pub fn from_pool(
+        pool: Pubkey,
+        account: &PoolAccount,
+        global_config: Pubkey,
+        protocol_fee_recipient: Pubkey,
+        user: Pubkey,
+        token_program: Pubkey,
+    ) -> Result<Self, BuildError> {
+        let coin_creator = account
+            .coin_creator
+            .ok_or_else(|| BuildError::MissingAccount(format!("池账户{}缺少coin_creator字段", pool)))?;
+        Ok(Self {
+            pool,
+            global_config,
+            base_mint: account.base_mint,
+            quote_mint: account.quote_mint,
+            pool_base_token_account: account.pool_base_token_account,
+            pool_quote_token_account: account.pool_quote_token_account,
+            coin_creator,
+            user,
+            protocol_fee_recipient,
+            base_token_program: token_program,
+            quote_token_program: token_program,
+            is_buy: true,
+            base_amount: 0,
+            quote_limit: 0,
+        })
+    }
+
+    pub fn user_base_token_account(&self) -> Pubkey {
+        associated_token_address(&self.user, &self.base_mint, &self.base_token_program)
+    }
+
+    pub fn user_quote_token_account(&self) -> Pubkey {
+        associated_token_address(&self.user, &self.quote_mint, &self.quote_token_program)
+    }
+
+    pub fn coin_creator_vault_authority(&self) -> Pubkey {
+        pumpamm_coin_creator_vault_authority(&self.coin_creator)
+    }
+
+    /// 创建者手续费金库: 权限账户持有报价代币的关联代币账户
+    pub fn coin_creator_vault_ata(&self) -> Pubkey {
+        associated_token_address(&self.coin_creator_vault_authority(), &self.quote_mint, &self.quote_token_program)
+    }
+
+    /// 账户顺序和当前IDL一致，buy和sell的前19个账户相同:
+    /// pool, user, globalConfig, baseMint, quoteMint, userBase, userQuote, poolBase, poolQuote,
+    /// protocolFeeRecipient, protocolFeeRecipientTokenAccount, baseTokenProgram, quoteTokenProgram,
+    /// systemProgram, associatedTokenProgram, eventAuthority, program, coinCreatorVaultAta, coinCreatorVaultAuthority；
+    /// buy之后是globalVolumeAccumulator, userVolumeAccumulator, feeConfig, feeProgram共23个，
+    /// sell之后只有feeConfig, feeProgram共21个。
+    pub fn instruction(&self) -> Instruction {
+        let program_id = pumpamm_program_id();
+        let protocol_fee_recipient_token_account =
+            associated_token_address(&self.protocol_fee_recipient, &self.quote_mint, &self.quote_token_program);
+        let mut accounts = vec![
+            AccountMeta::new_readonly(self.pool, false),
+            AccountMeta::new(self.user, true),
+            AccountMeta::new_readonly(self.global_config, false),
+            AccountMeta::new_readonly(self.base_mint, false),
+            AccountMeta::new_readonly(self.quote_mint, false),
+            AccountMeta::new(self.user_base_token_account(), false),
+            AccountMeta::new(self.user_quote_token_account(), false),
+            AccountMeta::new(self.pool_base_token_account, false),
+            AccountMeta::new(self.pool_quote_token_account, false),
+            AccountMeta::new_readonly(self.protocol_fee_recipient, false),
+            AccountMeta::new(protocol_fee_recipient_token_account, false),
+            AccountMeta::new_readonly(self.base_token_program, false),
+            AccountMeta::new_readonly(self.quote_token_program, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(ATA_PROGRAM_ID.parse().expect("合法的ATA程序ID"), false),
+            AccountMeta::new_readonly(event_authority(&program_id), false),
+            AccountMeta::new_readonly(program_id, false),
+            AccountMeta::new(self.coin_creator_vault_ata(), false),
+            AccountMeta::new_readonly(self.coin_creator_vault_authority(), false),
+        ];
+        if self.is_buy {
+            accounts.push(AccountMeta::new(global_volume_accumulator(&program_id), false));
+            accounts.push(AccountMeta::new(user_volume_accumulator(&program_id, &self.user), false));
+        }
+        accounts.push(AccountMeta::new_readonly(fee_config(&program_id), false));
+        accounts.push(AccountMeta::new_readonly(pump_fee_program_id(), false));
+
+        let discriminator = if self.is_buy { &BUY_IX } else { &SELL_IX };
+        Instruction {
+            program_id,
+            accounts,
+            data: anchor_instruction_data(discriminator, self.base_amount, self.quote_limit),
+        }
+    }
+
+    /// 用 `decode_pumpamm_actions` 还原交易，确认只有这一笔兑换、参数和账户列表一致
+    pub fn verify(&self, transaction: &VersionedTransaction) -> Result<(), BuildError> {
+        verify_instruction_accounts(transaction, &self.instruction())?;
+        let actions = decode_pumpamm_actions(transaction);
+        let [PumpAmmAction::Swap(swap)] = actions.as_slice() else {
+            return Err(BuildError::RoundTrip(format!("解析出的Pump AMM操作为 {:?}", actions)));
+        };
+        if swap.pool != self.pool
+            || swap.user != self.user
+            || swap.base_mint != self.base_mint
+            || swap.quote_mint != self.quote_mint
+            || swap.coin_creator_vault_authority != Some(self.coin_creator_vault_authority())
+            || swap.is_buy != self.is_buy
+            || swap.base_amount != self.base_amount
+            || swap.quote_limit != self.quote_limit
+        {
+            return Err(BuildError::RoundTrip(format!("Pump AMM兑换不一致: {:?}", swap)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::TransactionPrefix;
+    use crate::transaction::pda::pumpamm_global_config;
+    use crate::transaction::swap::WSOL_MINT;
+    use crate::transaction::token_parser::TOKEN_PROGRAM_ID;
+    use solana_sdk::{hash::Hash, signature::Keypair, signer::Signer};
+
+    fn builder(user: Pubkey, is_buy: bool) -> PoolSwapBuilder {
+        let token_program: Pubkey = TOKEN_PROGRAM_ID.parse().expect("合法的Token程序ID");
+        PoolSwapBuilder {
+            pool: Pubkey::new_unique(),
+            global_config: pumpamm_global_config(),
+            base_mint: Pubkey::new_unique(),
+            quote_mint: WSOL_MINT.parse().expect("合法的WSOL Mint"),
+            pool_base_token_account: Pubkey::new_unique(),
+            pool_quote_token_account: Pubkey::new_unique(),
+            coin_creator: Pubkey::new_unique(),
+            user,
+            protocol_fee_recipient: Pubkey::new_unique(),
+            base_token_program: token_program,
+            quote_token_program: token_program,
+            is_buy,
+            base_amount: 2_000_000,
+            quote_limit: 30_000_000,
+        }
+    }
+
+    #[test]
+    fn buy_round_trip() {
+        let payer = Keypair::new();
+        let builder = builder(payer.pubkey(), true);
+        let instruction = builder.instruction();
+        assert_eq!(instruction.accounts.len(), 23);
+        let program_id = pumpamm_program_id();
+        assert_eq!(
+            instruction.accounts[19],
+            AccountMeta::new("C2aFPdENg4A2HQsmrd5rTw5TaYBX5Ku887cWjbFKtZpw".parse().expect("合法的地址"), false)
+        );
+        assert_eq!(
+            instruction.accounts[20],
+            AccountMeta::new(user_volume_accumulator(&program_id, &payer.pubkey()), false)
+        );
+        assert_eq!(
+            instruction.accounts[21].pubkey.to_string(),
+            "5PHirr8joyTMp9JMm6nW7hNDVyEYdkzDqazxPD7RaTjx"
+        );
+        assert_eq!(instruction.accounts[22].pubkey, pump_fee_program_id());
+
+        let prefix = TransactionPrefix::default();
+        let transaction = prefix.build(&payer, instruction, Hash::default());
+        prefix.verify(&transaction).expect("前缀校验");
+        builder.verify(&transaction).expect("买入校验");
+    }
+
+    #[test]
+    fn sell_round_trip() {
+        let payer = Keypair::new();
+        let builder = builder(payer.pubkey(), false);
+        let instruction = builder.instruction();
+        assert_eq!(instruction.accounts.len(), 21);
+        assert_eq!(instruction.accounts[18].pubkey, builder.coin_creator_vault_authority());
+        assert_eq!(instruction.accounts[19].pubkey, fee_config(&pumpamm_program_id()));
+        assert_eq!(instruction.accounts[20].pubkey, pump_fee_program_id());
+
+        let transaction = TransactionPrefix::default().build(&payer, instruction, Hash::default());
+        builder.verify(&transaction).expect("卖出校验");
+    }
+
+    #[test]
+    fn verify_rejects_other_user_volume_accumulator() {
+        let payer = Keypair::new();
+        let builder = builder(payer.pubkey(), true);
+        let mut instruction = builder.instruction();
+        instruction.accounts[20].pubkey = user_volume_accumulator(&pumpamm_program_id(), &Pubkey::new_unique());
+
+        let transaction = TransactionPrefix::default().build(&payer, instruction, Hash::default());
+        assert!(matches!(builder.verify(&transaction), Err(BuildError::RoundTrip(_))));
+    }
+}
@@ -0,0 +1,314 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use jito_protos::bundle::BundleResult;
+use solana_sdk::{pubkey::Pubkey, transaction::VersionedTransaction};
+use std::fs;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::client::ShredstreamClient;
+use crate::config::{BundleTransport, Config};
+use crate::decoder::{decode_entries, Quarantine};
+use crate::scanner::filter_transactions;
+use crate::transaction::tip::find_tips;
+
+use super::json_rpc::{region_url, select_fastest_region, JsonRpcClient, BLOCK_ENGINE_REGIONS};
+use super::tracker::BundleTracker;
+use super::{describe_bundle_result, SearcherClient};
+
+// 启动时刷新小费账户的超时
+const TIP_REFRESH_TIMEOUT: Duration = Duration::from_secs(10);
+
+const USAGE: &str = "用法: bundle <tip-accounts | next-leader [区域...] | send <交易文件> | track <交易文件> | status <bundle ID...> | results>";
+
+// 交易文件每行一笔base64编码的bincode序列化交易，空行和#开头的行忽略
+fn load_transactions(path: &str) -> Result<Vec<VersionedTransaction>, Box<dyn std::error::Error>> {
+    let text = fs::read_to_string(path)?;
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let data = STANDARD.decode(line)?;
+            Ok(bincode::deserialize::<VersionedTransaction>(&data)?)
+        })
+        .collect()
+}
+
+// 按 BLOCK_ENGINE_REGION 确定Block Engine地址，未设置时使用 BLOCK_ENGINE_URL
+async fn resolve_block_engine_url(config: &Config) -> String {
+    match config.block_engine_region.as_deref() {
+        Some(region) if region.eq_ignore_ascii_case("auto") => {
+            match select_fastest_region(&BLOCK_ENGINE_REGIONS, config.block_engine_rps).await {
+                Some(url) => url,
+                None => {
+                    warn!(block_engine = %config.block_engine_url, "所有区域探测失败，使用默认地址");
+                    config.block_engine_url.clone()
+                }
+            }
+        }
+        Some(region) => region_url(region.trim()),
+        None => config.block_engine_url.clone(),
+    }
+}
+
+/// 启动时从Block Engine刷新小费账户，和内置列表不同时记录差异，失败时继续使用内置列表
+pub async fn refresh_tip_accounts(config: &mut Config) {
+    if !config.refresh_tip_accounts {
+        return;
+    }
+    let block_engine_url = resolve_block_engine_url(config).await;
+    let fetch = async {
+        match config.bundle_transport {
+            BundleTransport::JsonRpc => JsonRpcClient::new(&block_engine_url, config.block_engine_rps)
+                .get_tip_accounts()
+                .await
+                .map_err(|e| e.to_string()),
+            BundleTransport::Grpc => match SearcherClient::connect(block_engine_url.clone()).await {
+                Ok(mut client) => client.tip_accounts().await.map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            },
+        }
+    };
+    let fetched = match tokio::time::timeout(TIP_REFRESH_TIMEOUT, fetch).await {
+        Ok(Ok(accounts)) if !accounts.is_empty() => accounts,
+        Ok(Ok(_)) => {
+            warn!(block_engine = %block_engine_url, "Block Engine返回的小费账户为空，使用内置列表");
+            return;
+        }
+        Ok(Err(e)) => {
+            warn!(block_engine = %block_engine_url, error = %e, "刷新小费账户失败，使用内置列表");
+            return;
+        }
+        Err(_) => {
+            warn!(block_engine = %block_engine_url, "刷新小费账户超时，使用内置列表");
+            return;
+        }
+    };
+
+    let added: Vec<&Pubkey> = fetched.iter().filter(|account| !config.jito_tip_accounts.contains(account)).collect();
+    let removed: Vec<&Pubkey> = config.jito_tip_accounts.iter().filter(|account| !fetched.contains(account)).collect();
+    if added.is_empty() && removed.is_empty() {
+        info!(block_engine = %block_engine_url, accounts = fetched.len(), "小费账户与内置列表一致");
+    } else {
+        warn!(
+            block_engine = %block_engine_url,
+            added = ?added,
+            removed = ?removed,
+            "Block Engine返回的小费账户与内置列表不同，使用返回的列表"
+        );
+    }
+    config.jito_tip_accounts = fetched;
+}
+
+/// 和Block Engine交互
+///
+/// 用法: `jito-shredstream-client bundle <子命令>`，地址取自 `BLOCK_ENGINE_URL`/`BLOCK_ENGINE_REGION`，可以指向本地mock服务；
+/// `BUNDLE_TRANSPORT` 决定 `tip-accounts` 和 `send` 使用gRPC还是JSON-RPC:
+/// - `tip-accounts`: 输出当前的小费账户
+/// - `next-leader [区域...]`: 输出下一个Jito leader (仅gRPC)
+/// - `send <交易文件>`: 把文件中的交易按顺序作为一个bundle提交，输出bundle ID
+/// - `track <交易文件>`: 提交bundle，同时订阅bundle结果和Entry流，输出bundle的生命周期
+/// - `status <bundle ID...>`: 查询bundle是否上链 (仅JSON-RPC)
+/// - `results`: 订阅并逐行输出bundle结果，直到流结束 (仅gRPC)
+pub async fn run_bundle_command(args: &[String], config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let command = args.first().ok_or(USAGE)?;
+    let block_engine_url = resolve_block_engine_url(config).await;
+    if command == "track" {
+        return track_bundle(args, config, block_engine_url).await;
+    }
+    let transport = match command.as_str() {
+        "next-leader" | "results" => BundleTransport::Grpc,
+        "status" => BundleTransport::JsonRpc,
+        _ => config.bundle_transport,
+    };
+
+    if transport == BundleTransport::JsonRpc {
+        let client = JsonRpcClient::new(&block_engine_url, config.block_engine_rps);
+        info!(endpoint = %client.endpoint(), "使用JSON-RPC接口");
+        match command.as_str() {
+            "tip-accounts" => {
+                for account in client.get_tip_accounts().await? {
+                    println!("{}", account);
+                }
+            }
+            "send" => {
+                let transactions = load_bundle_file(args, config)?;
+                println!("{}", client.send_bundle(&transactions).await?);
+            }
+            "status" => {
+                let bundle_ids = &args[1..];
+                if bundle_ids.is_empty() {
+                    return Err(USAGE.into());
+                }
+                let statuses = client.get_bundle_statuses(bundle_ids).await?;
+                println!("查询slot: {}", statuses.context_slot);
+                for (bundle_id, status) in bundle_ids.iter().zip(&statuses.statuses) {
+                    match status {
+                        Some(status) => println!(
+                            "bundle {}: slot={}, 确认状态={}, {}, 交易数={}",
+                            status.bundle_id,
+                            status.slot,
+                            status.confirmation_status.as_deref().unwrap_or("未知"),
+                            if status.is_ok() { "成功".to_string() } else { format!("错误={}", status.err) },
+                            status.transactions.len()
+                        ),
+                        None => println!("bundle {}: 未找到或未上链", bundle_id),
+                    }
+                }
+            }
+            _ => return Err(USAGE.into()),
+        }
+        return Ok(());
+    }
+
+    let mut client = SearcherClient::connect(block_engine_url).await?;
+    match command.as_str() {
+        "tip-accounts" => {
+            for account in client.tip_accounts().await? {
+                println!("{}", account);
+            }
+        }
+        "next-leader" => {
+            let leader = client.next_scheduled_leader(&args[1..]).await?;
+            println!(
+                "当前slot: {}, 下一个leader: {} (slot {}, 还有{}个slot, 区域 {})",
+                leader.current_slot,
+                leader.next_leader_identity,
+                leader.next_leader_slot,
+                leader.slots_until_leader(),
+                leader.next_leader_region
+            );
+        }
+        "send" => {
+            let transactions = load_bundle_file(args, config)?;
+            println!("{}", client.send_bundle(&transactions).await?);
+        }
+        "results" => {
+            let mut stream = client.subscribe_bundle_results().await?;
+            while let Some(result) = stream.message().await? {
+                println!("{}", describe_bundle_result(&result));
+            }
+            warn!(block_engine = %client.block_engine_url(), "bundle结果流已结束");
+        }
+        _ => return Err(USAGE.into()),
+    }
+    Ok(())
+}
+
+// 读取send的交易文件，没有小费转账时给出警告
+fn load_bundle_file(args: &[String], config: &Config) -> Result<Vec<VersionedTransaction>, Box<dyn std::error::Error>> {
+    let path = args.get(1).ok_or(USAGE)?;
+    let transactions = load_transactions(path)?;
+    let tipped = transactions
+        .iter()
+        .any(|transaction| !find_tips(transaction, &config.jito_tip_accounts).is_empty());
+    if !tipped {
+        warn!(path = %path, "bundle中没有转给Jito小费账户的交易，很可能不会被接受");
+    }
+    Ok(transactions)
+}
+
+// 等待下一个bundle结果，没有订阅时永远挂起
+async fn next_result(
+    stream: &mut Option<tonic::Streaming<BundleResult>>,
+) -> Result<Option<BundleResult>, tonic::Status> {
+    match stream {
+        Some(stream) => stream.message().await,
+        None => std::future::pending().await,
+    }
+}
+
+// 批次中由bundle交易的付款账户签名的交易，扫描失败时回退到完整解码
+fn payer_transactions(quarantine: &Quarantine, slot: u64, data: &[u8], payers: &[Pubkey]) -> Vec<VersionedTransaction> {
+    match filter_transactions(data, payers) {
+        Ok(transactions) => transactions,
+        Err(_) => quarantine
+            .accept(slot, data, decode_entries(data))
+            .into_iter()
+            .flat_map(|entry| entry.transactions)
+            .collect(),
+    }
+}
+
+// 提交bundle，关联Block Engine结果和Entry流直到bundle不需要再跟踪
+async fn track_bundle(args: &[String], config: &Config, block_engine_url: String) -> Result<(), Box<dyn std::error::Error>> {
+    let transactions = load_bundle_file(args, config)?;
+    let payers: Vec<Pubkey> = transactions
+        .iter()
+        .filter_map(|transaction| transaction.message.static_account_keys().first().copied())
+        .collect();
+
+    // 先订阅再提交，避免错过最早的结果和Entry
+    let shredstream = ShredstreamClient::new(config.server_url.clone());
+    let mut shredstream_client = shredstream.connect().await?;
+    let mut entries = shredstream.subscribe_entries(&mut shredstream_client).await?;
+
+    let mut searcher = match SearcherClient::connect(block_engine_url.clone()).await {
+        Ok(searcher) => Some(searcher),
+        Err(e) => {
+            warn!(error = %e, "无法连接searcher服务，只根据Entry流跟踪");
+            None
+        }
+    };
+    let mut results = match searcher.as_mut() {
+        Some(searcher) => match searcher.subscribe_bundle_results().await {
+            Ok(results) => Some(results),
+            Err(e) => {
+                warn!(error = %e, "订阅bundle结果失败，只根据Entry流跟踪");
+                None
+            }
+        },
+        None => None,
+    };
+
+    let bundle_id = match (config.bundle_transport, searcher.as_mut()) {
+        (BundleTransport::Grpc, Some(searcher)) => searcher.send_bundle(&transactions).await?,
+        (BundleTransport::Grpc, None) => return Err("无法连接searcher服务，不能通过gRPC提交bundle".into()),
+        (BundleTransport::JsonRpc, _) => {
+            JsonRpcClient::new(&block_engine_url, config.block_engine_rps)
+                .send_bundle(&transactions)
+                .await?
+        }
+    };
+    println!("{}", bundle_id);
+
+    let mut tracker = BundleTracker::default();
+    tracker.track(bundle_id.clone(), &transactions);
+    let quarantine = Quarantine::new(None);
+
+    while !tracker.is_settled() {
+        tokio::select! {
+            result = next_result(&mut results) => match result {
+                Ok(Some(result)) => {
+                    if let Some(lifecycle) = tracker.on_result(&result) {
+                        lifecycle.log();
+                    }
+                }
+                Ok(None) | Err(_) => {
+                    warn!(block_engine = %block_engine_url, "bundle结果流已结束，只根据Entry流跟踪");
+                    results = None;
+                }
+            },
+            entry = entries.message() => match entry {
+                Ok(Some(entry)) => {
+                    let transactions = payer_transactions(&quarantine, entry.slot, &entry.entries, &payers);
+                    for changed in tracker.on_entries(entry.slot, &transactions) {
+                        if let Some(lifecycle) = tracker.get(&changed) {
+                            lifecycle.log();
+                        }
+                    }
+                }
+                Ok(None) | Err(_) => {
+                    warn!(upstream = %config.server_url, "Entry流已结束，停止跟踪");
+                    break;
+                }
+            },
+        }
+    }
+
+    if let Some(lifecycle) = tracker.get(&bundle_id) {
+        let mut out = String::new();
+        lifecycle.write(&mut out)?;
+        print!("{}", out);
+    }
+    Ok(())
+}
@@ -0,0 +1,484 @@
+use futures_util::future::join_all;
+use serde_json::{json, Value};
+use solana_sdk::{bs58, pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{sleep_until, Instant};
+use tracing::{debug, info, instrument, warn};
+
+use super::{build_bundle, BundleError, MAX_BUNDLE_TRANSACTIONS};
+
+// Block Engine的JSON-RPC路径，和gRPC使用同一个主机
+const BUNDLES_PATH: &str = "/api/v1/bundles";
+
+// 主网Block Engine区域，地址为 https://<区域>.mainnet.block-engine.jito.wtf
+pub const BLOCK_ENGINE_REGIONS: [&str; 8] = [
+    "amsterdam",
+    "dublin",
+    "frankfurt",
+    "london",
+    "ny",
+    "slc",
+    "singapore",
+    "tokyo",
+];
+
+/// 区域对应的Block Engine地址
+pub fn region_url(region: &str) -> String {
+    format!("https://{}.mainnet.block-engine.jito.wtf", region)
+}
+
+// JSON-RPC请求失败原因
+#[derive(Debug)]
+pub enum JsonRpcError {
+    Http(reqwest::Error),
+    // 非2xx响应的状态码和响应体
+    Status(u16, String),
+    // 429，超出Block Engine的频率限制
+    RateLimited,
+    Rpc { code: i64, message: String },
+    InvalidResponse(String),
+    // getBundleStatuses一次查询的bundle ID超过上限
+    TooManyBundleIds(usize),
+    Bundle(BundleError),
+}
+
+impl fmt::Display for JsonRpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonRpcError::Http(error) => write!(f, "HTTP请求失败: {}", error),
+            JsonRpcError::Status(status, body) => write!(f, "Block Engine返回HTTP {}: {}", status, body),
+            JsonRpcError::RateLimited => write!(f, "超出Block Engine的请求频率限制"),
+            JsonRpcError::Rpc { code, message } => write!(f, "JSON-RPC错误 {}: {}", code, message),
+            JsonRpcError::InvalidResponse(error) => write!(f, "无法解析JSON-RPC响应: {}", error),
+            JsonRpcError::TooManyBundleIds(count) => {
+                write!(f, "一次最多查询{}个bundle，实际为{}个", MAX_BUNDLE_TRANSACTIONS, count)
+            }
+            JsonRpcError::Bundle(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for JsonRpcError {}
+
+impl From<reqwest::Error> for JsonRpcError {
+    fn from(error: reqwest::Error) -> Self {
+        JsonRpcError::Http(error)
+    }
+}
+
+impl From<BundleError> for JsonRpcError {
+    fn from(error: BundleError) -> Self {
+        JsonRpcError::Bundle(error)
+    }
+}
+
+/// getBundleStatuses中已上链的bundle
+#[derive(Debug, Clone)]
+pub struct BundleStatus {
+    pub bundle_id: String,
+    pub transactions: Vec<Signature>,
+    pub slot: u64,
+    // processed、confirmed或finalized
+    pub confirmation_status: Option<String>,
+    // {"Ok": null} 表示成功，其他为错误
+    pub err: Value,
+}
+
+impl BundleStatus {
+    pub fn is_ok(&self) -> bool {
+        match &self.err {
+            Value::Null => true,
+            Value::Object(object) => object.contains_key("Ok"),
+            _ => false,
+        }
+    }
+}
+
+/// getBundleStatuses的结果，未找到或未上链的bundle为None
+#[derive(Debug, Clone)]
+pub struct BundleStatuses {
+    // 查询时Block Engine所在的slot
+    pub context_slot: u64,
+    pub statuses: Vec<Option<BundleStatus>>,
+}
+
+// 按固定间隔放行请求
+#[derive(Debug)]
+struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_second: f64) -> Self {
+        let interval = if requests_per_second > 0.0 {
+            Duration::from_secs_f64(1.0 / requests_per_second)
+        } else {
+            Duration::ZERO
+        };
+        Self {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn acquire(&self) {
+        let mut next = self.next.lock().await;
+        let now = Instant::now();
+        if *next > now {
+            sleep_until(*next).await;
+        }
+        *next = (*next).max(now) + self.interval;
+    }
+}
+
+/// Block Engine的JSON-RPC HTTP客户端，可以代替gRPC提交bundle
+///
+/// 请求按 `requests_per_second` 排队发出，地址可以指向本地的HTTP stub。
+#[derive(Debug)]
+pub struct JsonRpcClient {
+    endpoint: String,
+    http: reqwest::Client,
+    limiter: RateLimiter,
+    next_id: AtomicU64,
+}
+
+impl JsonRpcClient {
+    pub fn new(block_engine_url: &str, requests_per_second: f64) -> Self {
+        Self {
+            endpoint: format!("{}{}", block_engine_url.trim_end_matches('/'), BUNDLES_PATH),
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("HTTP客户端配置合法"),
+            limiter: RateLimiter::new(requests_per_second),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    #[instrument(name = "json_rpc", skip(self, params), fields(endpoint = %self.endpoint))]
+    async fn call(&self, method: &str, params: Value) -> Result<Value, JsonRpcError> {
+        self.limiter.acquire().await;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+
+        let response = self.http.post(&self.endpoint).json(&request).send().await?;
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(JsonRpcError::RateLimited);
+        }
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(JsonRpcError::Status(status.as_u16(), body));
+        }
+        debug!(id, bytes = body.len(), "JSON-RPC响应");
+
+        let mut value: Value = serde_json::from_str(&body).map_err(|e| JsonRpcError::InvalidResponse(e.to_string()))?;
+        if let Some(error) = value.get("error") {
+            return Err(JsonRpcError::Rpc {
+                code: error.get("code").and_then(Value::as_i64).unwrap_or_default(),
+                message: error.get("message").and_then(Value::as_str).unwrap_or_default().to_string(),
+            });
+        }
+        value
+            .get_mut("result")
+            .map(Value::take)
+            .ok_or_else(|| JsonRpcError::InvalidResponse("缺少result字段".to_string()))
+    }
+
+    /// 当前的Jito小费账户
+    pub async fn get_tip_accounts(&self) -> Result<Vec<Pubkey>, JsonRpcError> {
+        let result = self.call("getTipAccounts", json!([])).await?;
+        let accounts = result
+            .as_array()
+            .ok_or_else(|| JsonRpcError::InvalidResponse("getTipAccounts的结果不是数组".to_string()))?;
+        accounts
+            .iter()
+            .map(|account| {
+                account
+                    .as_str()
+                    .and_then(|account| Pubkey::from_str(account).ok())
+                    .ok_or_else(|| JsonRpcError::InvalidResponse(format!("非法的小费账户: {}", account)))
+            })
+            .collect()
+    }
+
+    /// 提交bundle，交易按base58编码，返回bundle ID
+    pub async fn send_bundle(&self, transactions: &[VersionedTransaction]) -> Result<String, JsonRpcError> {
+        let bundle = build_bundle(transactions)?;
+        let encoded: Vec<String> = bundle
+            .packets
+            .iter()
+            .map(|packet| bs58::encode(&packet.data).into_string())
+            .collect();
+        let result = self.call("sendBundle", json!([encoded])).await?;
+        let bundle_id = result
+            .as_str()
+            .ok_or_else(|| JsonRpcError::InvalidResponse("sendBundle的结果不是字符串".to_string()))?;
+        info!(endpoint = %self.endpoint, bundle_id, transactions = transactions.len(), "已通过JSON-RPC提交bundle");
+        Ok(bundle_id.to_string())
+    }
+
+    /// 查询bundle状态，一次最多5个
+    pub async fn get_bundle_statuses(&self, bundle_ids: &[String]) -> Result<BundleStatuses, JsonRpcError> {
+        if bundle_ids.len() > MAX_BUNDLE_TRANSACTIONS {
+            return Err(JsonRpcError::TooManyBundleIds(bundle_ids.len()));
+        }
+        let result = self.call("getBundleStatuses", json!([bundle_ids])).await?;
+        // 所有bundle都未找到时result可能为null
+        if result.is_null() {
+            return Ok(BundleStatuses {
+                context_slot: 0,
+                statuses: vec![None; bundle_ids.len()],
+            });
+        }
+        let context_slot = result
+            .pointer("/context/slot")
+            .and_then(Value::as_u64)
+            .unwrap_or_default();
+        let values = result
+            .get("value")
+            .and_then(Value::as_array)
+            .ok_or_else(|| JsonRpcError::InvalidResponse("getBundleStatuses缺少value数组".to_string()))?;
+        let statuses = values.iter().map(parse_bundle_status).collect::<Result<Vec<_>, _>>()?;
+        Ok(BundleStatuses { context_slot, statuses })
+    }
+}
+
+fn parse_bundle_status(value: &Value) -> Result<Option<BundleStatus>, JsonRpcError> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    let invalid = |field: &str| JsonRpcError::InvalidResponse(format!("bundle状态缺少{}", field));
+    let transactions = value
+        .get("transactions")
+        .and_then(Value::as_array)
+        .ok_or_else(|| invalid("transactions"))?
+        .iter()
+        .map(|signature| {
+            signature
+                .as_str()
+                .and_then(|signature| Signature::from_str(signature).ok())
+                .ok_or_else(|| invalid("合法的交易签名"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // 文档中两种字段名都出现过
+    let confirmation_status = value
+        .get("confirmation_status")
+        .or_else(|| value.get("confirmationStatus"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
+    Ok(Some(BundleStatus {
+        bundle_id: value
+            .get("bundle_id")
+            .and_then(Value::as_str)
+            .ok_or_else(|| invalid("bundle_id"))?
+            .to_string(),
+        transactions,
+        slot: value.get("slot").and_then(Value::as_u64).ok_or_else(|| invalid("slot"))?,
+        confirmation_status,
+        err: value.get("err").cloned().unwrap_or(Value::Null),
+    }))
+}
+
+/// 并发用getTipAccounts探测各区域的往返延迟，返回最快区域的地址
+pub async fn select_fastest_region(regions: &[&str], requests_per_second: f64) -> Option<String> {
+    let probes = regions.iter().map(|region| async move {
+        let url = region_url(region);
+        let client = JsonRpcClient::new(&url, requests_per_second);
+        let start = Instant::now();
+        match client.get_tip_accounts().await {
+            Ok(_) => {
+                let elapsed = start.elapsed();
+                debug!(region, elapsed_ms = elapsed.as_millis() as u64, "区域探测");
+                Some((elapsed, url))
+            }
+            Err(e) => {
+                warn!(region, error = %e, "区域探测失败");
+                None
+            }
+        }
+    });
+    let fastest = join_all(probes).await.into_iter().flatten().min_by_key(|(elapsed, _)| *elapsed);
+    if let Some((elapsed, url)) = &fastest {
+        info!(block_engine = %url, elapsed_ms = elapsed.as_millis() as u64, "选择延迟最低的Block Engine区域");
+    }
+    fastest.map(|(_, url)| url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    // 只应答一个请求的HTTP stub，返回请求行和JSON请求体
+    async fn stub(status: u16, body: String) -> (String, JoinHandle<(String, Value)>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("绑定本地端口");
+        let url = format!("http://{}", listener.local_addr().expect("本地地址"));
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.expect("接受连接");
+            let mut request = Vec::new();
+            let mut chunk = [0u8; 4096];
+            let body_start = loop {
+                let read = socket.read(&mut chunk).await.expect("读取请求");
+                assert!(read > 0, "请求不完整");
+                request.extend_from_slice(&chunk[..read]);
+                let Some(header_end) = request.windows(4).position(|window| window == b"\r\n\r\n") else {
+                    continue;
+                };
+                let headers = String::from_utf8_lossy(&request[..header_end]).to_ascii_lowercase();
+                let length: usize = headers
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .and_then(|length| length.trim().parse().ok())
+                    .unwrap_or_default();
+                if request.len() >= header_end + 4 + length {
+                    break header_end + 4;
+                }
+            };
+            let response = format!(
+                "HTTP/1.1 {} Stub\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.expect("写入响应");
+            let request_line = String::from_utf8_lossy(&request).lines().next().unwrap_or_default().to_string();
+            (request_line, serde_json::from_slice(&request[body_start..]).expect("JSON请求体"))
+        });
+        (url, handle)
+    }
+
+    fn result(result: Value) -> String {
+        json!({"jsonrpc": "2.0", "id": 1, "result": result}).to_string()
+    }
+
+    fn transaction() -> VersionedTransaction {
+        VersionedTransaction {
+            signatures: vec![Signature::new_unique()],
+            ..VersionedTransaction::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn send_bundle() {
+        let (url, server) = stub(200, result(json!("bundle-1"))).await;
+        let client = JsonRpcClient::new(&format!("{}/", url), 0.0);
+        let transaction = transaction();
+
+        assert_eq!(client.send_bundle(std::slice::from_ref(&transaction)).await.expect("提交成功"), "bundle-1");
+        let (request_line, request) = server.await.expect("stub正常结束");
+        assert_eq!(request_line, "POST /api/v1/bundles HTTP/1.1");
+        assert_eq!(request["method"], "sendBundle");
+        let encoded = bs58::encode(bincode::serialize(&transaction).expect("序列化交易")).into_string();
+        assert_eq!(request["params"], json!([[encoded]]));
+    }
+
+    #[tokio::test]
+    async fn get_bundle_statuses() {
+        let (first, second) = (Signature::new_unique(), Signature::new_unique());
+        let body = result(json!({
+            "context": {"slot": 300},
+            "value": [
+                {
+                    "bundle_id": "a",
+                    "transactions": [first.to_string()],
+                    "slot": 298,
+                    "confirmation_status": "confirmed",
+                    "err": {"Ok": null},
+                },
+                null,
+                {
+                    "bundle_id": "c",
+                    "transactions": [second.to_string()],
+                    "slot": 299,
+                    "confirmationStatus": "finalized",
+                    "err": {"Err": "BundleFailed"},
+                },
+            ],
+        }));
+        let (url, server) = stub(200, body).await;
+        let client = JsonRpcClient::new(&url, 0.0);
+        let ids: Vec<String> = ["a", "b", "c"].map(str::to_string).to_vec();
+
+        let statuses = client.get_bundle_statuses(&ids).await.expect("查询成功");
+        let (_, request) = server.await.expect("stub正常结束");
+        assert_eq!(request["method"], "getBundleStatuses");
+        assert_eq!(request["params"], json!([["a", "b", "c"]]));
+
+        assert_eq!(statuses.context_slot, 300);
+        let [Some(landed), None, Some(failed)] = statuses.statuses.as_slice() else {
+            panic!("状态不符合预期: {:?}", statuses.statuses);
+        };
+        assert_eq!(landed.bundle_id, "a");
+        assert_eq!(landed.transactions, vec![first]);
+        assert_eq!(landed.slot, 298);
+        assert_eq!(landed.confirmation_status.as_deref(), Some("confirmed"));
+        assert!(landed.is_ok());
+        assert_eq!(failed.transactions, vec![second]);
+        assert_eq!(failed.confirmation_status.as_deref(), Some("finalized"));
+        assert!(!failed.is_ok());
+    }
+
+    #[tokio::test]
+    async fn get_bundle_statuses_null_result() {
+        let (url, server) = stub(200, result(Value::Null)).await;
+        let client = JsonRpcClient::new(&url, 0.0);
+
+        let statuses = client.get_bundle_statuses(&["a".to_string(), "b".to_string()]).await.expect("查询成功");
+        server.await.expect("stub正常结束");
+        assert_eq!(statuses.context_slot, 0);
+        assert!(matches!(statuses.statuses.as_slice(), [None, None]));
+    }
+
+    #[tokio::test]
+    async fn rate_limited() {
+        let (url, server) = stub(429, "{}".to_string()).await;
+        let client = JsonRpcClient::new(&url, 0.0);
+
+        let error = client.get_tip_accounts().await.expect_err("429应当失败");
+        server.await.expect("stub正常结束");
+        assert!(matches!(error, JsonRpcError::RateLimited), "{:?}", error);
+    }
+
+    #[tokio::test]
+    async fn rpc_error() {
+        let body = json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32602, "message": "bundle过大"}}).to_string();
+        let (url, server) = stub(200, body).await;
+        let client = JsonRpcClient::new(&url, 0.0);
+
+        let error = client.send_bundle(&[transaction()]).await.expect_err("RPC错误应当失败");
+        server.await.expect("stub正常结束");
+        assert!(
+            matches!(&error, JsonRpcError::Rpc { code: -32602, message } if message == "bundle过大"),
+            "{:?}",
+            error
+        );
+    }
+
+    #[tokio::test]
+    async fn too_many_bundle_ids() {
+        // 参数检查在发出请求之前，地址不可达也不影响
+        let client = JsonRpcClient::new("http://127.0.0.1:9", 0.0);
+        let ids = vec![String::new(); MAX_BUNDLE_TRANSACTIONS + 1];
+
+        let error = client.get_bundle_statuses(&ids).await.expect_err("超过上限应当失败");
+        assert!(matches!(error, JsonRpcError::TooManyBundleIds(6)), "{:?}", error);
+    }
+}
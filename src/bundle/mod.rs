@@ -0,0 +1,232 @@
+// 通过Block Engine的SearcherService提交bundle
+// 交易按bincode序列化后放进Packet，和验证者接收的格式一致
+
+use jito_protos::bundle::{bundle_result, rejected, Bundle, BundleResult, DroppedReason};
+use jito_protos::packet::{Meta, Packet};
+use jito_protos::searcher::{
+    searcher_service_client::SearcherServiceClient, GetTipAccountsRequest, NextScheduledLeaderRequest,
+    SendBundleRequest, SubscribeBundleResultsRequest,
+};
+use solana_sdk::{packet::PACKET_DATA_SIZE, pubkey::Pubkey, transaction::VersionedTransaction};
+use std::fmt;
+use std::str::FromStr;
+use tonic::transport::Channel;
+use tracing::{info, instrument};
+
+pub mod command;
+pub mod json_rpc;
+pub mod tracker;
+
+// 单个bundle最多包含的交易数
+pub const MAX_BUNDLE_TRANSACTIONS: usize = 5;
+
+// bundle构造或提交失败原因
+#[derive(Debug)]
+pub enum BundleError {
+    Transport(tonic::transport::Error),
+    Status(Box<tonic::Status>),
+    Empty,
+    TooManyTransactions(usize),
+    Serialize(String),
+    // 第几笔交易序列化后超过单个packet的大小
+    TooLarge { index: usize, size: usize },
+    InvalidTipAccount(String),
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BundleError::Transport(error) => write!(f, "连接Block Engine失败: {}", error),
+            BundleError::Status(status) => write!(f, "Block Engine返回错误: {:?} {}", status.code(), status.message()),
+            BundleError::Empty => write!(f, "bundle中没有交易"),
+            BundleError::TooManyTransactions(count) => {
+                write!(f, "bundle包含{}笔交易，最多{}笔", count, MAX_BUNDLE_TRANSACTIONS)
+            }
+            BundleError::Serialize(error) => write!(f, "交易序列化失败: {}", error),
+            BundleError::TooLarge { index, size } => {
+                write!(f, "第{}笔交易序列化后为{}字节，超过{}字节", index, size, PACKET_DATA_SIZE)
+            }
+            BundleError::InvalidTipAccount(account) => write!(f, "非法的小费账户: {}", account),
+        }
+    }
+}
+
+impl std::error::Error for BundleError {}
+
+impl From<tonic::transport::Error> for BundleError {
+    fn from(error: tonic::transport::Error) -> Self {
+        BundleError::Transport(error)
+    }
+}
+
+impl From<tonic::Status> for BundleError {
+    fn from(status: tonic::Status) -> Self {
+        BundleError::Status(Box::new(status))
+    }
+}
+
+/// 下一个连接到Block Engine的leader
+#[derive(Debug, Clone)]
+pub struct NextLeader {
+    pub current_slot: u64,
+    pub next_leader_slot: u64,
+    pub next_leader_identity: String,
+    pub next_leader_region: String,
+}
+
+impl NextLeader {
+    /// 距离下一个Jito leader还有多少个slot
+    pub fn slots_until_leader(&self) -> u64 {
+        self.next_leader_slot.saturating_sub(self.current_slot)
+    }
+}
+
+/// 把单笔交易封装成packet
+pub fn transaction_packet(index: usize, transaction: &VersionedTransaction) -> Result<Packet, BundleError> {
+    let data = bincode::serialize(transaction).map_err(|e| BundleError::Serialize(e.to_string()))?;
+    if data.len() > PACKET_DATA_SIZE {
+        return Err(BundleError::TooLarge { index, size: data.len() });
+    }
+    Ok(Packet {
+        meta: Some(Meta {
+            size: data.len() as u64,
+            ..Meta::default()
+        }),
+        data,
+    })
+}
+
+/// 按顺序把交易组成bundle，最多 `MAX_BUNDLE_TRANSACTIONS` 笔
+pub fn build_bundle(transactions: &[VersionedTransaction]) -> Result<Bundle, BundleError> {
+    if transactions.is_empty() {
+        return Err(BundleError::Empty);
+    }
+    if transactions.len() > MAX_BUNDLE_TRANSACTIONS {
+        return Err(BundleError::TooManyTransactions(transactions.len()));
+    }
+    let packets = transactions
+        .iter()
+        .enumerate()
+        .map(|(index, transaction)| transaction_packet(index, transaction))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Bundle { header: None, packets })
+}
+
+/// 输出用的bundle结果描述
+pub fn describe_bundle_result(result: &BundleResult) -> String {
+    let status = match &result.result {
+        Some(bundle_result::Result::Accepted(accepted)) => {
+            format!("已接受: slot={}, 验证者={}", accepted.slot, accepted.validator_identity)
+        }
+        Some(bundle_result::Result::Rejected(rejected)) => match &rejected.reason {
+            Some(rejected::Reason::StateAuctionBidRejected(reason)) => format!(
+                "被拒绝: 状态拍卖出价过低, 拍卖={}, 模拟出价={}{}",
+                reason.auction_id,
+                reason.simulated_bid_lamports,
+                optional_message(&reason.msg)
+            ),
+            Some(rejected::Reason::WinningBatchBidRejected(reason)) => format!(
+                "被拒绝: 赢得拍卖但出价低于其他赢家, 拍卖={}, 模拟出价={}{}",
+                reason.auction_id,
+                reason.simulated_bid_lamports,
+                optional_message(&reason.msg)
+            ),
+            Some(rejected::Reason::SimulationFailure(reason)) => {
+                format!("被拒绝: 模拟失败, 交易={}{}", reason.tx_signature, optional_message(&reason.msg))
+            }
+            Some(rejected::Reason::InternalError(reason)) => format!("被拒绝: 内部错误, {}", reason.msg),
+            Some(rejected::Reason::DroppedBundle(reason)) => format!("被拒绝: 已丢弃, {}", reason.msg),
+            None => "被拒绝: 原因未知".to_string(),
+        },
+        Some(bundle_result::Result::Processed(processed)) => format!(
+            "已处理: slot={}, 验证者={}, 区块内序号={}",
+            processed.slot, processed.validator_identity, processed.bundle_index
+        ),
+        Some(bundle_result::Result::Finalized(_)) => "已最终确认".to_string(),
+        Some(bundle_result::Result::Dropped(dropped)) => {
+            let reason = match DroppedReason::try_from(dropped.reason) {
+                Ok(DroppedReason::BlockhashExpired) => "blockhash过期",
+                Ok(DroppedReason::PartiallyProcessed) => "部分交易已上链",
+                Ok(DroppedReason::NotFinalized) => "已处理但未最终确认",
+                Err(_) => "原因未知",
+            };
+            format!("未上链: {}", reason)
+        }
+        None => "结果为空".to_string(),
+    };
+    format!("bundle {}: {}", result.bundle_id, status)
+}
+
+fn optional_message(message: &Option<String>) -> String {
+    message.as_ref().map(|message| format!(", {}", message)).unwrap_or_default()
+}
+
+/// Block Engine的searcher客户端
+///
+/// 地址可以指向本地的mock服务，和主网Block Engine使用同一套接口。
+pub struct SearcherClient {
+    block_engine_url: String,
+    client: SearcherServiceClient<Channel>,
+}
+
+impl SearcherClient {
+    #[instrument(name = "connect_block_engine", skip_all, fields(block_engine = %block_engine_url))]
+    pub async fn connect(block_engine_url: String) -> Result<Self, BundleError> {
+        let client = SearcherServiceClient::connect(block_engine_url.clone()).await?;
+        info!("已连接Block Engine");
+        Ok(Self { block_engine_url, client })
+    }
+
+    pub fn block_engine_url(&self) -> &str {
+        &self.block_engine_url
+    }
+
+    /// 提交bundle，返回服务端分配的bundle ID
+    #[instrument(name = "send_bundle", skip_all, fields(block_engine = %self.block_engine_url, transactions = transactions.len()))]
+    pub async fn send_bundle(&mut self, transactions: &[VersionedTransaction]) -> Result<String, BundleError> {
+        let bundle = build_bundle(transactions)?;
+        let response = self
+            .client
+            .send_bundle(SendBundleRequest { bundle: Some(bundle) })
+            .await?
+            .into_inner();
+        info!(bundle_id = %response.uuid, "已提交bundle");
+        Ok(response.uuid)
+    }
+
+    /// 当前的Jito小费账户
+    pub async fn tip_accounts(&mut self) -> Result<Vec<Pubkey>, BundleError> {
+        let response = self.client.get_tip_accounts(GetTipAccountsRequest {}).await?.into_inner();
+        response
+            .accounts
+            .iter()
+            .map(|account| Pubkey::from_str(account).map_err(|_| BundleError::InvalidTipAccount(account.clone())))
+            .collect()
+    }
+
+    /// 下一个连接到Block Engine的leader，`regions` 为空时只看当前区域
+    pub async fn next_scheduled_leader(&mut self, regions: &[String]) -> Result<NextLeader, BundleError> {
+        let response = self
+            .client
+            .get_next_scheduled_leader(NextScheduledLeaderRequest { regions: regions.to_vec() })
+            .await?
+            .into_inner();
+        Ok(NextLeader {
+            current_slot: response.current_slot,
+            next_leader_slot: response.next_leader_slot,
+            next_leader_identity: response.next_leader_identity,
+            next_leader_region: response.next_leader_region,
+        })
+    }
+
+    /// 订阅本searcher提交的bundle的结果
+    pub async fn subscribe_bundle_results(&mut self) -> Result<tonic::Streaming<BundleResult>, BundleError> {
+        let stream = self
+            .client
+            .subscribe_bundle_results(SubscribeBundleResultsRequest {})
+            .await?
+            .into_inner();
+        info!(block_engine = %self.block_engine_url, "已订阅bundle结果");
+        Ok(stream)
+    }
+}
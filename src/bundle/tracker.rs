@@ -0,0 +1,440 @@
+use jito_protos::bundle::{bundle_result, BundleResult};
+use solana_sdk::{signature::Signature, transaction::VersionedTransaction};
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+use super::describe_bundle_result;
+
+// 提交后最多跟踪多少个slot，和blockhash的有效期一致
+pub const TRACK_SLOTS: u64 = 150;
+
+/// 综合Block Engine结果和Entry流得出的bundle状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleState {
+    // 已提交，还没有任何结果
+    Submitted,
+    // Block Engine已转发给验证者
+    Accepted,
+    // 交易出现在Entry流中，或Block Engine报告已处理
+    Landed,
+    Finalized,
+    Rejected,
+    Dropped,
+    // 超过跟踪窗口仍未上链
+    Expired,
+}
+
+impl fmt::Display for BundleState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BundleState::Submitted => "已提交",
+            BundleState::Accepted => "已转发",
+            BundleState::Landed => "已上链",
+            BundleState::Finalized => "已最终确认",
+            BundleState::Rejected => "被拒绝",
+            BundleState::Dropped => "未上链",
+            BundleState::Expired => "已过期",
+        })
+    }
+}
+
+/// 单个bundle从提交到上链(或失败)的记录
+#[derive(Debug, Clone)]
+pub struct BundleLifecycle {
+    pub bundle_id: String,
+    pub signatures: Vec<Signature>,
+    pub submitted_at: Instant,
+    // 提交后在Entry流中看到的第一个slot，用于计算跟踪窗口
+    pub first_stream_slot: Option<u64>,
+    // 每次Accepted的slot和验证者，同一个bundle可能转发给多个验证者
+    pub accepted: Vec<(u64, String)>,
+    pub rejected: Option<String>,
+    pub dropped: Option<String>,
+    // Processed的slot、验证者和区块内序号
+    pub processed: Option<(u64, String, u64)>,
+    pub finalized: bool,
+    // 交易在Entry流中出现的slot
+    pub seen: HashMap<Signature, u64>,
+    // 第一次在Entry流中看到交易距提交的时间
+    pub first_seen_after: Option<Duration>,
+    pub expired: bool,
+}
+
+impl BundleLifecycle {
+    fn new(bundle_id: String, signatures: Vec<Signature>) -> Self {
+        Self {
+            bundle_id,
+            signatures,
+            submitted_at: Instant::now(),
+            first_stream_slot: None,
+            accepted: Vec::new(),
+            rejected: None,
+            dropped: None,
+            processed: None,
+            finalized: false,
+            seen: HashMap::new(),
+            first_seen_after: None,
+            expired: false,
+        }
+    }
+
+    /// 所有交易都出现在Entry流中的slot，不在同一个slot时取最后一个
+    pub fn landed_slot(&self) -> Option<u64> {
+        if self.signatures.is_empty() || self.seen.len() < self.signatures.len() {
+            return None;
+        }
+        self.seen.values().max().copied()
+    }
+
+    /// 最终状态: 最终确认 > 上链 > 被拒绝/未上链 > 已过期 > 已转发 > 已提交
+    ///
+    /// Entry流中的交易只说明被leader打包，不代表执行成功；Block Engine报告失败但交易出现在流中时仍视为上链，由 `conflicts` 给出说明。
+    pub fn state(&self) -> BundleState {
+        if self.finalized {
+            BundleState::Finalized
+        } else if self.processed.is_some() || self.landed_slot().is_some() {
+            BundleState::Landed
+        } else if self.rejected.is_some() {
+            BundleState::Rejected
+        } else if self.dropped.is_some() {
+            BundleState::Dropped
+        } else if self.expired {
+            BundleState::Expired
+        } else if !self.accepted.is_empty() {
+            BundleState::Accepted
+        } else {
+            BundleState::Submitted
+        }
+    }
+
+    /// 不再需要跟踪: 已最终确认、被拒绝、未上链、已过期，或者已处理且在流中看到了所有交易
+    ///
+    /// 只有一方确认上链时继续等待另一方，直到超过跟踪窗口。
+    pub fn is_settled(&self) -> bool {
+        match self.state() {
+            BundleState::Finalized | BundleState::Rejected | BundleState::Dropped | BundleState::Expired => true,
+            BundleState::Landed => self.expired || (self.processed.is_some() && self.landed_slot().is_some()),
+            BundleState::Submitted | BundleState::Accepted => false,
+        }
+    }
+
+    /// Block Engine的结果和Entry流中的观察不一致的地方
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = Vec::new();
+        let landed_slot = self.landed_slot();
+        if !self.seen.is_empty() && landed_slot.is_none() {
+            conflicts.push(format!("只有{}/{}笔交易出现在Entry流中", self.seen.len(), self.signatures.len()));
+        }
+        if let Some(slot) = landed_slot {
+            if self.rejected.is_some() {
+                conflicts.push(format!("Block Engine报告被拒绝，但交易在slot {}出现在Entry流中", slot));
+            }
+            if self.dropped.is_some() {
+                conflicts.push(format!("Block Engine报告未上链，但交易在slot {}出现在Entry流中", slot));
+            }
+        }
+        if let (Some((processed_slot, _, _)), Some(slot)) = (&self.processed, landed_slot) {
+            if *processed_slot != slot {
+                conflicts.push(format!("Block Engine报告在slot {}处理，Entry流中在slot {}", processed_slot, slot));
+            }
+        }
+        if self.finalized && self.seen.is_empty() {
+            conflicts.push("Block Engine报告已最终确认，但Entry流中没有看到交易".to_string());
+        }
+        conflicts
+    }
+
+    pub fn write(&self, out: &mut impl Write) -> fmt::Result {
+        writeln!(out, "\n===== bundle生命周期 =====")?;
+        writeln!(out, "bundle ID: {}", self.bundle_id)?;
+        writeln!(out, "状态: {}", self.state())?;
+        for (slot, validator) in &self.accepted {
+            writeln!(out, "已转发: slot={}, 验证者={}", slot, validator)?;
+        }
+        if let Some((slot, validator, index)) = &self.processed {
+            writeln!(out, "已处理: slot={}, 验证者={}, 区块内序号={}", slot, validator, index)?;
+        }
+        if let Some(reason) = &self.rejected {
+            writeln!(out, "被拒绝: {}", reason)?;
+        }
+        if let Some(reason) = &self.dropped {
+            writeln!(out, "未上链: {}", reason)?;
+        }
+        for signature in &self.signatures {
+            match self.seen.get(signature) {
+                Some(slot) => writeln!(out, "交易 {}: Entry流 slot {}", signature, slot)?,
+                None => writeln!(out, "交易 {}: Entry流中未出现", signature)?,
+            }
+        }
+        if let Some(after) = self.first_seen_after {
+            writeln!(out, "提交后 {} 毫秒在Entry流中看到", after.as_millis())?;
+        }
+        for conflict in self.conflicts() {
+            writeln!(out, "不一致: {}", conflict)?;
+        }
+        Ok(())
+    }
+
+    pub fn log(&self) {
+        info!(
+            event = "bundle_lifecycle",
+            bundle_id = %self.bundle_id,
+            state = %self.state(),
+            transactions = self.signatures.len(),
+            seen = self.seen.len(),
+            landed_slot = self.landed_slot(),
+            processed_slot = self.processed.as_ref().map(|(slot, _, _)| *slot),
+            accepted = self.accepted.len(),
+            finalized = self.finalized,
+            first_seen_ms = self.first_seen_after.map(|after| after.as_millis() as u64),
+            conflicts = self.conflicts().len(),
+            "bundle状态"
+        );
+    }
+}
+
+/// 按bundle ID和交易签名关联Block Engine结果和Entry流
+#[derive(Debug, Default)]
+pub struct BundleTracker {
+    bundles: HashMap<String, BundleLifecycle>,
+    by_signature: HashMap<Signature, String>,
+}
+
+impl BundleTracker {
+    /// 开始跟踪一个已提交的bundle
+    pub fn track(&mut self, bundle_id: String, transactions: &[VersionedTransaction]) {
+        let signatures: Vec<Signature> = transactions.iter().filter_map(|tx| tx.signatures.first().copied()).collect();
+        for signature in &signatures {
+            self.by_signature.insert(*signature, bundle_id.clone());
+        }
+        self.bundles
+            .insert(bundle_id.clone(), BundleLifecycle::new(bundle_id, signatures));
+    }
+
+    pub fn get(&self, bundle_id: &str) -> Option<&BundleLifecycle> {
+        self.bundles.get(bundle_id)
+    }
+
+    /// 应用Block Engine推送的结果，返回状态发生变化的bundle
+    pub fn on_result(&mut self, result: &BundleResult) -> Option<&BundleLifecycle> {
+        let Some(lifecycle) = self.bundles.get_mut(&result.bundle_id) else {
+            debug!(result = %describe_bundle_result(result), "未跟踪的bundle结果");
+            return None;
+        };
+        let before = lifecycle.state();
+        match &result.result {
+            Some(bundle_result::Result::Accepted(accepted)) => {
+                lifecycle.accepted.push((accepted.slot, accepted.validator_identity.clone()));
+            }
+            Some(bundle_result::Result::Processed(processed)) => {
+                lifecycle.processed = Some((processed.slot, processed.validator_identity.clone(), processed.bundle_index));
+            }
+            Some(bundle_result::Result::Finalized(_)) => lifecycle.finalized = true,
+            Some(bundle_result::Result::Rejected(_)) => lifecycle.rejected = Some(describe_bundle_result(result)),
+            Some(bundle_result::Result::Dropped(_)) => lifecycle.dropped = Some(describe_bundle_result(result)),
+            None => {}
+        }
+        debug!(bundle_id = %result.bundle_id, result = %describe_bundle_result(result), "bundle结果");
+        (lifecycle.state() != before).then_some(&*lifecycle)
+    }
+
+    /// 在Entry流的一个批次中查找跟踪中的交易，并把超过跟踪窗口的bundle标记为过期；返回状态发生变化的bundle ID
+    pub fn on_entries(&mut self, slot: u64, transactions: &[VersionedTransaction]) -> Vec<String> {
+        let mut changed = Vec::new();
+        for transaction in transactions {
+            let Some(signature) = transaction.signatures.first() else {
+                continue;
+            };
+            let Some(lifecycle) = self
+                .by_signature
+                .get(signature)
+                .and_then(|bundle_id| self.bundles.get_mut(bundle_id))
+            else {
+                continue;
+            };
+            let before = lifecycle.state();
+            lifecycle.seen.entry(*signature).or_insert(slot);
+            lifecycle.first_seen_after.get_or_insert_with(|| lifecycle.submitted_at.elapsed());
+            if lifecycle.state() != before {
+                changed.push(lifecycle.bundle_id.clone());
+            }
+        }
+
+        for lifecycle in self.bundles.values_mut() {
+            let first_slot = *lifecycle.first_stream_slot.get_or_insert(slot);
+            let before = lifecycle.state();
+            if slot.saturating_sub(first_slot) > TRACK_SLOTS && !lifecycle.is_settled() {
+                lifecycle.expired = true;
+            }
+            if lifecycle.state() != before {
+                changed.push(lifecycle.bundle_id.clone());
+            }
+        }
+        changed
+    }
+
+    /// 所有跟踪中的bundle都不需要再跟踪
+    pub fn is_settled(&self) -> bool {
+        self.bundles.values().all(BundleLifecycle::is_settled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jito_protos::bundle::{rejected, Accepted, Finalized, Processed, Rejected, SimulationFailure};
+
+    fn transaction() -> VersionedTransaction {
+        VersionedTransaction {
+            signatures: vec![Signature::new_unique()],
+            ..VersionedTransaction::default()
+        }
+    }
+
+    fn result(bundle_id: &str, result: bundle_result::Result) -> BundleResult {
+        BundleResult {
+            bundle_id: bundle_id.to_string(),
+            result: Some(result),
+        }
+    }
+
+    fn accepted(slot: u64) -> bundle_result::Result {
+        bundle_result::Result::Accepted(Accepted {
+            slot,
+            validator_identity: "validator".to_string(),
+        })
+    }
+
+    fn processed(slot: u64) -> bundle_result::Result {
+        bundle_result::Result::Processed(Processed {
+            validator_identity: "validator".to_string(),
+            slot,
+            bundle_index: 0,
+        })
+    }
+
+    fn rejected() -> bundle_result::Result {
+        bundle_result::Result::Rejected(Rejected {
+            reason: Some(rejected::Reason::SimulationFailure(SimulationFailure {
+                tx_signature: String::new(),
+                msg: Some("模拟失败".to_string()),
+            })),
+        })
+    }
+
+    // 跟踪一个包含两笔交易的bundle，并用一个空批次确定跟踪窗口的起点
+    fn tracker(start_slot: u64) -> (BundleTracker, Vec<VersionedTransaction>) {
+        let transactions = vec![transaction(), transaction()];
+        let mut tracker = BundleTracker::default();
+        tracker.track("bundle".to_string(), &transactions);
+        assert!(tracker.on_entries(start_slot, &[]).is_empty());
+        (tracker, transactions)
+    }
+
+    #[test]
+    fn accepted_then_landed() {
+        let (mut tracker, transactions) = tracker(100);
+        let state = tracker.on_result(&result("bundle", accepted(101))).map(BundleLifecycle::state);
+        assert_eq!(state, Some(BundleState::Accepted));
+
+        assert_eq!(tracker.on_entries(102, &transactions), vec!["bundle".to_string()]);
+        let lifecycle = tracker.get("bundle").expect("正在跟踪");
+        assert_eq!(lifecycle.state(), BundleState::Landed);
+        assert_eq!(lifecycle.landed_slot(), Some(102));
+        assert!(lifecycle.first_seen_after.is_some());
+        // 只有Entry流确认上链，继续等待Block Engine的结果
+        assert!(!tracker.is_settled());
+
+        // 状态不变时不返回
+        assert!(tracker.on_result(&result("bundle", processed(102))).is_none());
+        let lifecycle = tracker.get("bundle").expect("正在跟踪");
+        assert!(lifecycle.is_settled());
+        assert!(lifecycle.conflicts().is_empty());
+
+        let state = tracker
+            .on_result(&result("bundle", bundle_result::Result::Finalized(Finalized {})))
+            .map(BundleLifecycle::state);
+        assert_eq!(state, Some(BundleState::Finalized));
+    }
+
+    #[test]
+    fn processed_slot_mismatch_is_a_conflict() {
+        let (mut tracker, transactions) = tracker(100);
+        tracker.on_entries(102, &transactions);
+        tracker.on_result(&result("bundle", processed(103)));
+        let conflicts = tracker.get("bundle").expect("正在跟踪").conflicts();
+        assert_eq!(conflicts, vec!["Block Engine报告在slot 103处理，Entry流中在slot 102".to_string()]);
+    }
+
+    #[test]
+    fn rejected_but_seen_on_stream() {
+        let (mut tracker, transactions) = tracker(100);
+        let state = tracker.on_result(&result("bundle", rejected())).map(BundleLifecycle::state);
+        assert_eq!(state, Some(BundleState::Rejected));
+        assert!(tracker.is_settled());
+
+        // 交易仍然出现在流中时以流为准
+        assert_eq!(tracker.on_entries(101, &transactions), vec!["bundle".to_string()]);
+        let lifecycle = tracker.get("bundle").expect("正在跟踪");
+        assert_eq!(lifecycle.state(), BundleState::Landed);
+        assert_eq!(
+            lifecycle.conflicts(),
+            vec!["Block Engine报告被拒绝，但交易在slot 101出现在Entry流中".to_string()]
+        );
+    }
+
+    #[test]
+    fn partial_appearance() {
+        let (mut tracker, transactions) = tracker(100);
+        tracker.on_result(&result("bundle", accepted(100)));
+
+        // 只看到一笔交易时状态不变
+        assert!(tracker.on_entries(101, &transactions[..1]).is_empty());
+        let lifecycle = tracker.get("bundle").expect("正在跟踪");
+        assert_eq!(lifecycle.state(), BundleState::Accepted);
+        assert_eq!(lifecycle.landed_slot(), None);
+        assert_eq!(lifecycle.conflicts(), vec!["只有1/2笔交易出现在Entry流中".to_string()]);
+        assert!(!lifecycle.is_settled());
+
+        // 另一笔在后面的slot出现，取较晚的slot
+        assert_eq!(tracker.on_entries(103, &transactions[1..]), vec!["bundle".to_string()]);
+        let lifecycle = tracker.get("bundle").expect("正在跟踪");
+        assert_eq!(lifecycle.landed_slot(), Some(103));
+        assert_eq!(lifecycle.seen[&transactions[0].signatures[0]], 101);
+    }
+
+    #[test]
+    fn expires_after_track_slots() {
+        let (mut tracker, _) = tracker(1_000);
+        assert!(tracker.on_entries(1_000 + TRACK_SLOTS, &[]).is_empty());
+        assert_eq!(tracker.get("bundle").map(BundleLifecycle::state), Some(BundleState::Submitted));
+
+        assert_eq!(tracker.on_entries(1_001 + TRACK_SLOTS, &[]), vec!["bundle".to_string()]);
+        assert_eq!(tracker.get("bundle").map(BundleLifecycle::state), Some(BundleState::Expired));
+        assert!(tracker.is_settled());
+    }
+
+    #[test]
+    fn landed_without_processed_settles_at_expiry() {
+        let (mut tracker, transactions) = tracker(1_000);
+        tracker.on_entries(1_001, &transactions);
+        assert!(!tracker.is_settled());
+
+        // 已上链的bundle过期时状态不变，但不再等待Block Engine
+        assert!(tracker.on_entries(1_001 + TRACK_SLOTS, &[]).is_empty());
+        let lifecycle = tracker.get("bundle").expect("正在跟踪");
+        assert_eq!(lifecycle.state(), BundleState::Landed);
+        assert!(lifecycle.expired);
+        assert!(tracker.is_settled());
+    }
+
+    #[test]
+    fn untracked_results_and_transactions_are_ignored() {
+        let (mut tracker, _) = tracker(100);
+        assert!(tracker.on_result(&result("other", accepted(100))).is_none());
+        assert!(tracker.on_entries(101, &[transaction()]).is_empty());
+        assert_eq!(tracker.get("bundle").map(BundleLifecycle::state), Some(BundleState::Submitted));
+    }
+}
@@ -5,6 +5,7 @@ use jito_protos::shredstream::{
 };
 use tokio::time::sleep;
 use std::time::Duration;
+use tracing::{info, instrument, warn};
 
 pub struct ShredstreamClient {
     server_url: String,
@@ -15,10 +16,14 @@ impl ShredstreamClient {
         Self { server_url }
     }
 
+    #[instrument(name = "connect", skip(self), fields(upstream = %self.server_url))]
     pub async fn connect(&self) -> Result<ShredstreamProxyClient<Channel>, tonic::transport::Error> {
-        ShredstreamProxyClient::connect(self.server_url.clone()).await
+        let client = ShredstreamProxyClient::connect(self.server_url.clone()).await?;
+        info!("已连接Shredstream代理");
+        Ok(client)
     }
 
+    #[instrument(name = "subscribe", skip(self, client), fields(upstream = %self.server_url))]
     pub async fn subscribe_entries(
         &self,
         client: &mut ShredstreamProxyClient<Channel>
@@ -26,8 +31,14 @@ impl ShredstreamClient {
         loop {
             let request = tonic::Request::new(SubscribeEntriesRequest {});
             match client.subscribe_entries(request).await {
-                Ok(response) => return Ok(response.into_inner()),
-                Err(_) => sleep(Duration::from_secs(5)).await,
+                Ok(response) => {
+                    info!("已订阅Entry流");
+                    return Ok(response.into_inner());
+                }
+                Err(status) => {
+                    warn!(code = ?status.code(), message = status.message(), "订阅失败，5秒后重试");
+                    sleep(Duration::from_secs(5)).await;
+                }
             }
         }
     }
@@ -1,14 +1,114 @@
 use std::env;
 use solana_sdk::pubkey::Pubkey;
 use std::str::FromStr;
+use std::path::PathBuf;
 
 // 添加Pump AMM程序ID常量
 pub const PUMPAMM_PROGRAM_ID: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
 
+// Pump Global账户中的默认手续费接收账户
+pub const PUMP_FEE_RECIPIENT: &str = "CebN5WGQ4jvEPvsVU4EoHEpgzq1VV7AbicfhtW4xC9iM";
+
+// 主网Jito小费账户，与searcher服务GetTipAccounts的返回一致
+pub const JITO_TIP_ACCOUNTS: [&str; 8] = [
+    "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+    "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+    "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+    "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+    "DfXygSm4jCyNCybVYYK6DwvWqjKee8pbDmJGcLWNDXjh",
+    "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+    "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+    "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
+];
+
+// 日志输出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Pretty,
+    Json,
+}
+
+impl LogFormat {
+    /// 从环境变量值解析日志格式，无法识别时回退到Pretty
+    pub fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "json" => LogFormat::Json,
+            _ => LogFormat::Pretty,
+        }
+    }
+}
+
+// 下游处理不过来时的策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackpressurePolicy {
+    // 阻塞上游，最终通过gRPC流控让代理放慢
+    Block,
+    // 丢弃新到的批次/输出并计数
+    Drop,
+}
+
+impl BackpressurePolicy {
+    /// 从环境变量值解析策略，无法识别时回退到Block
+    pub fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "drop" => BackpressurePolicy::Drop,
+            _ => BackpressurePolicy::Block,
+        }
+    }
+}
+
+// 提交bundle和查询小费账户使用的接口
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleTransport {
+    // SearcherService gRPC
+    Grpc,
+    // /api/v1/bundles 的JSON-RPC HTTP接口
+    JsonRpc,
+}
+
+impl BundleTransport {
+    /// 从环境变量值解析接口，无法识别时回退到gRPC
+    pub fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "json-rpc" | "jsonrpc" | "http" => BundleTransport::JsonRpc,
+            _ => BundleTransport::Grpc,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Config {
     pub server_url: String,
     pub target_accounts: Vec<Pubkey>,
+    pub log_format: LogFormat,
+    pub log_filter: String,
+    pub dead_letter_dir: Option<PathBuf>,
+    pub slot_duration_ms: u64,
+    pub pipeline_workers: usize,
+    pub pipeline_capacity: usize,
+    pub pipeline_policy: BackpressurePolicy,
+    pub lazy_filter: bool,
+    pub record_dir: Option<PathBuf>,
+    pub track_pump_curves: bool,
+    pub track_dex_swaps: bool,
+    pub account_snapshot: Option<PathBuf>,
+    pub detect_launches: bool,
+    pub jito_tip_accounts: Vec<Pubkey>,
+    pub refresh_tip_accounts: bool,
+    pub watch_wallets: Vec<Pubkey>,
+    pub wallet_history: usize,
+    pub wallet_positions: usize,
+    pub copy_trade_rules: Option<PathBuf>,
+    pub signal_output: Option<PathBuf>,
+    pub block_engine_url: String,
+    pub block_engine_region: Option<String>,
+    pub block_engine_rps: f64,
+    pub bundle_transport: BundleTransport,
+    pub payer_keypair: Option<PathBuf>,
+    pub pump_fee_recipient: Pubkey,
+    pub compute_unit_limit: Option<u32>,
+    pub compute_unit_price: Option<u64>,
+    pub bundle_tip_lamports: u64,
 }
 
 impl Default for Config {
@@ -47,6 +147,102 @@ impl Default for Config {
             server_url: env::var("SHREDSTREAM_SERVER_URL")
                 .unwrap_or_else(|_| "http://127.0.0.1:18890".to_string()),
             target_accounts: accounts,
+            log_format: LogFormat::parse(&env::var("LOG_FORMAT").unwrap_or_default()),
+            // 兼容tracing的RUST_LOG写法，例如 "info,jito_shredstream_client=debug"
+            log_filter: env::var("RUST_LOG")
+                .unwrap_or_else(|_| "info".to_string()),
+            // 未设置时解码失败的批次只计数和记录日志，不落盘
+            dead_letter_dir: env::var("DEAD_LETTER_DIR").ok().map(PathBuf::from),
+            // 估计slot开始时间所用的slot时长
+            slot_duration_ms: env::var("SLOT_DURATION_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(400),
+            // 解码worker数量，默认等于CPU核数
+            pipeline_workers: env::var("PIPELINE_WORKERS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4)),
+            pipeline_capacity: env::var("PIPELINE_CAPACITY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1024),
+            pipeline_policy: BackpressurePolicy::parse(&env::var("PIPELINE_POLICY").unwrap_or_default()),
+            // 默认先扫描账户列表，只反序列化命中的交易
+            lazy_filter: env::var("LAZY_FILTER")
+                .map(|v| v != "0" && !v.eq_ignore_ascii_case("false"))
+                .unwrap_or(true),
+            // 录制原始批次，供基准测试和离线回放使用
+            record_dir: env::var("RECORD_DIR").ok().map(PathBuf::from),
+            // 跟踪所有Pump交易以估计曲线状态，而不仅是目标账户的交易
+            track_pump_curves: env::var("TRACK_PUMP_CURVES")
+                .map(|v| v != "0" && !v.eq_ignore_ascii_case("false"))
+                .unwrap_or(true),
+            // 解码所有Raydium AMM v4/CPMM、Meteora DLMM和Orca Whirlpool交易，输出兑换和新池
+            track_dex_swaps: env::var("TRACK_DEX_SWAPS")
+                .map(|v| v != "0" && !v.eq_ignore_ascii_case("false"))
+                .unwrap_or(true),
+            // 启动时加载的Pump/Pump AMM账户快照(文件或目录)，用于精确初始化曲线和池状态
+            account_snapshot: env::var("ACCOUNT_SNAPSHOT").ok().map(PathBuf::from),
+            // 识别所有Pump create指令并输出新代币发射事件，而不仅是目标账户的交易
+            detect_launches: env::var("DETECT_LAUNCHES")
+                .map(|v| v != "0" && !v.eq_ignore_ascii_case("false"))
+                .unwrap_or(true),
+            // 逗号分隔的Jito小费账户，设为空字符串关闭小费统计
+            jito_tip_accounts: env::var("JITO_TIP_ACCOUNTS")
+                .unwrap_or_else(|_| JITO_TIP_ACCOUNTS.join(","))
+                .split(',')
+                .filter_map(|account| Pubkey::from_str(account.trim()).ok())
+                .collect(),
+            // 显式配置了Block Engine且没有手动指定小费账户时，启动时用GetTipAccounts刷新
+            refresh_tip_accounts: (env::var("BLOCK_ENGINE_URL").is_ok() || env::var("BLOCK_ENGINE_REGION").is_ok())
+                && env::var("JITO_TIP_ACCOUNTS").is_err(),
+            // 逗号分隔的关注钱包，跟踪它们的兑换并在买入新代币时提醒
+            watch_wallets: env::var("WATCH_WALLETS")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|wallet| Pubkey::from_str(wallet.trim()).ok())
+                .collect(),
+            // 每个关注钱包保留的最近兑换笔数
+            wallet_history: env::var("WALLET_HISTORY")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(200),
+            // 每个关注钱包最多保留的代币持仓数，超出时丢弃最久没有交易的持仓
+            wallet_positions: env::var("WALLET_POSITIONS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1000),
+            // 跟单规则文件(JSON)，不设置时不匹配跟单信号
+            copy_trade_rules: env::var("COPY_TRADE_RULES").ok().map(PathBuf::from),
+            // 跟单信号追加写入的文件，不设置时输出到stdout
+            signal_output: env::var("SIGNAL_OUTPUT").ok().map(PathBuf::from),
+            // 提交bundle的Block Engine地址，可以指向本地mock服务
+            block_engine_url: env::var("BLOCK_ENGINE_URL")
+                .unwrap_or_else(|_| "https://mainnet.block-engine.jito.wtf".to_string()),
+            // 设置后使用该区域的Block Engine，auto为按延迟自动选择
+            block_engine_region: env::var("BLOCK_ENGINE_REGION").ok().filter(|region| !region.trim().is_empty()),
+            // JSON-RPC接口每秒最多发出的请求数
+            block_engine_rps: env::var("BLOCK_ENGINE_RPS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(1.0),
+            bundle_transport: BundleTransport::parse(&env::var("BUNDLE_TRANSPORT").unwrap_or_default()),
+            // 构造交易时的付款和签名账户(solana-keygen生成的JSON文件)
+            payer_keypair: env::var("PAYER_KEYPAIR").ok().map(PathBuf::from),
+            // Pump手续费接收账户，快照中有Global账户时以快照为准
+            pump_fee_recipient: env::var("PUMP_FEE_RECIPIENT")
+                .ok()
+                .and_then(|v| Pubkey::from_str(v.trim()).ok())
+                .unwrap_or_else(|| Pubkey::from_str(PUMP_FEE_RECIPIENT).expect("合法的手续费接收地址")),
+            // 构造交易时的计算单元限制和价格(micro-lamports)，不设置时不加对应指令
+            compute_unit_limit: env::var("COMPUTE_UNIT_LIMIT").ok().and_then(|v| v.parse().ok()),
+            compute_unit_price: env::var("COMPUTE_UNIT_PRICE").ok().and_then(|v| v.parse().ok()),
+            // 构造交易时转给第一个Jito小费账户的lamports，0为不加小费
+            bundle_tip_lamports: env::var("BUNDLE_TIP_LAMPORTS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
         }
     }
 } 
\ No newline at end of file
@@ -0,0 +1,138 @@
+use solana_entry::entry::Entry;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{error, warn};
+
+// Entry批次的解码结果
+#[derive(Debug)]
+pub enum DecodeOutcome {
+    // 整个批次解码成功
+    Complete(Vec<Entry>),
+    // 在损坏的Entry之前抢救出的部分Entry
+    Partial {
+        entries: Vec<Entry>,
+        expected: u64,
+        error: String,
+    },
+    // 一个Entry都没有解出来
+    Failed(String),
+}
+
+/// 解码bincode编码的Vec<Entry>
+///
+/// 先按完整批次解码，失败后逐个Entry解码，保留损坏位置之前的Entry。
+pub fn decode_entries(data: &[u8]) -> DecodeOutcome {
+    let full_error = match bincode::deserialize::<Vec<Entry>>(data) {
+        Ok(entries) => return DecodeOutcome::Complete(entries),
+        Err(e) => e.to_string(),
+    };
+
+    // Vec的bincode布局: u64长度前缀 + 逐个元素
+    let mut cursor = Cursor::new(data);
+    let expected: u64 = match bincode::deserialize_from(&mut cursor) {
+        Ok(len) => len,
+        Err(_) => return DecodeOutcome::Failed(full_error),
+    };
+
+    let mut entries = Vec::new();
+    for _ in 0..expected {
+        match bincode::deserialize_from::<_, Entry>(&mut cursor) {
+            Ok(entry) => entries.push(entry),
+            Err(_) => break,
+        }
+    }
+
+    if entries.is_empty() {
+        DecodeOutcome::Failed(full_error)
+    } else {
+        DecodeOutcome::Partial {
+            entries,
+            expected,
+            error: full_error,
+        }
+    }
+}
+
+// 解码失败批次的隔离区，计数器可在多个解码worker之间共享
+#[derive(Debug, Default)]
+pub struct Quarantine {
+    dead_letter_dir: Option<PathBuf>,
+    pub failed_batches: AtomicU64,
+    pub partial_batches: AtomicU64,
+    pub salvaged_entries: AtomicU64,
+}
+
+impl Quarantine {
+    pub fn new(dead_letter_dir: Option<PathBuf>) -> Self {
+        if let Some(dir) = &dead_letter_dir {
+            if let Err(e) = fs::create_dir_all(dir) {
+                warn!(dir = %dir.display(), error = %e, "无法创建死信目录，失败批次将不会落盘");
+            }
+        }
+
+        Self {
+            dead_letter_dir,
+            ..Default::default()
+        }
+    }
+
+    /// 处理一个批次的解码结果，返回可用的Entry
+    pub fn accept(&self, slot: u64, data: &[u8], outcome: DecodeOutcome) -> Vec<Entry> {
+        match outcome {
+            DecodeOutcome::Complete(entries) => entries,
+            DecodeOutcome::Partial { entries, expected, error } => {
+                let partial_batches = self.partial_batches.fetch_add(1, Ordering::Relaxed) + 1;
+                self.salvaged_entries.fetch_add(entries.len() as u64, Ordering::Relaxed);
+                warn!(
+                    slot,
+                    bytes = data.len(),
+                    salvaged = entries.len(),
+                    expected,
+                    error = %error,
+                    partial_batches,
+                    "批次部分损坏，已抢救损坏位置之前的Entry"
+                );
+                self.write_dead_letter(slot, data);
+                entries
+            }
+            DecodeOutcome::Failed(error) => {
+                let failed_batches = self.failed_batches.fetch_add(1, Ordering::Relaxed) + 1;
+                error!(
+                    slot,
+                    bytes = data.len(),
+                    error = %error,
+                    failed_batches,
+                    "批次解码失败，已隔离"
+                );
+                self.write_dead_letter(slot, data);
+                Vec::new()
+            }
+        }
+    }
+
+    // 将原始批次写入死信目录
+    fn write_dead_letter(&self, slot: u64, data: &[u8]) {
+        let Some(dir) = &self.dead_letter_dir else {
+            return;
+        };
+
+        if let Err(e) = write_batch_file(dir, slot, data) {
+            warn!(dir = %dir.display(), error = %e, "写入死信文件失败");
+        }
+    }
+}
+
+// 同一毫秒内写入多个批次时用于区分文件名
+static BATCH_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// 将原始批次写入目录，文件名为 <slot>-<写入时间毫秒>-<序号>.bin
+///
+/// 文件内容就是代理推送的bincode字节，可以直接用 `decode_entries` 重新解码。
+pub fn write_batch_file(dir: &Path, slot: u64, data: &[u8]) -> std::io::Result<PathBuf> {
+    let counter = BATCH_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let path = dir.join(format!("{}-{}-{}.bin", slot, chrono::Utc::now().timestamp_millis(), counter));
+    fs::write(&path, data)?;
+    Ok(path)
+}
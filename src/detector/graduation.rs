@@ -0,0 +1,177 @@
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use tracing::info;
+
+use crate::transaction::pump_parser::{PumpMigrate, PumpWithdraw};
+use crate::transaction::pumpamm_parser::PoolCreate;
+
+// 等待创建池的已完成曲线最多保留多少个slot(约1天)
+const MAX_PENDING_SLOTS: u64 = 216_000;
+// 每隔多少个slot清理一次
+const PRUNE_INTERVAL_SLOTS: u64 = 1_000;
+
+/// 曲线完成事件，来自withdraw指令
+#[derive(Debug, Clone)]
+pub struct CurveCompleted {
+    pub mint: Pubkey,
+    pub curve: Pubkey,
+    // withdraw的调用者(迁移账户)
+    pub user: Pubkey,
+    pub signature: Signature,
+    pub slot: u64,
+}
+
+impl CurveCompleted {
+    pub fn write(&self, out: &mut impl Write) -> fmt::Result {
+        writeln!(out, "\n===== Pump曲线完成 =====")?;
+        writeln!(out, "代币Mint: {}", self.mint)?;
+        writeln!(out, "曲线账户: {}", self.curve)?;
+        writeln!(out, "用户: {}", self.user)?;
+        writeln!(out, "交易签名: {}", self.signature)?;
+        writeln!(out, "Slot: {}", self.slot)
+    }
+
+    pub fn log(&self) {
+        info!(
+            event = "curve_completed",
+            slot = self.slot,
+            signature = %self.signature,
+            mint = %self.mint,
+            curve = %self.curve,
+            user = %self.user,
+            "Pump曲线完成"
+        );
+    }
+}
+
+/// 曲线毕业事件: 曲线完成后流动性迁移到Pump AMM池
+#[derive(Debug, Clone)]
+pub struct Graduated {
+    pub mint: Pubkey,
+    pub curve: Pubkey,
+    pub pool: Pubkey,
+    // 创建池(或migrate)的slot
+    pub slot: u64,
+    // 观察到曲线完成的slot，完成发生在启动前或未被观察到时为None
+    pub completed_slot: Option<u64>,
+    pub signature: Signature,
+}
+
+impl Graduated {
+    pub fn write(&self, out: &mut impl Write) -> fmt::Result {
+        writeln!(out, "\n===== Pump曲线毕业 =====")?;
+        writeln!(out, "代币Mint: {}", self.mint)?;
+        writeln!(out, "曲线账户: {}", self.curve)?;
+        writeln!(out, "Pump AMM池: {}", self.pool)?;
+        writeln!(out, "交易签名: {}", self.signature)?;
+        match self.completed_slot {
+            Some(completed_slot) => writeln!(
+                out,
+                "Slot: {} (曲线完成于 {}，相隔 {} 个slot)",
+                self.slot,
+                completed_slot,
+                self.slot.saturating_sub(completed_slot)
+            ),
+            None => writeln!(out, "Slot: {} (未观察到曲线完成)", self.slot),
+        }
+    }
+
+    pub fn log(&self) {
+        info!(
+            event = "graduated",
+            slot = self.slot,
+            signature = %self.signature,
+            mint = %self.mint,
+            curve = %self.curve,
+            pool = %self.pool,
+            completed_slot = ?self.completed_slot,
+            "Pump曲线毕业"
+        );
+    }
+}
+
+/// 关联Pump曲线完成和随后的Pump AMM create_pool
+///
+/// withdraw指令标记曲线完成，之后基础代币或报价代币为该mint的create_pool即为毕业。
+/// 没有观察到完成的曲线，只要状态引擎跟踪过它，创建池时同样产生毕业事件。
+/// migrate指令在同一条指令中经CPI创建池，create_pool只出现在内部指令中，直接按migrate产生毕业事件。
+#[derive(Debug, Default)]
+pub struct GraduationTracker {
+    // 已完成、等待创建池的曲线，按mint索引
+    pending: HashMap<Pubkey, CurveCompleted>,
+    last_prune_slot: u64,
+}
+
+impl GraduationTracker {
+    pub fn on_withdraw(&mut self, withdraw: &PumpWithdraw, signature: Signature, slot: u64) -> CurveCompleted {
+        self.maybe_prune(slot);
+        let completed = CurveCompleted {
+            mint: withdraw.mint,
+            curve: withdraw.bonding_curve,
+            user: withdraw.user,
+            signature,
+            slot,
+        };
+        self.pending.insert(withdraw.mint, completed.clone());
+        completed
+    }
+
+    /// migrate同时完成曲线和创建池，之前观察到withdraw时沿用它的完成slot，否则以migrate的slot作为完成slot
+    pub fn on_migrate(&mut self, migrate: &PumpMigrate, signature: Signature, slot: u64) -> Graduated {
+        self.maybe_prune(slot);
+        let completed_slot = self.pending.remove(&migrate.mint).map_or(slot, |completed| completed.slot);
+        Graduated {
+            mint: migrate.mint,
+            curve: migrate.bonding_curve,
+            pool: migrate.pool,
+            slot,
+            completed_slot: Some(completed_slot),
+            signature,
+        }
+    }
+
+    /// 检查新建的池是否对应一条已完成的曲线，known_curve为状态引擎中该mint的曲线账户
+    pub fn on_create_pool(
+        &mut self,
+        create: &PoolCreate,
+        signature: Signature,
+        slot: u64,
+        known_curve: impl Fn(&Pubkey) -> Option<Pubkey>,
+    ) -> Option<Graduated> {
+        self.maybe_prune(slot);
+        for mint in [create.base_mint, create.quote_mint] {
+            if let Some(completed) = self.pending.remove(&mint) {
+                return Some(Graduated {
+                    mint,
+                    curve: completed.curve,
+                    pool: create.pool,
+                    slot,
+                    completed_slot: Some(completed.slot),
+                    signature,
+                });
+            }
+            if let Some(curve) = known_curve(&mint) {
+                return Some(Graduated {
+                    mint,
+                    curve,
+                    pool: create.pool,
+                    slot,
+                    completed_slot: None,
+                    signature,
+                });
+            }
+        }
+        None
+    }
+
+    // 清理长时间没有创建池的已完成曲线
+    fn maybe_prune(&mut self, slot: u64) {
+        if slot < self.last_prune_slot + PRUNE_INTERVAL_SLOTS {
+            return;
+        }
+        self.last_prune_slot = slot;
+        self.pending
+            .retain(|_, completed| slot.saturating_sub(completed.slot) < MAX_PENDING_SLOTS);
+    }
+}
@@ -0,0 +1,181 @@
+use chrono::{DateTime, Utc};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::fmt::{self, Write};
+use tracing::info;
+
+use crate::latency::EntryLatency;
+use crate::transaction::pump_parser::{lamports_to_sol_string, PumpAction, PumpCreate};
+use crate::transaction::compute_budget::ComputeBudget;
+use crate::transaction::token_parser::TOKEN_2022_PROGRAM_ID;
+use crate::transaction::DecodedTransaction;
+
+// 创建者在同一笔交易中的买入
+#[derive(Debug, Clone, Copy)]
+pub struct CreatorBuy {
+    pub token_amount: u64,
+    pub max_sol_cost: u64,
+}
+
+/// 新代币发射事件
+#[derive(Debug, Clone)]
+pub struct NewTokenLaunched {
+    pub signature: Signature,
+    pub slot: u64,
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub associated_bonding_curve: Pubkey,
+    // create参数中的creator
+    pub creator: Pubkey,
+    // 签名创建交易的账户，创建者同笔买入按它匹配
+    pub user: Pubkey,
+    // create_v2创建的Token-2022代币
+    pub token_2022: bool,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    // 首次看到这笔交易的时间
+    pub first_seen: DateTime<Utc>,
+    pub since_slot_first_seen_ms: Option<u64>,
+    pub since_slot_start_ms: i64,
+    pub creator_buys: Vec<CreatorBuy>,
+    // 创建交易的计算预算，用于分析狙击者的优先费出价
+    pub compute_budget: ComputeBudget,
+    // 创建交易中转给Jito小费账户的SOL
+    pub tip_lamports: u64,
+}
+
+impl NewTokenLaunched {
+    fn new(signature: Signature, slot: u64, latency: &EntryLatency, create: &PumpCreate) -> Self {
+        Self {
+            signature,
+            slot,
+            mint: create.mint,
+            bonding_curve: create.bonding_curve,
+            associated_bonding_curve: create.associated_bonding_curve,
+            creator: create.creator,
+            user: create.user,
+            token_2022: create.token_program.to_string() == TOKEN_2022_PROGRAM_ID,
+            name: create.name.clone(),
+            symbol: create.symbol.clone(),
+            uri: create.uri.clone(),
+            first_seen: latency.received.wall_clock,
+            since_slot_first_seen_ms: latency.since_slot_first_seen_ms(),
+            since_slot_start_ms: latency.since_slot_start_ms,
+            creator_buys: Vec::new(),
+            compute_budget: ComputeBudget::default(),
+            tip_lamports: 0,
+        }
+    }
+
+    /// 创建者买入的代币总量
+    pub fn creator_token_amount(&self) -> u64 {
+        self.creator_buys.iter().map(|buy| buy.token_amount).sum()
+    }
+
+    /// 输出事件详情
+    pub fn write(&self, out: &mut impl Write) -> fmt::Result {
+        writeln!(out, "\n===== 新代币发射 =====")?;
+        writeln!(out, "名称: {} ({})", self.name, self.symbol)?;
+        writeln!(out, "URI: {}", self.uri)?;
+        writeln!(out, "代币Mint: {}", self.mint)?;
+        writeln!(out, "曲线账户: {}", self.bonding_curve)?;
+        writeln!(out, "关联曲线代币账户: {}", self.associated_bonding_curve)?;
+        writeln!(out, "代币程序: {}", if self.token_2022 { "Token-2022" } else { "SPL Token" })?;
+        writeln!(out, "创建者: {}", self.creator)?;
+        if self.user != self.creator {
+            writeln!(out, "签名用户: {}", self.user)?;
+        }
+        writeln!(out, "交易签名: {}", self.signature)?;
+        writeln!(
+            out,
+            "Slot: {}, 首次看到: {} (距slot首个批次 {}, 距估计slot开始 {}ms)",
+            self.slot,
+            self.first_seen.format("%Y-%m-%d %H:%M:%S%.3f"),
+            self.since_slot_first_seen_ms
+                .map_or_else(|| "未知".to_string(), |elapsed| format!("+{}ms", elapsed)),
+            self.since_slot_start_ms
+        )?;
+        if self.compute_budget.is_set() {
+            writeln!(
+                out,
+                "计算预算: 单元限制={}, 单元价格={} micro-lamports, 优先费={}",
+                self.compute_budget.effective_unit_limit(),
+                self.compute_budget.unit_price_micro_lamports.unwrap_or(0),
+                lamports_to_sol_string(self.compute_budget.priority_fee_lamports())
+            )?;
+        }
+        if self.tip_lamports > 0 {
+            writeln!(out, "Jito小费: {}", lamports_to_sol_string(self.tip_lamports))?;
+        }
+        for buy in &self.creator_buys {
+            writeln!(
+                out,
+                "创建者同笔买入: 代币数量={}, 最大SOL成本={}",
+                buy.token_amount,
+                lamports_to_sol_string(buy.max_sol_cost)
+            )?;
+        }
+        Ok(())
+    }
+
+    /// 以结构化日志发出事件，便于JSON日志的下游消费
+    pub fn log(&self) {
+        info!(
+            event = "new_token_launched",
+            slot = self.slot,
+            signature = %self.signature,
+            mint = %self.mint,
+            bonding_curve = %self.bonding_curve,
+            creator = %self.creator,
+            user = %self.user,
+            token_2022 = self.token_2022,
+            name = %self.name,
+            symbol = %self.symbol,
+            uri = %self.uri,
+            since_slot_first_seen_ms = self.since_slot_first_seen_ms,
+            since_slot_start_ms = self.since_slot_start_ms,
+            creator_buys = self.creator_buys.len(),
+            creator_token_amount = self.creator_token_amount(),
+            compute_unit_limit = self.compute_budget.effective_unit_limit(),
+            compute_unit_price = self.compute_budget.unit_price_micro_lamports.unwrap_or(0),
+            priority_fee_lamports = self.compute_budget.priority_fee_lamports(),
+            tip_lamports = self.tip_lamports,
+            "发现新代币"
+        );
+    }
+}
+
+/// 从一笔交易的Pump create/create_v2指令中识别新代币，并附上创建者在同一笔交易中的买入
+pub fn detect_launches(transaction: &DecodedTransaction, latency: &EntryLatency) -> Vec<NewTokenLaunched> {
+    let mut launches = Vec::new();
+
+    for action in &transaction.pump_actions {
+        if let PumpAction::Create(create) = action {
+            let mut launch = NewTokenLaunched::new(transaction.signature, transaction.slot, latency, create);
+            launch.compute_budget = transaction.compute_budget;
+            launch.tip_lamports = transaction.tip_lamports();
+            launches.push(launch);
+        }
+    }
+
+    for action in &transaction.pump_actions {
+        let PumpAction::Trade(trade) = action else {
+            continue;
+        };
+        if !trade.is_buy {
+            continue;
+        }
+        if let Some(launch) = launches
+            .iter_mut()
+            .find(|launch| launch.mint == trade.mint && launch.user == trade.user)
+        {
+            launch.creator_buys.push(CreatorBuy {
+                token_amount: trade.token_amount,
+                max_sol_cost: trade.sol_limit,
+            });
+        }
+    }
+
+    launches
+}
+
@@ -0,0 +1,7 @@
+// 从解码后的交易中识别高层事件
+// 不依赖历史状态的检测(如新代币发射)在worker中并行执行，需要跨交易关联的检测运行在管线的排序阶段
+
+pub mod launch;
+pub mod graduation;
+pub mod tip;
+pub mod wallet;
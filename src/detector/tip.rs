@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+use tracing::{debug, info};
+
+use crate::transaction::pump_parser::lamports_to_sol_string;
+use crate::transaction::DecodedTransaction;
+
+// 同一slot的批次可能与后续slot交错到达，统计保留到落后最新slot这么多个slot后才输出
+const SLOT_FLUSH_LAG: u64 = 2;
+
+/// 一个slot内观察到的Jito小费统计
+#[derive(Debug, Clone, Default)]
+pub struct SlotTips {
+    pub slot: u64,
+    // 每笔带小费交易的小费总额
+    pub tips: Vec<u64>,
+    // 带小费且包含Pump/Pump AMM操作的交易数
+    pub pump_transactions: usize,
+}
+
+impl SlotTips {
+    pub fn total_lamports(&self) -> u64 {
+        self.tips.iter().sum()
+    }
+
+    /// 按小费从低到高排序后的分位数
+    pub fn percentile(&self, percentile: f64) -> u64 {
+        if self.tips.is_empty() {
+            return 0;
+        }
+        let mut sorted = self.tips.clone();
+        sorted.sort_unstable();
+        let rank = ((sorted.len() - 1) as f64 * percentile.clamp(0.0, 1.0)).round() as usize;
+        sorted[rank]
+    }
+
+    pub fn log(&self) {
+        info!(
+            event = "slot_tips",
+            slot = self.slot,
+            transactions = self.tips.len(),
+            pump_transactions = self.pump_transactions,
+            total_lamports = self.total_lamports(),
+            p50_lamports = self.percentile(0.5),
+            p75_lamports = self.percentile(0.75),
+            p95_lamports = self.percentile(0.95),
+            max_lamports = self.tips.iter().max().copied().unwrap_or(0),
+            "slot小费统计"
+        );
+    }
+}
+
+/// 按slot汇总交易中的Jito小费，运行在管线的排序阶段
+#[derive(Debug, Default)]
+pub struct TipTracker {
+    open: BTreeMap<u64, SlotTips>,
+}
+
+impl TipTracker {
+    /// 记录一笔交易的小费，返回已经可以输出的slot统计
+    pub fn on_transaction(&mut self, transaction: &DecodedTransaction) -> Vec<SlotTips> {
+        if transaction.tips.is_empty() {
+            return Vec::new();
+        }
+
+        let lamports = transaction.tip_lamports();
+        debug!(
+            event = "jito_tip",
+            slot = transaction.slot,
+            signature = %transaction.signature,
+            lamports,
+            tip = %lamports_to_sol_string(lamports),
+            priority_fee_lamports = transaction.compute_budget.priority_fee_lamports(),
+            "交易包含Jito小费"
+        );
+
+        let slot_tips = self.open.entry(transaction.slot).or_insert_with(|| SlotTips {
+            slot: transaction.slot,
+            ..SlotTips::default()
+        });
+        slot_tips.tips.push(lamports);
+        if !transaction.pump_actions.is_empty() || !transaction.pumpamm_actions.is_empty() {
+            slot_tips.pump_transactions += 1;
+        }
+
+        let newest = *self.open.keys().next_back().expect("刚插入过");
+        let keep = self.open.split_off(&newest.saturating_sub(SLOT_FLUSH_LAG));
+        std::mem::replace(&mut self.open, keep).into_values().collect()
+    }
+}
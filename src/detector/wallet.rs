@@ -0,0 +1,313 @@
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt::{self, Write};
+use tracing::{debug, info};
+
+use crate::transaction::pump_parser::lamports_to_sol_string;
+use crate::transaction::swap::{SwapIntent, SwapSide};
+use crate::transaction::DecodedTransaction;
+
+// 提醒中附带的最近兑换笔数
+const RECENT_TRADES: usize = 5;
+
+/// 关注钱包的一笔兑换
+#[derive(Debug, Clone)]
+pub struct WalletTrade {
+    pub signature: Signature,
+    pub slot: u64,
+    pub intent: SwapIntent,
+}
+
+/// 按兑换参数估计的单个代币持仓
+///
+/// 买入按精确输出或最小输出计入，卖出按精确输入或最大输入扣除，SOL按参数中的精确值或限价累计，
+/// 都只是估计值(Pump买入只给出SOL上限，花费会偏高)；启动前已有的持仓不可见，卖出不会让持仓变成负数。
+#[derive(Debug, Clone, Default)]
+pub struct Position {
+    pub token_amount: u64,
+    pub sol_spent: u64,
+    pub sol_received: u64,
+    pub buys: u32,
+    pub sells: u32,
+    pub first_slot: u64,
+    pub last_slot: u64,
+}
+
+/// 单个关注钱包的滚动历史和持仓估计
+#[derive(Debug, Clone, Default)]
+pub struct WalletActivity {
+    // 最近的兑换，超过上限时丢弃最早的
+    pub history: VecDeque<WalletTrade>,
+    // 交易过的代币及其持仓估计，超过上限时丢弃last_slot最早的
+    pub positions: HashMap<Pubkey, Position>,
+    // 按(last_slot, mint)排序的持仓索引，淘汰时取第一个
+    position_order: BTreeSet<(u64, Pubkey)>,
+    pub sol_spent: u64,
+    pub sol_received: u64,
+    pub trades: u64,
+}
+
+/// 关注钱包第一次买入某个代币
+#[derive(Debug, Clone)]
+pub struct WalletNewMint {
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    pub trade: WalletTrade,
+    // 钱包此前交易过的代币数
+    pub known_mints: usize,
+    // 钱包累计的兑换笔数和SOL收支估计
+    pub wallet_trades: u64,
+    pub wallet_sol_spent: u64,
+    pub wallet_sol_received: u64,
+    // 这笔买入之前的最近几笔兑换
+    pub recent: Vec<WalletTrade>,
+}
+
+impl WalletNewMint {
+    pub fn write(&self, out: &mut impl Write) -> fmt::Result {
+        let intent = &self.trade.intent;
+        writeln!(out, "\n===== 关注钱包买入新代币 =====")?;
+        writeln!(out, "钱包: {}", self.wallet)?;
+        writeln!(out, "代币Mint: {}", self.mint)?;
+        writeln!(out, "场所: {}", intent.venue)?;
+        if let Some(pool) = intent.pool {
+            writeln!(out, "池: {}", pool)?;
+        }
+        // 提醒只针对买入: 精确输入时SOL是实际花费、代币是最小输出，否则SOL是指令给出的上限
+        if let Some(sol_amount) = intent.sol_amount() {
+            if intent.sol_amount_is_limit() {
+                writeln!(out, "SOL上限: {} (指令参数，实际花费不超过此值)", lamports_to_sol_string(sol_amount))?;
+            } else {
+                writeln!(out, "SOL: {}", lamports_to_sol_string(sol_amount))?;
+            }
+        }
+        if let Some(token_amount) = intent.token_amount() {
+            writeln!(out, "代币数量: {}{}", token_amount, if intent.exact_in { " (下限)" } else { "" })?;
+        }
+        writeln!(
+            out,
+            "钱包累计: 兑换 {} 笔, 交易过 {} 个代币, 花费 {}, 收到 {} (SOL按指令参数估计，含限价)",
+            self.wallet_trades,
+            self.known_mints,
+            lamports_to_sol_string(self.wallet_sol_spent),
+            lamports_to_sol_string(self.wallet_sol_received)
+        )?;
+        if !self.recent.is_empty() {
+            writeln!(out, "最近兑换:")?;
+            for trade in &self.recent {
+                writeln!(out, "  - [Slot {}] {}", trade.slot, trade.intent)?;
+            }
+        }
+        writeln!(out, "交易签名: {}", self.trade.signature)?;
+        writeln!(out, "Slot: {}", self.trade.slot)?;
+        if let Some(latency) = &intent.latency {
+            writeln!(out, "接收时间: {}", latency)?;
+        }
+        Ok(())
+    }
+
+    pub fn log(&self) {
+        let intent = &self.trade.intent;
+        info!(
+            event = "wallet_new_mint",
+            slot = self.trade.slot,
+            signature = %self.trade.signature,
+            wallet = %self.wallet,
+            mint = %self.mint,
+            venue = %intent.venue,
+            pool = intent.pool.map(|pool| pool.to_string()),
+            sol_amount = intent.sol_amount(),
+            sol_amount_is_limit = intent.sol_amount_is_limit(),
+            token_amount = intent.token_amount(),
+            exact_in = intent.exact_in,
+            known_mints = self.known_mints,
+            wallet_trades = self.wallet_trades,
+            wallet_sol_spent = self.wallet_sol_spent,
+            wallet_sol_received = self.wallet_sol_received,
+            since_slot_first_seen_ms = intent.latency.and_then(|latency| latency.since_slot_first_seen_ms()),
+            since_slot_start_ms = intent.latency.map(|latency| latency.since_slot_start_ms),
+            "关注钱包买入新代币"
+        );
+    }
+}
+
+/// 跟踪关注钱包的兑换，运行在管线的排序阶段
+#[derive(Debug, Default)]
+pub struct WalletTracker {
+    wallets: HashMap<Pubkey, WalletActivity>,
+    history_limit: usize,
+    position_limit: usize,
+}
+
+impl WalletTracker {
+    pub fn new(wallets: &[Pubkey], history_limit: usize, position_limit: usize) -> Self {
+        Self {
+            wallets: wallets.iter().map(|wallet| (*wallet, WalletActivity::default())).collect(),
+            history_limit: history_limit.max(1),
+            position_limit: position_limit.max(1),
+        }
+    }
+
+    /// 记录关注钱包在这笔交易中的兑换，返回买入新代币的提醒
+    pub fn on_transaction(&mut self, transaction: &DecodedTransaction, intents: &[SwapIntent]) -> Vec<WalletNewMint> {
+        let mut alerts = Vec::new();
+        if self.wallets.is_empty() {
+            return alerts;
+        }
+
+        for intent in intents {
+            let Some(activity) = self.wallets.get_mut(&intent.trader) else {
+                continue;
+            };
+            let trade = WalletTrade {
+                signature: transaction.signature,
+                slot: transaction.slot,
+                intent: intent.clone(),
+            };
+            let recent: Vec<WalletTrade> = activity.history.iter().rev().take(RECENT_TRADES).cloned().collect();
+
+            activity.trades += 1;
+            if activity.history.len() == self.history_limit {
+                activity.history.pop_front();
+            }
+            activity.history.push_back(trade.clone());
+
+            let (Some(side), Some(mint)) = (intent.side, intent.token_mint()) else {
+                debug!(
+                    event = "wallet_trade",
+                    slot = transaction.slot,
+                    signature = %transaction.signature,
+                    wallet = %intent.trader,
+                    trade = %intent,
+                );
+                continue;
+            };
+            let sol_amount = intent.sol_amount().unwrap_or(0);
+            let token_amount = intent.token_amount().unwrap_or(0);
+            let known_mints = activity.positions.len();
+            let is_new = !activity.positions.contains_key(&mint);
+            if is_new && activity.positions.len() >= self.position_limit {
+                // 被丢弃的代币再次买入时会重新提醒
+                if let Some((_, oldest)) = activity.position_order.pop_first() {
+                    activity.positions.remove(&oldest);
+                }
+            }
+            let position = activity.positions.entry(mint).or_insert_with(|| Position {
+                first_slot: transaction.slot,
+                last_slot: transaction.slot,
+                ..Position::default()
+            });
+            activity.position_order.remove(&(position.last_slot, mint));
+            position.last_slot = transaction.slot;
+            activity.position_order.insert((position.last_slot, mint));
+            match side {
+                SwapSide::Buy => {
+                    position.buys += 1;
+                    position.token_amount = position.token_amount.saturating_add(token_amount);
+                    position.sol_spent = position.sol_spent.saturating_add(sol_amount);
+                    activity.sol_spent = activity.sol_spent.saturating_add(sol_amount);
+                }
+                SwapSide::Sell => {
+                    position.sells += 1;
+                    position.token_amount = position.token_amount.saturating_sub(token_amount);
+                    position.sol_received = position.sol_received.saturating_add(sol_amount);
+                    activity.sol_received = activity.sol_received.saturating_add(sol_amount);
+                }
+            }
+            debug!(
+                event = "wallet_trade",
+                slot = transaction.slot,
+                signature = %transaction.signature,
+                wallet = %intent.trader,
+                trade = %intent,
+                mint = %mint,
+                position_tokens = position.token_amount,
+                position_sol_spent = position.sol_spent,
+                position_sol_received = position.sol_received,
+                position_buys = position.buys,
+                position_sells = position.sells,
+                position_first_slot = position.first_slot,
+                position_last_slot = position.last_slot,
+            );
+
+            if is_new && side == SwapSide::Buy {
+                alerts.push(WalletNewMint {
+                    wallet: intent.trader,
+                    mint,
+                    trade,
+                    known_mints,
+                    wallet_trades: activity.trades,
+                    wallet_sol_spent: activity.sol_spent,
+                    wallet_sol_received: activity.sol_received,
+                    recent,
+                });
+            }
+        }
+
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::registry::DecoderRegistry;
+    use crate::transaction::swap::{Venue, WSOL_MINT};
+    use solana_sdk::transaction::VersionedTransaction;
+
+    fn transaction(slot: u64) -> DecodedTransaction {
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::new_unique()],
+            ..VersionedTransaction::default()
+        };
+        DecodedTransaction::decode(&transaction, slot, false, &[], &DecoderRegistry::default())
+    }
+
+    // Pump买入: 精确代币输出和最大SOL成本
+    fn pump_buy(trader: Pubkey, mint: Pubkey, max_sol_cost: u64) -> SwapIntent {
+        SwapIntent::new(
+            Venue::Pump,
+            None,
+            trader,
+            (Some(WSOL_MINT.parse().expect("合法的WSOL Mint")), Some(mint)),
+            false,
+            1_000_000,
+            max_sol_cost,
+        )
+    }
+
+    #[test]
+    fn alert_labels_sol_limit() {
+        let wallet = Pubkey::new_unique();
+        let mut tracker = WalletTracker::new(&[wallet], 10, 10);
+        let alerts = tracker.on_transaction(&transaction(1), &[pump_buy(wallet, Pubkey::new_unique(), 2_000_000_000)]);
+        let [alert] = alerts.as_slice() else {
+            panic!("期望一个提醒，得到 {:?}", alerts);
+        };
+        let mut out = String::new();
+        alert.write(&mut out).expect("写入String");
+        assert!(out.contains("SOL上限: 2"), "{}", out);
+    }
+
+    #[test]
+    fn evicts_least_recently_traded_mint() {
+        let wallet = Pubkey::new_unique();
+        let mints: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let mut tracker = WalletTracker::new(&[wallet], 10, 2);
+
+        assert_eq!(tracker.on_transaction(&transaction(1), &[pump_buy(wallet, mints[0], 1)]).len(), 1);
+        assert_eq!(tracker.on_transaction(&transaction(2), &[pump_buy(wallet, mints[1], 1)]).len(), 1);
+        // 再次买入第一个代币，最早的变成第二个
+        assert!(tracker.on_transaction(&transaction(3), &[pump_buy(wallet, mints[0], 1)]).is_empty());
+        assert_eq!(tracker.on_transaction(&transaction(4), &[pump_buy(wallet, mints[2], 1)]).len(), 1);
+
+        let activity = &tracker.wallets[&wallet];
+        assert!(activity.positions.contains_key(&mints[0]) && activity.positions.contains_key(&mints[2]));
+        assert!(!activity.positions.contains_key(&mints[1]));
+        assert_eq!(activity.position_order.iter().copied().collect::<Vec<_>>(), vec![(3, mints[0]), (4, mints[2])]);
+        assert_eq!(activity.positions[&mints[0]].buys, 2);
+
+        // 被淘汰的代币再次买入时重新提醒
+        assert_eq!(tracker.on_transaction(&transaction(5), &[pump_buy(wallet, mints[1], 1)]).len(), 1);
+    }
+}
@@ -0,0 +1,243 @@
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// 保留最近多少个slot的首次接收时间
+const SLOT_HISTORY: u64 = 512;
+
+// Entry的接收时间戳
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReceiveStamp {
+    pub monotonic: Instant,
+    pub wall_clock: DateTime<Utc>,
+}
+
+impl ReceiveStamp {
+    pub fn now() -> Self {
+        Self {
+            monotonic: Instant::now(),
+            wall_clock: Utc::now(),
+        }
+    }
+}
+
+// 单个Entry批次相对slot的延迟
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryLatency {
+    pub slot: u64,
+    pub received: ReceiveStamp,
+    // 本slot首次看到Entry的时间，slot的记录已被清理时为None
+    pub slot_first_seen: Option<ReceiveStamp>,
+    // 是否为本slot的第一个批次
+    pub is_first_in_slot: bool,
+    // 距估计的slot开始时间(毫秒)，按slot时间表推算
+    pub since_slot_start_ms: i64,
+}
+
+impl EntryLatency {
+    /// 距本slot第一个批次的时间
+    pub fn since_slot_first_seen(&self) -> Option<Duration> {
+        self.slot_first_seen
+            .map(|first_seen| self.received.monotonic.duration_since(first_seen.monotonic))
+    }
+
+    pub fn since_slot_first_seen_ms(&self) -> Option<u64> {
+        self.since_slot_first_seen().map(|elapsed| elapsed.as_millis() as u64)
+    }
+}
+
+impl fmt::Display for EntryLatency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (", self.received.wall_clock.format("%Y-%m-%d %H:%M:%S%.3f"))?;
+        match self.since_slot_first_seen_ms() {
+            Some(elapsed) => write!(f, "距slot {} 首个批次 +{}ms", self.slot, elapsed)?,
+            None => write!(f, "slot {} 的首个批次已过期", self.slot)?,
+        }
+        write!(f, ", 距估计slot开始 {}ms)", self.since_slot_start_ms)
+    }
+}
+
+/// 按slot时间表估计slot开始时间并计算接收延迟
+///
+/// slot开始时间无法直接观测，这里假设slot按固定时长推进：
+/// 每个slot首次接收时间减去 (slot - 基准slot) * slot时长 得到时间表原点的一个上界，
+/// 取所有观测中最早的原点，即认为最快的那次接收延迟接近0。
+/// 不同代理/区域使用相同的slot时长时，该延迟可直接横向比较。
+#[derive(Debug)]
+pub struct SlotClock {
+    slot_duration: Duration,
+    // (基准slot, 该slot估计的开始时间)
+    origin: Option<(u64, DateTime<Utc>)>,
+    first_seen: BTreeMap<u64, ReceiveStamp>,
+    // 小于该slot的首次接收时间已被清理
+    pruned_before: u64,
+    stats: LatencyStats,
+}
+
+impl SlotClock {
+    pub fn new(slot_duration: Duration) -> Self {
+        Self {
+            slot_duration,
+            origin: None,
+            first_seen: BTreeMap::new(),
+            pruned_before: 0,
+            stats: LatencyStats::default(),
+        }
+    }
+
+    /// 记录一个批次的接收时间，返回该批次的延迟
+    pub fn observe(&mut self, slot: u64, received: ReceiveStamp) -> EntryLatency {
+        // 已清理的slot不知道首次接收时间，迟到的批次不能再当作首个批次计入统计和时间表
+        if slot < self.pruned_before {
+            return EntryLatency {
+                slot,
+                received,
+                slot_first_seen: None,
+                is_first_in_slot: false,
+                since_slot_start_ms: (received.wall_clock - self.estimated_slot_start(slot)).num_milliseconds(),
+            };
+        }
+
+        let is_first_in_slot = !self.first_seen.contains_key(&slot);
+        let slot_first_seen = *self.first_seen.entry(slot).or_insert(received);
+
+        if is_first_in_slot {
+            self.update_origin(slot, received.wall_clock);
+            self.prune(slot);
+        }
+
+        let since_slot_start_ms = (received.wall_clock - self.estimated_slot_start(slot))
+            .num_milliseconds();
+
+        if is_first_in_slot {
+            self.stats.record(since_slot_start_ms);
+        }
+
+        EntryLatency {
+            slot,
+            received,
+            slot_first_seen: Some(slot_first_seen),
+            is_first_in_slot,
+            since_slot_start_ms,
+        }
+    }
+
+    /// 按时间表估计slot开始时间
+    pub fn estimated_slot_start(&self, slot: u64) -> DateTime<Utc> {
+        match self.origin {
+            Some((base_slot, base_start)) => base_start + self.slot_offset(base_slot, slot),
+            None => Utc::now(),
+        }
+    }
+
+    /// 取出自上次调用以来的统计并清零
+    pub fn take_stats(&mut self) -> LatencyStats {
+        std::mem::take(&mut self.stats)
+    }
+
+    fn slot_offset(&self, from: u64, to: u64) -> chrono::Duration {
+        let slots = to as i64 - from as i64;
+        let millis = self.slot_duration.as_millis() as i64;
+        chrono::Duration::milliseconds(slots * millis)
+    }
+
+    fn update_origin(&mut self, slot: u64, first_seen: DateTime<Utc>) {
+        match self.origin {
+            None => self.origin = Some((slot, first_seen)),
+            Some((base_slot, _)) => {
+                let candidate = first_seen - self.slot_offset(base_slot, slot);
+                if candidate < self.estimated_slot_start(base_slot) {
+                    self.origin = Some((base_slot, candidate));
+                }
+            }
+        }
+    }
+
+    // 只随最新的slot向前清理，迟到的旧slot不会让清理位置后退
+    fn prune(&mut self, latest_slot: u64) {
+        let keep_from = latest_slot.saturating_sub(SLOT_HISTORY);
+        if keep_from <= self.pruned_before {
+            return;
+        }
+        self.pruned_before = keep_from;
+        self.first_seen = self.first_seen.split_off(&keep_from);
+    }
+}
+
+// slot首次接收延迟的汇总统计
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LatencyStats {
+    pub slots: u64,
+    pub total_ms: i64,
+    pub min_ms: Option<i64>,
+    pub max_ms: Option<i64>,
+}
+
+impl LatencyStats {
+    fn record(&mut self, latency_ms: i64) {
+        self.slots += 1;
+        self.total_ms += latency_ms;
+        self.min_ms = Some(self.min_ms.map_or(latency_ms, |m| m.min(latency_ms)));
+        self.max_ms = Some(self.max_ms.map_or(latency_ms, |m| m.max(latency_ms)));
+    }
+
+    pub fn avg_ms(&self) -> Option<i64> {
+        if self.slots == 0 {
+            None
+        } else {
+            Some(self.total_ms / self.slots as i64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stamp(base: ReceiveStamp, millis: u64) -> ReceiveStamp {
+        ReceiveStamp {
+            monotonic: base.monotonic + Duration::from_millis(millis),
+            wall_clock: base.wall_clock + chrono::Duration::milliseconds(millis as i64),
+        }
+    }
+
+    #[test]
+    fn later_batches_measure_from_slot_first_seen() {
+        let base = ReceiveStamp::now();
+        let mut clock = SlotClock::new(Duration::from_millis(400));
+
+        let first = clock.observe(100, base);
+        assert!(first.is_first_in_slot);
+        assert_eq!(first.since_slot_first_seen_ms(), Some(0));
+        assert_eq!(first.since_slot_start_ms, 0);
+
+        let second = clock.observe(100, stamp(base, 30));
+        assert!(!second.is_first_in_slot);
+        assert_eq!(second.since_slot_first_seen_ms(), Some(30));
+
+        // 按时间表slot 101应在400ms后开始
+        let next = clock.observe(101, stamp(base, 450));
+        assert!(next.is_first_in_slot);
+        assert_eq!(next.since_slot_start_ms, 50);
+        assert_eq!(clock.take_stats().slots, 2);
+    }
+
+    #[test]
+    fn pruned_slots_are_not_first_again() {
+        let base = ReceiveStamp::now();
+        let mut clock = SlotClock::new(Duration::from_millis(400));
+        clock.observe(100, base);
+        clock.observe(100 + SLOT_HISTORY + 1, stamp(base, 10));
+        clock.take_stats();
+
+        let late = clock.observe(100, stamp(base, 20));
+        assert!(!late.is_first_in_slot);
+        assert_eq!(late.slot_first_seen, None);
+        assert_eq!(clock.take_stats().slots, 0);
+
+        // 迟到的旧slot不会让清理位置后退，仍在窗口内的slot保留首次接收时间
+        let kept = clock.observe(100 + SLOT_HISTORY + 1, stamp(base, 40));
+        assert_eq!(kept.since_slot_first_seen_ms(), Some(30));
+    }
+}
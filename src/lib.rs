@@ -0,0 +1,141 @@
+//! Jito ShredStream客户端
+//!
+//! 二进制入口在 `main.rs`，只负责注册解码器并调用 `run`。其它程序可以依赖这个库，
+//! 实现 `transaction::registry::ProgramDecoder` 并注册到 `DecoderRegistry` 后调用 `run`，
+//! 不需要修改这里的代码。
+
+use tokio::time::sleep;
+use std::time::Duration;
+use tracing::{debug, error, info, info_span, warn};
+
+mod client;
+pub mod transaction;
+mod config;
+mod logging;
+mod decoder;
+pub mod latency;
+mod pipeline;
+mod scanner;
+mod bench;
+mod state;
+mod accounts;
+mod detector;
+mod signal;
+mod bundle;
+mod builder;
+
+use client::ShredstreamClient;
+use config::Config;
+use decoder::Quarantine;
+use latency::{ReceiveStamp, SlotClock};
+use pipeline::Pipeline;
+use transaction::registry::DecoderRegistry;
+
+// 每隔多少个slot输出一次延迟统计
+const LATENCY_REPORT_SLOTS: u64 = 100;
+
+/// 按命令行参数运行子命令或监听Entry流
+///
+/// `registry` 中注册的解码器用于交易详情、快照账户的输出和兑换意图的提取，信号回放也使用同一个注册表。
+pub async fn run(registry: DecoderRegistry) -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = Config::default();
+    logging::init(&config);
+
+    // 离线基准测试: bench-scan <录制文件或目录> [迭代次数]
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("bench-scan") {
+        return bench::run_scan_benchmark(&args[1..], &config.target_accounts);
+    }
+    // 离线回放跟单规则: signal-replay <规则文件> <录制文件或目录>
+    if args.first().map(String::as_str) == Some("signal-replay") {
+        return signal::replay::run_signal_replay(&args[1..], &config, &registry);
+    }
+    // 和Block Engine的searcher服务交互: bundle <子命令>
+    if args.first().map(String::as_str) == Some("bundle") {
+        return bundle::command::run_bundle_command(&args[1..], &config).await;
+    }
+    // 离线构造Pump/Pump AMM买卖交易: build <子命令>
+    if args.first().map(String::as_str) == Some("build") {
+        return builder::command::run_build_command(&args[1..], &config);
+    }
+
+    bundle::command::refresh_tip_accounts(&mut config).await;
+
+    let client = ShredstreamClient::new(config.server_url.clone());
+    let mut slot_clock = SlotClock::new(Duration::from_millis(config.slot_duration_ms));
+    let mut pipeline = Pipeline::spawn(&config, Quarantine::new(config.dead_letter_dir.clone()), registry);
+
+    info!(
+        upstream = %config.server_url,
+        workers = config.pipeline_workers,
+        capacity = config.pipeline_capacity,
+        policy = ?config.pipeline_policy,
+        "开始监听目标账户的交易..."
+    );
+    for account in &config.target_accounts {
+        info!(%account, "监控账户");
+    }
+
+    loop {
+        match client.connect().await {
+            Ok(mut jito_client) => {
+                match client.subscribe_entries(&mut jito_client).await {
+                    Ok(mut stream) => {
+                        loop {
+                            let message = stream.message().await;
+                            let received = ReceiveStamp::now();
+                            let slot_entry = match message {
+                                Ok(Some(slot_entry)) => slot_entry,
+                                Ok(None) => {
+                                    warn!(upstream = %config.server_url, "Entry流已结束，重新连接");
+                                    break;
+                                }
+                                Err(status) => {
+                                    warn!(upstream = %config.server_url, code = ?status.code(), message = status.message(), "Entry流出错，重新连接");
+                                    break;
+                                }
+                            };
+
+                            let entry_latency = {
+                                let _receive = info_span!("receive", slot = slot_entry.slot, upstream = %config.server_url).entered();
+                                let entry_latency = slot_clock.observe(slot_entry.slot, received);
+                                debug!(
+                                    latency_slot = entry_latency.slot,
+                                    bytes = slot_entry.entries.len(),
+                                    since_slot_start_ms = entry_latency.since_slot_start_ms,
+                                    since_first_seen_ms = entry_latency.since_slot_first_seen_ms(),
+                                    first_in_slot = entry_latency.is_first_in_slot,
+                                    "接收批次"
+                                );
+                                if entry_latency.is_first_in_slot && slot_entry.slot % LATENCY_REPORT_SLOTS == 0 {
+                                    let stats = slot_clock.take_stats();
+                                    info!(
+                                        slots = stats.slots,
+                                        avg_ms = stats.avg_ms(),
+                                        min_ms = stats.min_ms,
+                                        max_ms = stats.max_ms,
+                                        "slot首次接收延迟统计"
+                                    );
+                                }
+                                entry_latency
+                            };
+
+                            if !pipeline.submit(slot_entry.slot, slot_entry.entries, entry_latency).await {
+                                error!("处理管线已停止，退出");
+                                return Err("处理管线已关闭".into());
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        warn!(upstream = %config.server_url, error = %e, "订阅错误，5秒后重试...");
+                        sleep(Duration::from_secs(5)).await;
+                    }
+                }
+            }
+            Err(e) => {
+                warn!(upstream = %config.server_url, error = %e, "连接错误，5秒后重试...");
+                sleep(Duration::from_secs(5)).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use tracing_subscriber::{fmt, EnvFilter};
+
+use crate::config::{Config, LogFormat};
+
+/// 初始化全局tracing订阅者
+///
+/// 日志写入stderr，stdout保留给交易详情输出，避免两者混在一起。
+/// 每条事件都会带上所在span(connect/subscribe/slot/decode/match/render/sink)的字段，
+/// 可以据此判断某笔交易在哪个阶段被丢弃。
+pub fn init(config: &Config) {
+    let filter = EnvFilter::try_new(&config.log_filter)
+        .unwrap_or_else(|_| EnvFilter::new("info"));
+
+    let builder = fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_target(true);
+
+    // 重复初始化时忽略错误
+    let _ = match config.log_format {
+        LogFormat::Json => builder
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .try_init(),
+        LogFormat::Pretty => builder.pretty().try_init(),
+    };
+}
@@ -0,0 +1,543 @@
+use solana_sdk::{pubkey::Pubkey, transaction::VersionedTransaction};
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, error, info, info_span, trace, warn};
+
+use crate::accounts::snapshot::AccountSnapshot;
+use crate::config::{self, BackpressurePolicy, Config};
+use crate::detector::launch::detect_launches;
+use crate::detector::wallet::WalletTracker;
+use crate::decoder::{decode_entries, write_batch_file, Quarantine};
+use crate::latency::EntryLatency;
+use crate::scanner::filter_transactions;
+use crate::signal::rules::RuleSet;
+use crate::signal::{CopySignal, CopyTradeEngine, SignalOutput};
+use crate::state::StateEngine;
+use crate::state::bonding_curve::GlobalParams;
+use crate::state::pool::FeeConfig;
+use crate::transaction::registry::DecoderRegistry;
+use crate::transaction::{group_by_accounts, write_transaction_info, DecodedTransaction};
+use crate::transaction::pump_parser::{lamports_to_sol_string, PUMP_PROGRAM_ID};
+use crate::transaction::ata_parser::decode_ata_actions;
+use crate::transaction::jupiter_parser::decode_jupiter_routes;
+use crate::transaction::pumpamm_parser::parse_pumpamm_transaction;
+use crate::transaction::meteora_dlmm_parser::METEORA_DLMM_PROGRAM_ID;
+use crate::transaction::orca_whirlpool_parser::ORCA_WHIRLPOOL_PROGRAM_ID;
+use crate::transaction::raydium_amm_parser::RAYDIUM_AMM_PROGRAM_ID;
+use crate::transaction::raydium_cpmm_parser::RAYDIUM_CPMM_PROGRAM_ID;
+use crate::transaction::tip::find_tips;
+
+// 排序阶段最多缓存的乱序批次，超过后跳过缺失的序号，避免一个丢失的批次让后续输出永远等待
+const MAX_REORDER_PENDING: usize = 4096;
+
+// 接收阶段产生的原始批次
+#[derive(Debug)]
+pub struct RawBatch {
+    pub seq: u64,
+    pub slot: u64,
+    pub data: Vec<u8>,
+    pub latency: EntryLatency,
+}
+
+// 解码阶段产生的批次输出
+#[derive(Debug)]
+pub struct ProcessedBatch {
+    pub seq: u64,
+    pub slot: u64,
+    pub output: String,
+    // 需要按顺序应用到状态引擎的交易
+    pub transactions: Vec<DecodedTransaction>,
+    // 排序阶段产生的跟单信号，经单独的channel交给信号输出，不随输出一起丢弃
+    pub signals: Vec<CopySignal>,
+}
+
+impl ProcessedBatch {
+    // 处理失败时占住序号的空批次，排序阶段不必等待它
+    fn placeholder(seq: u64, slot: u64) -> Self {
+        Self {
+            seq,
+            slot,
+            output: String::new(),
+            transactions: Vec::new(),
+            signals: Vec::new(),
+        }
+    }
+}
+
+// 各阶段丢弃的计数
+#[derive(Debug, Default)]
+pub struct PipelineStats {
+    pub dropped_batches: AtomicU64,
+    pub dropped_outputs: AtomicU64,
+}
+
+// 各worker共享的只读上下文
+struct WorkerContext {
+    upstream: String,
+    target_accounts: Vec<Pubkey>,
+    // 需要反序列化的交易: 目标账户，状态跟踪关心的程序，Jito小费账户、关注钱包和跟单规则中的钱包
+    scan_accounts: Vec<Pubkey>,
+    tip_accounts: Vec<Pubkey>,
+    quarantine: Quarantine,
+    lazy_filter: bool,
+    record_dir: Option<PathBuf>,
+    detect_launches: bool,
+    // 交易详情中按程序ID分派指令解码
+    registry: DecoderRegistry,
+}
+
+/// 接收 → 反序列化 → 过滤 → 解码 → 输出 的分阶段处理管线
+///
+/// 反序列化、过滤和解码在worker池中并行执行，各阶段之间用有界channel连接。
+/// worker的结果按接收序号重新排序后再交给输出阶段，保证同一slot内(以及slot之间)的输出顺序与接收顺序一致。
+/// 输出阶段运行在阻塞线程上，stdout变慢不会拖住接收任务，只会按策略阻塞或丢弃。
+pub struct Pipeline {
+    input: mpsc::Sender<RawBatch>,
+    policy: BackpressurePolicy,
+    next_seq: u64,
+    stats: Arc<PipelineStats>,
+}
+
+impl Pipeline {
+    pub fn spawn(config: &Config, quarantine: Quarantine, registry: DecoderRegistry) -> Self {
+        let capacity = config.pipeline_capacity.max(1);
+        let policy = config.pipeline_policy;
+        let stats = Arc::new(PipelineStats::default());
+
+        let (input_tx, input_rx) = mpsc::channel::<RawBatch>(capacity);
+        let (decoded_tx, decoded_rx) = mpsc::channel::<ProcessedBatch>(capacity);
+        let (sink_tx, sink_rx) = mpsc::channel::<ProcessedBatch>(capacity);
+        // 跟单信号数量很少，用无界channel保证在Drop策略下也不会丢失
+        let (signal_tx, signal_rx) = mpsc::unbounded_channel::<(u64, Vec<CopySignal>)>();
+
+        let rules = match &config.copy_trade_rules {
+            Some(path) => match RuleSet::load(path) {
+                Ok(rules) => {
+                    info!(path = %path.display(), rules = rules.rules.len(), "已加载跟单规则");
+                    rules
+                }
+                Err(e) => {
+                    warn!(path = %path.display(), error = %e, "加载跟单规则失败，不匹配跟单信号");
+                    RuleSet::default()
+                }
+            },
+            None => RuleSet::default(),
+        };
+        let signal_output = match SignalOutput::open(config.signal_output.as_deref()) {
+            Ok(output) => output,
+            Err(e) => {
+                warn!(error = %e, "无法打开跟单信号输出文件，改为输出到stdout");
+                SignalOutput::Stdout
+            }
+        };
+
+        let mut scan_accounts = config.target_accounts.clone();
+        if config.track_pump_curves || config.detect_launches {
+            scan_accounts.push(PUMP_PROGRAM_ID.parse().expect("合法的Pump程序ID"));
+        }
+        if config.track_dex_swaps {
+            scan_accounts.push(RAYDIUM_AMM_PROGRAM_ID.parse().expect("合法的Raydium AMM程序ID"));
+            scan_accounts.push(RAYDIUM_CPMM_PROGRAM_ID.parse().expect("合法的Raydium CPMM程序ID"));
+            scan_accounts.push(METEORA_DLMM_PROGRAM_ID.parse().expect("合法的Meteora DLMM程序ID"));
+            scan_accounts.push(ORCA_WHIRLPOOL_PROGRAM_ID.parse().expect("合法的Orca Whirlpool程序ID"));
+        }
+        scan_accounts.extend(&config.jito_tip_accounts);
+        scan_accounts.extend(&config.watch_wallets);
+        scan_accounts.extend(rules.wallets());
+        // 自定义解码器的程序不在上面的列表里，不加进来的话惰性过滤会跳过它们的交易
+        scan_accounts.extend(registry.custom_program_ids());
+
+        let context = Arc::new(WorkerContext {
+            upstream: config.server_url.clone(),
+            target_accounts: config.target_accounts.clone(),
+            scan_accounts,
+            tip_accounts: config.jito_tip_accounts.clone(),
+            quarantine,
+            lazy_filter: config.lazy_filter,
+            record_dir: config.record_dir.clone(),
+            detect_launches: config.detect_launches,
+            registry,
+        });
+        let input_rx = Arc::new(Mutex::new(input_rx));
+
+        if let Some(dir) = &context.record_dir {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                warn!(dir = %dir.display(), error = %e, "无法创建录制目录");
+            }
+        }
+
+        for worker in 0..config.pipeline_workers.max(1) {
+            let input_rx = Arc::clone(&input_rx);
+            let decoded_tx = decoded_tx.clone();
+            let context = Arc::clone(&context);
+            tokio::spawn(async move {
+                loop {
+                    let batch = input_rx.lock().await.recv().await;
+                    let Some(batch) = batch else {
+                        break;
+                    };
+                    let (seq, slot) = (batch.seq, batch.slot);
+                    trace!(worker, seq, "worker接收批次");
+                    // 解码在阻塞线程上执行，panic只影响当前批次
+                    let batch_context = Arc::clone(&context);
+                    let processed = tokio::task::spawn_blocking(move || {
+                        catch_unwind(AssertUnwindSafe(|| process_batch(&batch_context, batch)))
+                    })
+                    .await;
+                    let processed = match processed {
+                        Ok(Ok(processed)) => processed,
+                        Ok(Err(_)) | Err(_) => {
+                            error!(worker, seq, slot, "处理批次时panic，已跳过该批次");
+                            ProcessedBatch::placeholder(seq, slot)
+                        }
+                    };
+                    if decoded_tx.send(processed).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(decoded_tx);
+
+        let mut state = StateEngine::new(GlobalParams::default(), FeeConfig::default());
+        state.wallets = WalletTracker::new(&config.watch_wallets, config.wallet_history, config.wallet_positions);
+        state.signals = CopyTradeEngine::new(rules);
+        if let Some(path) = &config.account_snapshot {
+            match AccountSnapshot::load(path) {
+                Ok(snapshot) => {
+                    let summary = state.load_snapshot(&snapshot);
+                    for account in &snapshot.accounts {
+                        let decoded = account
+                            .owner
+                            .as_ref()
+                            .and_then(|owner| context.registry.decode_account(owner, &account.data));
+                        if let Some(decoded) = decoded {
+                            debug!(pubkey = %account.pubkey, account = %decoded.name, params = %decoded.params, "快照账户");
+                        }
+                    }
+                    info!(
+                        path = %path.display(),
+                        accounts = snapshot.accounts.len(),
+                        skipped = snapshot.skipped,
+                        globals = summary.globals,
+                        global_configs = summary.global_configs,
+                        curves = summary.curves,
+                        pools = summary.pools,
+                        pools_with_reserves = summary.pools_with_reserves,
+                        mismatched_vaults = summary.mismatched_vaults,
+                        token_accounts = summary.token_accounts,
+                        unrecognized = summary.unrecognized,
+                        failed = summary.failed,
+                        "已加载账户快照"
+                    );
+                }
+                Err(e) => warn!(path = %path.display(), error = %e, "加载账户快照失败，使用默认参数"),
+            }
+        }
+        tokio::spawn(reorder(decoded_rx, sink_tx, signal_tx, state, policy, Arc::clone(&stats)));
+        tokio::task::spawn_blocking(move || sink(sink_rx));
+        tokio::task::spawn_blocking(move || signal_sink(signal_rx, signal_output));
+
+        Self {
+            input: input_tx,
+            policy,
+            next_seq: 0,
+            stats,
+        }
+    }
+
+    /// 提交一个接收到的批次，管线已关闭时返回false
+    pub async fn submit(&mut self, slot: u64, data: Vec<u8>, latency: EntryLatency) -> bool {
+        let batch = RawBatch {
+            seq: self.next_seq,
+            slot,
+            data,
+            latency,
+        };
+
+        let accepted = match self.policy {
+            BackpressurePolicy::Block => match self.input.send(batch).await {
+                Ok(()) => true,
+                Err(_) => {
+                    error!(slot, "处理管线已关闭");
+                    return false;
+                }
+            },
+            BackpressurePolicy::Drop => match self.input.try_send(batch) {
+                Ok(()) => true,
+                Err(mpsc::error::TrySendError::Full(batch)) => {
+                    let dropped = self.stats.dropped_batches.fetch_add(1, Ordering::Relaxed) + 1;
+                    warn!(slot = batch.slot, bytes = batch.data.len(), dropped, "解码worker处理不过来，已丢弃批次");
+                    false
+                }
+                Err(mpsc::error::TrySendError::Closed(_)) => {
+                    error!(slot, "处理管线已关闭");
+                    return false;
+                }
+            },
+        };
+
+        // 只有成功进入管线的批次才占用序号，排序阶段依赖序号连续
+        if accepted {
+            self.next_seq += 1;
+        }
+        true
+    }
+}
+
+// 反序列化、过滤并解码一个批次
+fn process_batch(context: &WorkerContext, batch: RawBatch) -> ProcessedBatch {
+    let slot_span = info_span!("slot", slot = batch.slot, seq = batch.seq, upstream = %context.upstream);
+    let _slot_guard = slot_span.enter();
+
+    let mut output = String::new();
+
+    if let Some(dir) = &context.record_dir {
+        if let Err(e) = write_batch_file(dir, batch.slot, &batch.data) {
+            warn!(dir = %dir.display(), error = %e, "录制批次失败");
+        }
+    }
+
+    let transactions = {
+        let _decode = info_span!("decode", bytes = batch.data.len(), lazy = context.lazy_filter).entered();
+        let scanned = if context.lazy_filter {
+            match filter_transactions(&batch.data, &context.scan_accounts) {
+                Ok(transactions) => Some(transactions),
+                Err(e) => {
+                    debug!(error = %e, "快速扫描失败，回退到完整解码");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        match scanned {
+            Some(transactions) => {
+                debug!(materialized = transactions.len(), "批次扫描完成");
+                transactions
+            }
+            None => {
+                let outcome = decode_entries(&batch.data);
+                let entries = context.quarantine.accept(batch.slot, &batch.data, outcome);
+                debug!(
+                    entries = entries.len(),
+                    transactions = entries.iter().map(|e| e.transactions.len()).sum::<usize>(),
+                    "批次解码完成"
+                );
+                entries.into_iter().flat_map(|entry| entry.transactions).collect()
+            }
+        }
+    };
+
+    let transactions_by_account = {
+        let _match = info_span!("match").entered();
+        let matched = group_by_accounts(&transactions, &context.target_accounts);
+        for (account, transactions) in &matched {
+            debug!(%account, matched = transactions.len(), "账户匹配");
+        }
+        if matched.is_empty() {
+            trace!("批次中没有目标账户的交易");
+        }
+        matched
+    };
+
+    // 写入String不会失败
+    let _ = render_matches(&mut output, context, &batch, &transactions_by_account);
+
+    let decoded: Vec<DecodedTransaction> = transactions
+        .iter()
+        .filter_map(|transaction| {
+            let keys = transaction.message.static_account_keys();
+            let matched = context.target_accounts.iter().any(|account| keys.contains(account));
+            if !matched && !context.scan_accounts.iter().any(|account| keys.contains(account)) {
+                return None;
+            }
+            let decoded = DecodedTransaction::decode(transaction, batch.slot, matched, &context.tip_accounts, &context.registry)
+                .with_latency(batch.latency);
+            (matched || decoded.has_actions()).then_some(decoded)
+        })
+        .collect();
+
+    if context.detect_launches {
+        for transaction in &decoded {
+            for launch in detect_launches(transaction, &batch.latency) {
+                launch.log();
+                let _ = launch.write(&mut output);
+            }
+        }
+    }
+
+    ProcessedBatch {
+        seq: batch.seq,
+        slot: batch.slot,
+        output,
+        transactions: decoded,
+        signals: Vec::new(),
+    }
+}
+
+// 生成匹配交易的输出文本
+fn render_matches(
+    out: &mut String,
+    context: &WorkerContext,
+    batch: &RawBatch,
+    transactions_by_account: &HashMap<Pubkey, Vec<&VersionedTransaction>>,
+) -> std::fmt::Result {
+    let latency = &batch.latency;
+
+    for (account, transactions) in transactions_by_account {
+        if transactions.is_empty() {
+            continue;
+        }
+        writeln!(out, "\n找到账户 {} 的 {} 笔新交易 当前Slot:[{}]", account, transactions.len(), batch.slot)?;
+
+        let is_pumpamm = account.to_string() == config::PUMPAMM_PROGRAM_ID;
+        if is_pumpamm {
+            writeln!(out, "===== Pump AMM协议交易 =====")?;
+        }
+
+        for (index, transaction) in transactions.iter().enumerate() {
+            let _render = info_span!("render", signature = %transaction.signatures[0], %account).entered();
+            debug!("生成交易输出");
+
+            writeln!(out, "\n交易 {}:", index + 1)?;
+            writeln!(out, "接收时间: {}", latency)?;
+            for tip in find_tips(transaction, &context.tip_accounts) {
+                writeln!(
+                    out,
+                    "Jito小费: {} ({} -> {})",
+                    lamports_to_sol_string(tip.lamports),
+                    tip.from,
+                    tip.tip_account
+                )?;
+            }
+            write_transaction_info(out, transaction, &context.registry)?;
+
+            if is_pumpamm {
+                let parsed_instructions = parse_pumpamm_transaction(transaction);
+                writeln!(out, "\nPump AMM指令总数: {}", parsed_instructions.len())?;
+
+                let has_create_pool = parsed_instructions.iter().any(|inst| inst.name == "CreatePool");
+                let has_deposit = parsed_instructions.iter().any(|inst| inst.name == "Deposit");
+                let has_buy = parsed_instructions.iter().any(|inst| inst.name == "Buy");
+                let has_sell = parsed_instructions.iter().any(|inst| inst.name == "Sell");
+
+                if has_create_pool {
+                    writeln!(out, "操作类型: 创建流动性池")?;
+                } else if has_deposit {
+                    writeln!(out, "操作类型: 存入流动性")?;
+                } else if has_buy {
+                    writeln!(out, "操作类型: 买入代币")?;
+                } else if has_sell {
+                    writeln!(out, "操作类型: 卖出代币")?;
+                } else {
+                    // 顶层为Jupiter时Pump AMM指令在内部指令中
+                    let routes = decode_jupiter_routes(transaction, &decode_ata_actions(transaction));
+                    if routes.iter().any(|route| route.pumpamm_hops().next().is_some()) {
+                        writeln!(out, "操作类型: 经Jupiter路由的兑换")?;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// 按序号重新排序worker的输出，按顺序更新状态引擎，再把输出和跟单信号分别交给输出阶段
+async fn reorder(
+    mut decoded_rx: mpsc::Receiver<ProcessedBatch>,
+    sink_tx: mpsc::Sender<ProcessedBatch>,
+    signal_tx: mpsc::UnboundedSender<(u64, Vec<CopySignal>)>,
+    mut state: StateEngine,
+    policy: BackpressurePolicy,
+    stats: Arc<PipelineStats>,
+) {
+    let mut pending = BTreeMap::new();
+    let mut next_seq = 0u64;
+
+    while let Some(batch) = decoded_rx.recv().await {
+        // 已经被跳过的序号迟到时直接丢弃
+        if batch.seq < next_seq {
+            warn!(slot = batch.slot, seq = batch.seq, "批次在被跳过后才到达，已丢弃");
+            continue;
+        }
+        pending.insert(batch.seq, batch);
+
+        if pending.len() > MAX_REORDER_PENDING && !pending.contains_key(&next_seq) {
+            if let Some(&first) = pending.keys().next() {
+                warn!(missing_from = next_seq, missing_to = first - 1, pending = pending.len(), "排序阶段等待的批次缺失，已跳过");
+                next_seq = first;
+            }
+        }
+
+        while let Some(mut batch) = pending.remove(&next_seq) {
+            next_seq += 1;
+
+            {
+                let _state = info_span!("state", slot = batch.slot, seq = batch.seq).entered();
+                let applied = catch_unwind(AssertUnwindSafe(|| {
+                    let _ = state.apply(&batch.transactions, &mut batch.output, &mut batch.signals);
+                }));
+                if applied.is_err() {
+                    error!(slot = batch.slot, seq = batch.seq, "状态引擎应用批次时panic，该批次的状态更新可能不完整");
+                }
+            }
+
+            // 信号不受背压策略影响，输出阶段丢弃批次时信号照常写出
+            if !batch.signals.is_empty() && signal_tx.send((batch.slot, std::mem::take(&mut batch.signals))).is_err() {
+                return;
+            }
+            // 没有匹配结果的批次不需要经过输出阶段
+            if batch.output.is_empty() {
+                continue;
+            }
+            batch.output.push_str("\n----------------------------------------------\n\n");
+
+            match policy {
+                BackpressurePolicy::Block => {
+                    if sink_tx.send(batch).await.is_err() {
+                        return;
+                    }
+                }
+                BackpressurePolicy::Drop => match sink_tx.try_send(batch) {
+                    Ok(()) => {}
+                    Err(mpsc::error::TrySendError::Full(batch)) => {
+                        let dropped = stats.dropped_outputs.fetch_add(1, Ordering::Relaxed) + 1;
+                        warn!(slot = batch.slot, seq = batch.seq, dropped, "输出阶段处理不过来，已丢弃输出");
+                    }
+                    Err(mpsc::error::TrySendError::Closed(_)) => return,
+                },
+            }
+        }
+    }
+}
+
+// 输出阶段，在阻塞线程上写stdout
+fn sink(mut sink_rx: mpsc::Receiver<ProcessedBatch>) {
+    let stdout = std::io::stdout();
+    while let Some(batch) = sink_rx.blocking_recv() {
+        let _sink = info_span!("sink", slot = batch.slot, seq = batch.seq).entered();
+        let mut handle = stdout.lock();
+        if let Err(e) = handle.write_all(batch.output.as_bytes()).and_then(|_| handle.flush()) {
+            warn!(slot = batch.slot, error = %e, "写入stdout失败");
+        }
+    }
+}
+
+// 跟单信号输出，在单独的阻塞线程上按排序阶段的顺序写出每一条信号
+fn signal_sink(mut signal_rx: mpsc::UnboundedReceiver<(u64, Vec<CopySignal>)>, mut signal_output: SignalOutput) {
+    while let Some((slot, signals)) = signal_rx.blocking_recv() {
+        let _sink = info_span!("signal_sink", slot).entered();
+        if let Err(e) = signal_output.write(&signals) {
+            warn!(slot, signals = signals.len(), error = %e, "写入跟单信号失败");
+        }
+    }
+}
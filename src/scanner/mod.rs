@@ -0,0 +1,326 @@
+use solana_sdk::{pubkey::Pubkey, transaction::VersionedTransaction};
+use std::fmt;
+
+// 版本化消息的前缀标志位
+const MESSAGE_VERSION_PREFIX: u8 = 0x80;
+const PUBKEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+const HASH_LEN: usize = 32;
+
+// 扫描失败原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanError {
+    // 数据在offset处提前结束
+    UnexpectedEof(usize),
+    // compact-u16长度编码非法
+    InvalidShortVec(usize),
+    // 不支持的消息版本
+    UnsupportedVersion(u8),
+    // 扫描通过但完整反序列化失败
+    Materialize(String),
+}
+
+impl fmt::Display for ScanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanError::UnexpectedEof(offset) => write!(f, "数据在偏移{}处提前结束", offset),
+            ScanError::InvalidShortVec(offset) => write!(f, "偏移{}处的compact-u16长度非法", offset),
+            ScanError::UnsupportedVersion(version) => write!(f, "不支持的消息版本: {}", version),
+            ScanError::Materialize(error) => write!(f, "交易反序列化失败: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for ScanError {}
+
+// 顺序读取bincode数据的游标
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ScanError> {
+        let end = self.pos.checked_add(len).ok_or(ScanError::UnexpectedEof(self.pos))?;
+        let bytes = self.data.get(self.pos..end).ok_or(ScanError::UnexpectedEof(self.pos))?;
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), ScanError> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, ScanError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn peek_u8(&self) -> Result<u8, ScanError> {
+        self.data.get(self.pos).copied().ok_or(ScanError::UnexpectedEof(self.pos))
+    }
+
+    fn u64(&mut self) -> Result<u64, ScanError> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().expect("8字节")))
+    }
+
+    // solana short_vec使用的compact-u16长度，最多3字节
+    fn short_len(&mut self) -> Result<usize, ScanError> {
+        let start = self.pos;
+        let mut value = 0usize;
+        for i in 0..3 {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7f) as usize) << (i * 7);
+            if byte & 0x80 == 0 {
+                if value > u16::MAX as usize {
+                    return Err(ScanError::InvalidShortVec(start));
+                }
+                return Ok(value);
+            }
+        }
+        Err(ScanError::InvalidShortVec(start))
+    }
+}
+
+/// 批次中单笔交易的零拷贝视图
+///
+/// 只记录交易在原始数据中的位置和静态账户列表，需要时再调用 `materialize` 完整反序列化。
+/// 地址查找表中的账户不在视图里，与完整解码后的 `static_account_keys` 一致。
+#[derive(Debug, Clone, Copy)]
+pub struct TransactionView<'a> {
+    bytes: &'a [u8],
+    account_keys: &'a [u8],
+}
+
+impl<'a> TransactionView<'a> {
+    /// 静态账户列表
+    pub fn account_keys(&self) -> impl Iterator<Item = &'a [u8]> {
+        self.account_keys.chunks_exact(PUBKEY_LEN)
+    }
+
+    pub fn contains_account(&self, account: &Pubkey) -> bool {
+        self.account_keys().any(|key| key == account.as_ref())
+    }
+
+    /// 完整反序列化这笔交易
+    pub fn materialize(&self) -> Result<VersionedTransaction, bincode::Error> {
+        bincode::deserialize(self.bytes)
+    }
+}
+
+// 跳过一条CompiledInstruction
+fn scan_instruction(reader: &mut Reader) -> Result<(), ScanError> {
+    reader.skip(1)?;
+    let accounts_len = reader.short_len()?;
+    reader.skip(accounts_len)?;
+    let data_len = reader.short_len()?;
+    reader.skip(data_len)
+}
+
+// 扫描一笔VersionedTransaction
+fn scan_transaction<'a>(reader: &mut Reader<'a>) -> Result<TransactionView<'a>, ScanError> {
+    let start = reader.pos;
+
+    let signature_count = reader.short_len()?;
+    reader.skip(signature_count * SIGNATURE_LEN)?;
+
+    let prefix = reader.peek_u8()?;
+    let versioned = prefix & MESSAGE_VERSION_PREFIX != 0;
+    if versioned {
+        let version = prefix & !MESSAGE_VERSION_PREFIX;
+        if version != 0 {
+            return Err(ScanError::UnsupportedVersion(version));
+        }
+        reader.skip(1)?;
+    }
+
+    // MessageHeader
+    reader.skip(3)?;
+
+    let key_count = reader.short_len()?;
+    let account_keys = reader.take(key_count * PUBKEY_LEN)?;
+
+    // recent_blockhash
+    reader.skip(HASH_LEN)?;
+
+    let instruction_count = reader.short_len()?;
+    for _ in 0..instruction_count {
+        scan_instruction(reader)?;
+    }
+
+    if versioned {
+        let lookup_count = reader.short_len()?;
+        for _ in 0..lookup_count {
+            reader.skip(PUBKEY_LEN)?;
+            let writable_len = reader.short_len()?;
+            reader.skip(writable_len)?;
+            let readonly_len = reader.short_len()?;
+            reader.skip(readonly_len)?;
+        }
+    }
+
+    Ok(TransactionView {
+        bytes: &reader.data[start..reader.pos],
+        account_keys,
+    })
+}
+
+/// 按bincode布局扫描 Vec<Entry>，返回每笔交易的视图，不分配交易本身
+pub fn scan_entries(data: &[u8]) -> Result<Vec<TransactionView<'_>>, ScanError> {
+    let mut reader = Reader::new(data);
+    let entry_count = reader.u64()?;
+    let mut views = Vec::new();
+
+    for _ in 0..entry_count {
+        // num_hashes + hash
+        reader.skip(8 + HASH_LEN)?;
+        let transaction_count = reader.u64()?;
+        for _ in 0..transaction_count {
+            views.push(scan_transaction(&mut reader)?);
+        }
+    }
+
+    Ok(views)
+}
+
+/// 只反序列化涉及目标账户的交易
+///
+/// 返回的交易保持批次内的原始顺序。扫描失败时返回错误，由调用方回退到完整解码。
+pub fn filter_transactions(data: &[u8], target_accounts: &[Pubkey]) -> Result<Vec<VersionedTransaction>, ScanError> {
+    let views = scan_entries(data)?;
+    let mut transactions = Vec::new();
+
+    for view in views {
+        if target_accounts.iter().any(|account| view.contains_account(account)) {
+            match view.materialize() {
+                Ok(transaction) => transactions.push(transaction),
+                Err(e) => return Err(ScanError::Materialize(e.to_string())),
+            }
+        }
+    }
+
+    Ok(transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_entry::entry::Entry;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::instruction::CompiledInstruction;
+    use solana_sdk::message::v0::{self, MessageAddressTableLookup};
+    use solana_sdk::message::{Message, MessageHeader, VersionedMessage};
+    use solana_sdk::signature::Signature;
+
+    fn legacy_transaction(keys: Vec<Pubkey>, data_len: usize) -> VersionedTransaction {
+        VersionedTransaction {
+            signatures: vec![Signature::new_unique()],
+            message: VersionedMessage::Legacy(Message {
+                header: MessageHeader {
+                    num_required_signatures: 1,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 1,
+                },
+                recent_blockhash: Hash::new_unique(),
+                instructions: vec![CompiledInstruction::new_from_raw_parts(
+                    (keys.len() - 1) as u8,
+                    vec![7; data_len],
+                    (0..keys.len() as u8 - 1).collect(),
+                )],
+                account_keys: keys,
+            }),
+        }
+    }
+
+    // 两个签名、带地址查找表的v0交易
+    fn v0_transaction(keys: Vec<Pubkey>) -> VersionedTransaction {
+        VersionedTransaction {
+            signatures: vec![Signature::new_unique(), Signature::new_unique()],
+            message: VersionedMessage::V0(v0::Message {
+                header: MessageHeader {
+                    num_required_signatures: 2,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 1,
+                },
+                recent_blockhash: Hash::new_unique(),
+                instructions: vec![
+                    CompiledInstruction::new_from_raw_parts(2, vec![1, 2, 3], vec![0, 1, 3]),
+                    CompiledInstruction::new_from_raw_parts(2, Vec::new(), vec![4]),
+                ],
+                address_table_lookups: vec![MessageAddressTableLookup {
+                    account_key: Pubkey::new_unique(),
+                    writable_indexes: vec![0, 5],
+                    readonly_indexes: vec![1],
+                }],
+                account_keys: keys,
+            }),
+        }
+    }
+
+    fn keys(count: usize) -> Vec<Pubkey> {
+        (0..count).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    // 第二个Entry中的legacy交易指令数据超过127字节，长度需要两字节的compact-u16
+    fn batch() -> (Vec<u8>, Vec<VersionedTransaction>) {
+        let transactions = vec![
+            legacy_transaction(keys(3), 16),
+            v0_transaction(keys(3)),
+            legacy_transaction(keys(4), 300),
+        ];
+        let entries = vec![
+            Entry { num_hashes: 1, hash: Hash::new_unique(), transactions: transactions[..2].to_vec() },
+            Entry { num_hashes: 0, hash: Hash::new_unique(), transactions: Vec::new() },
+            Entry { num_hashes: 2, hash: Hash::new_unique(), transactions: transactions[2..].to_vec() },
+        ];
+        (bincode::serialize(&entries).expect("序列化Entry"), transactions)
+    }
+
+    #[test]
+    fn scan_matches_full_deserialize() {
+        let (data, transactions) = batch();
+        let entries: Vec<Entry> = bincode::deserialize(&data).expect("完整反序列化");
+        let deserialized: Vec<VersionedTransaction> = entries.into_iter().flat_map(|entry| entry.transactions).collect();
+        assert_eq!(deserialized, transactions);
+
+        let views = scan_entries(&data).expect("扫描批次");
+        assert_eq!(views.len(), transactions.len());
+        for (view, transaction) in views.iter().zip(&transactions) {
+            let keys: Vec<&[u8]> = view.account_keys().collect();
+            let expected: Vec<&[u8]> = transaction.message.static_account_keys().iter().map(|key| key.as_ref()).collect();
+            assert_eq!(keys, expected);
+            assert_eq!(&view.materialize().expect("反序列化视图"), transaction);
+        }
+    }
+
+    #[test]
+    fn filter_keeps_batch_order() {
+        let (data, transactions) = batch();
+        let first = transactions[0].message.static_account_keys()[1];
+        let last = transactions[2].message.static_account_keys()[0];
+        let filtered = filter_transactions(&data, &[last, first]).expect("过滤批次");
+        assert_eq!(filtered, vec![transactions[0].clone(), transactions[2].clone()]);
+        assert!(filter_transactions(&data, &[Pubkey::new_unique()]).expect("过滤批次").is_empty());
+    }
+
+    #[test]
+    fn truncated_batch_is_an_error() {
+        let (data, _) = batch();
+        assert!(matches!(scan_entries(&data[..data.len() - 1]), Err(ScanError::UnexpectedEof(_))));
+    }
+
+    #[test]
+    fn unsupported_message_version() {
+        let (mut data, transactions) = batch();
+        // 第一个Entry头(8字节数量 + 8字节num_hashes + 32字节hash + 8字节交易数)之后是第一笔交易的签名
+        let v0_start = 8 + 8 + HASH_LEN + 8 + bincode::serialized_size(&transactions[0]).expect("交易大小") as usize;
+        let prefix = v0_start + 1 + 2 * SIGNATURE_LEN;
+        assert_eq!(data[prefix], MESSAGE_VERSION_PREFIX);
+        data[prefix] = MESSAGE_VERSION_PREFIX | 1;
+        assert_eq!(scan_entries(&data).err(), Some(ScanError::UnsupportedVersion(1)));
+    }
+}
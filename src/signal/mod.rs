@@ -0,0 +1,455 @@
+// 跟单信号: 按声明式规则把关注钱包的兑换转换成结构化信号
+// 规则匹配运行在管线的排序阶段，依赖曲线和池的状态估计计算市值；信号由输出阶段写到stdout或文件
+
+use serde_json::{json, Value};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use tracing::info;
+
+use crate::latency::EntryLatency;
+use crate::state::bonding_curve::{BondingCurveEngine, CurveSource};
+use crate::state::pool::PoolRegistry;
+use crate::transaction::swap::{SwapIntent, SwapSide, Venue, WSOL_MINT};
+use crate::transaction::DecodedTransaction;
+
+pub mod replay;
+pub mod rules;
+use rules::{Rule, RuleSet};
+
+// 每隔多少个slot清理一次过期的去重记录
+const PRUNE_INTERVAL_SLOTS: u64 = 1_000;
+
+/// 规则触发的跟单信号
+#[derive(Debug, Clone)]
+pub struct CopySignal {
+    pub rule: String,
+    pub signature: Signature,
+    pub slot: u64,
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    pub venue: Venue,
+    pub pool: Option<Pubkey>,
+    pub side: SwapSide,
+    // 观察到的SOL数量(精确值或限价)
+    pub sol_amount: u64,
+    pub exact_in: bool,
+    // 按规则缩放后的信号数量
+    pub size: u64,
+    // 应用这笔交易之后估计的市值，场所不是Pump曲线或Pump AMM池时为None
+    pub market_cap_sol: Option<f64>,
+    // 触发交易所在Entry批次的接收延迟，回放时为None
+    pub latency: Option<EntryLatency>,
+}
+
+impl CopySignal {
+    /// 输出用的结构化记录，数量同时给出lamports和SOL
+    pub fn to_json(&self) -> Value {
+        json!({
+            "event": "copy_trade_signal",
+            "rule": self.rule,
+            "slot": self.slot,
+            "signature": self.signature.to_string(),
+            "wallet": self.wallet.to_string(),
+            "mint": self.mint.to_string(),
+            "venue": self.venue.to_string(),
+            "pool": self.pool.map(|pool| pool.to_string()),
+            "side": match self.side {
+                SwapSide::Buy => "buy",
+                SwapSide::Sell => "sell",
+            },
+            "sol_amount": self.sol_amount,
+            "sol_amount_is_limit": self.exact_in == (self.side == SwapSide::Sell),
+            "size_lamports": self.size,
+            "size_sol": self.size as f64 / 1_000_000_000.0,
+            "market_cap_sol": self.market_cap_sol,
+            "received_at": self.latency.map(|latency| latency.received.wall_clock.to_rfc3339()),
+            "since_slot_first_seen_ms": self.latency.and_then(|latency| latency.since_slot_first_seen_ms()),
+            "since_slot_start_ms": self.latency.map(|latency| latency.since_slot_start_ms),
+        })
+    }
+
+    pub fn log(&self) {
+        info!(
+            event = "copy_trade_signal",
+            rule = %self.rule,
+            slot = self.slot,
+            signature = %self.signature,
+            wallet = %self.wallet,
+            mint = %self.mint,
+            venue = %self.venue,
+            side = %self.side,
+            sol_amount = self.sol_amount,
+            size = self.size,
+            market_cap_sol = self.market_cap_sol,
+            since_slot_first_seen_ms = self.latency.and_then(|latency| latency.since_slot_first_seen_ms()),
+            since_slot_start_ms = self.latency.map(|latency| latency.since_slot_start_ms),
+            "跟单信号"
+        );
+    }
+}
+
+/// 信号的输出位置
+#[derive(Debug)]
+pub enum SignalOutput {
+    Stdout,
+    // 追加写入的JSON Lines文件
+    File(File),
+}
+
+impl SignalOutput {
+    /// 未指定路径时输出到stdout
+    pub fn open(path: Option<&Path>) -> io::Result<Self> {
+        match path {
+            Some(path) => Ok(SignalOutput::File(OpenOptions::new().create(true).append(true).open(path)?)),
+            None => Ok(SignalOutput::Stdout),
+        }
+    }
+
+    /// 每个信号写一行JSON
+    pub fn write(&mut self, signals: &[CopySignal]) -> io::Result<()> {
+        if signals.is_empty() {
+            return Ok(());
+        }
+        let mut lines = String::new();
+        for signal in signals {
+            lines.push_str(&signal.to_json().to_string());
+            lines.push('\n');
+        }
+        match self {
+            SignalOutput::Stdout => {
+                let mut handle = io::stdout().lock();
+                handle.write_all(lines.as_bytes())?;
+                handle.flush()
+            }
+            SignalOutput::File(file) => {
+                file.write_all(lines.as_bytes())?;
+                file.flush()
+            }
+        }
+    }
+}
+
+/// 按规则集匹配兑换意图并处理冷却和去重
+#[derive(Debug, Default)]
+pub struct CopyTradeEngine {
+    rules: Vec<Rule>,
+    // 每条规则最近一次触发的slot
+    last_fired: Vec<Option<u64>>,
+    // (规则序号, 代币) -> 最近一次触发的slot
+    fired_mints: HashMap<(usize, Pubkey), u64>,
+    last_prune_slot: u64,
+}
+
+impl CopyTradeEngine {
+    pub fn new(rules: RuleSet) -> Self {
+        Self {
+            last_fired: vec![None; rules.rules.len()],
+            rules: rules.rules,
+            fired_mints: HashMap::new(),
+            last_prune_slot: 0,
+        }
+    }
+
+    /// 匹配这笔交易中的兑换，需要在曲线和池状态应用这笔交易之后调用
+    pub fn on_transaction(
+        &mut self,
+        transaction: &DecodedTransaction,
+        intents: &[SwapIntent],
+        curves: &BondingCurveEngine,
+        pools: &PoolRegistry,
+    ) -> Vec<CopySignal> {
+        let mut signals = Vec::new();
+        if self.rules.is_empty() {
+            return signals;
+        }
+        self.maybe_prune(transaction.slot);
+
+        for intent in intents {
+            for (index, rule) in self.rules.iter().enumerate() {
+                if !rule.matches(intent) {
+                    continue;
+                }
+                let (Some(mint), Some(sol_amount)) = (intent.token_mint(), intent.sol_amount()) else {
+                    continue;
+                };
+                let market_cap_sol = market_cap_sol(intent, mint, curves, pools);
+                if !rule.accepts_market_cap(market_cap_sol) {
+                    continue;
+                }
+                let cooling = self.last_fired[index]
+                    .is_some_and(|fired| transaction.slot.saturating_sub(fired) < rule.cooldown_slots);
+                let duplicate = self
+                    .fired_mints
+                    .get(&(index, mint))
+                    .is_some_and(|fired| transaction.slot.saturating_sub(*fired) < rule.dedup_slots);
+                if cooling || duplicate {
+                    continue;
+                }
+
+                self.last_fired[index] = Some(transaction.slot);
+                self.fired_mints.insert((index, mint), transaction.slot);
+                signals.push(CopySignal {
+                    rule: rule.name.clone(),
+                    signature: transaction.signature,
+                    slot: transaction.slot,
+                    wallet: intent.trader,
+                    mint,
+                    venue: intent.venue,
+                    pool: intent.pool,
+                    side: rule.side,
+                    sol_amount,
+                    exact_in: intent.exact_in,
+                    size: rule.scaled_size(sol_amount),
+                    market_cap_sol,
+                    latency: intent.latency,
+                });
+            }
+        }
+
+        signals
+    }
+
+    // 清理超出去重窗口的记录
+    fn maybe_prune(&mut self, slot: u64) {
+        if slot < self.last_prune_slot + PRUNE_INTERVAL_SLOTS {
+            return;
+        }
+        self.last_prune_slot = slot;
+        let rules = &self.rules;
+        self.fired_mints
+            .retain(|(index, _), fired| slot.saturating_sub(*fired) < rules[*index].dedup_slots);
+    }
+}
+
+// Pump AMM按池的现货价格和总供应量估计，其他场所(包括经Jupiter路由)按未完成的Pump曲线估计；
+// 按初始参数假设的曲线储备不可信，不给出市值，设置了市值条件的规则不会因此误触发
+fn market_cap_sol(intent: &SwapIntent, mint: Pubkey, curves: &BondingCurveEngine, pools: &PoolRegistry) -> Option<f64> {
+    if intent.venue == Venue::PumpAmm {
+        let pool = pools.pool(&intent.pool?)?;
+        let wsol: Pubkey = WSOL_MINT.parse().expect("合法的WSOL Mint");
+        if pool.base_mint != Some(mint) || pool.quote_mint != Some(wsol) || !pool.has_reserves() {
+            return None;
+        }
+        let supply = curves
+            .curve(&mint)
+            .map(|state| state.token_total_supply)
+            .unwrap_or(curves.params().token_total_supply);
+        return Some(pool.market_cap(supply));
+    }
+    curves
+        .curve(&mint)
+        .filter(|state| !state.complete && matches!(state.source, CurveSource::Create | CurveSource::Snapshot))
+        .map(|state| state.market_cap_sol())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_sdk::transaction::VersionedTransaction;
+    use crate::latency::{ReceiveStamp, SlotClock};
+    use crate::transaction::pda::bonding_curve;
+    use crate::transaction::pump_parser::{PumpCreate, PumpTrade};
+    use crate::transaction::registry::DecoderRegistry;
+
+    fn engine(rules: Value) -> CopyTradeEngine {
+        CopyTradeEngine::new(RuleSet::from_json(&rules).expect("合法的规则"))
+    }
+
+    fn transaction(slot: u64) -> DecodedTransaction {
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::new_unique()],
+            ..VersionedTransaction::default()
+        };
+        DecodedTransaction::decode(&transaction, slot, true, &[], &DecoderRegistry::default())
+    }
+
+    fn wsol() -> Pubkey {
+        WSOL_MINT.parse().expect("合法的WSOL Mint")
+    }
+
+    // 在Pump曲线上用精确的SOL输入买入
+    fn buy(trader: Pubkey, mint: Pubkey, lamports: u64) -> SwapIntent {
+        SwapIntent {
+            venue: Venue::Pump,
+            pool: None,
+            trader,
+            side: Some(SwapSide::Buy),
+            mint_in: Some(wsol()),
+            mint_out: Some(mint),
+            exact_in: true,
+            amount: lamports,
+            limit_amount: 0,
+            latency: None,
+        }
+    }
+
+    fn run(engine: &mut CopyTradeEngine, slot: u64, intents: &[SwapIntent]) -> Vec<CopySignal> {
+        engine.on_transaction(&transaction(slot), intents, &BondingCurveEngine::default(), &PoolRegistry::default())
+    }
+
+    #[test]
+    fn signal_fields() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut engine = engine(json!([{"name": "whale", "wallets": [wallet.to_string()], "size_scale": 0.1}]));
+        let signals = run(&mut engine, 10, &[buy(wallet, mint, 2_000_000_000)]);
+        let [signal] = signals.as_slice() else {
+            panic!("期望一个信号，得到 {:?}", signals);
+        };
+        assert_eq!(signal.rule, "whale");
+        assert_eq!(signal.slot, 10);
+        assert_eq!(signal.wallet, wallet);
+        assert_eq!(signal.mint, mint);
+        assert_eq!(signal.side, SwapSide::Buy);
+        assert_eq!(signal.sol_amount, 2_000_000_000);
+        assert_eq!(signal.size, 200_000_000);
+        assert_eq!(signal.market_cap_sol, None);
+    }
+
+    #[test]
+    fn signal_carries_receive_latency() {
+        let wallet = Pubkey::new_unique();
+        let mut engine = engine(json!([{"wallets": [wallet.to_string()]}]));
+        let mut clock = SlotClock::new(std::time::Duration::from_millis(400));
+        let first = ReceiveStamp::now();
+        clock.observe(10, first);
+        let latency = clock.observe(
+            10,
+            ReceiveStamp {
+                monotonic: first.monotonic + std::time::Duration::from_millis(25),
+                wall_clock: first.wall_clock + chrono::Duration::milliseconds(25),
+            },
+        );
+        let intent = SwapIntent { latency: Some(latency), ..buy(wallet, Pubkey::new_unique(), 1_000_000_000) };
+        let signals = run(&mut engine, 10, &[intent]);
+        assert_eq!(signals[0].latency, Some(latency));
+        let record = signals[0].to_json();
+        assert_eq!(record["since_slot_first_seen_ms"], json!(25));
+        assert_eq!(record["since_slot_start_ms"], json!(25));
+        assert_eq!(record["received_at"], json!(latency.received.wall_clock.to_rfc3339()));
+    }
+
+    #[test]
+    fn filters_wallet_venue_side_and_amount() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut engine = engine(json!([{"wallets": [wallet.to_string()], "min_sol": 1, "max_sol": 5}]));
+
+        let other_wallet = buy(Pubkey::new_unique(), mint, 2_000_000_000);
+        let other_venue = SwapIntent { venue: Venue::RaydiumAmm, ..buy(wallet, mint, 2_000_000_000) };
+        let sell = SwapIntent {
+            side: Some(SwapSide::Sell),
+            mint_in: Some(mint),
+            mint_out: Some(wsol()),
+            ..buy(wallet, mint, 2_000_000_000)
+        };
+        let too_small = buy(wallet, mint, 999_999_999);
+        let too_large = buy(wallet, mint, 5_000_000_001);
+        assert!(run(&mut engine, 1, &[other_wallet, other_venue, sell, too_small, too_large]).is_empty());
+        assert_eq!(run(&mut engine, 1, &[buy(wallet, mint, 5_000_000_000)]).len(), 1);
+    }
+
+    #[test]
+    fn unknown_market_cap_blocks_capped_rule() {
+        let mut engine = engine(json!([{"max_market_cap_sol": 100}]));
+        // 状态中没有这条曲线，市值未知
+        assert!(run(&mut engine, 1, &[buy(Pubkey::new_unique(), Pubkey::new_unique(), 1_000_000_000)]).is_empty());
+    }
+
+    #[test]
+    fn assumed_curve_has_no_market_cap() {
+        let trader = Pubkey::new_unique();
+        let mut engine = engine(json!([{"max_market_cap_sol": 100}]));
+        let mut curves = BondingCurveEngine::default();
+        let pools = PoolRegistry::default();
+
+        // 启动前已存在的曲线，只看到了买入，按初始参数假设的储备给出的市值不可信
+        let assumed = Pubkey::new_unique();
+        let trade = PumpTrade {
+            mint: assumed,
+            bonding_curve: bonding_curve(&assumed),
+            user: trader,
+            creator_vault: None,
+            is_buy: true,
+            token_amount: 1_000_000,
+            sol_limit: u64::MAX,
+        };
+        assert_eq!(curves.on_trade(&trade, 1).0.source, CurveSource::Assumed);
+        assert!(engine.on_transaction(&transaction(1), &[buy(trader, assumed, 1)], &curves, &pools).is_empty());
+
+        // 看到create的曲线从初始参数精确推算
+        let created = Pubkey::new_unique();
+        let create = PumpCreate {
+            mint: created,
+            bonding_curve: bonding_curve(&created),
+            associated_bonding_curve: Pubkey::new_unique(),
+            user: trader,
+            creator: trader,
+            token_program: Pubkey::new_unique(),
+            name: String::new(),
+            symbol: String::new(),
+            uri: String::new(),
+        };
+        curves.on_create(&create, 2);
+        let signals = engine.on_transaction(&transaction(2), &[buy(trader, created, 1)], &curves, &pools);
+        let [signal] = signals.as_slice() else {
+            panic!("期望一个信号，得到 {:?}", signals);
+        };
+        assert!(signal.market_cap_sol.is_some_and(|market_cap| market_cap < 100.0));
+    }
+
+    #[test]
+    fn dedup_window_per_mint() {
+        let trader = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut engine = engine(json!([{"dedup_slots": 100}]));
+
+        // 同一笔交易中同一代币的两次兑换只触发一次
+        assert_eq!(run(&mut engine, 1_000, &[buy(trader, mint, 1), buy(trader, mint, 2)]).len(), 1);
+        assert!(run(&mut engine, 1_099, &[buy(trader, mint, 1)]).is_empty());
+        // 窗口内其他代币不受影响
+        assert_eq!(run(&mut engine, 1_099, &[buy(trader, Pubkey::new_unique(), 1)]).len(), 1);
+        assert_eq!(run(&mut engine, 1_100, &[buy(trader, mint, 1)]).len(), 1);
+    }
+
+    #[test]
+    fn dedup_is_per_rule() {
+        let trader = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut engine = engine(json!([{"name": "a"}, {"name": "b"}]));
+        let signals = run(&mut engine, 1, &[buy(trader, mint, 1)]);
+        let rules: Vec<&str> = signals.iter().map(|signal| signal.rule.as_str()).collect();
+        assert_eq!(rules, ["a", "b"]);
+        assert!(run(&mut engine, 2, &[buy(trader, mint, 1)]).is_empty());
+    }
+
+    #[test]
+    fn cooldown_across_mints() {
+        let trader = Pubkey::new_unique();
+        let mut engine = engine(json!([{"cooldown_slots": 10}]));
+
+        assert_eq!(run(&mut engine, 100, &[buy(trader, Pubkey::new_unique(), 1)]).len(), 1);
+        assert!(run(&mut engine, 109, &[buy(trader, Pubkey::new_unique(), 1)]).is_empty());
+        // 冷却期间被跳过的兑换不会延长冷却
+        assert_eq!(run(&mut engine, 110, &[buy(trader, Pubkey::new_unique(), 1)]).len(), 1);
+    }
+
+    #[test]
+    fn prune_keeps_active_dedup_entries() {
+        let trader = Pubkey::new_unique();
+        let old = Pubkey::new_unique();
+        let recent = Pubkey::new_unique();
+        let mut engine = engine(json!([{"dedup_slots": 2_000}]));
+
+        run(&mut engine, 1, &[buy(trader, old, 1)]);
+        run(&mut engine, 1_500, &[buy(trader, recent, 1)]);
+        run(&mut engine, 2_500, &[]);
+        assert_eq!(engine.fired_mints.len(), 1);
+        assert!(run(&mut engine, 2_500, &[buy(trader, recent, 1)]).is_empty());
+        assert_eq!(run(&mut engine, 2_500, &[buy(trader, old, 1)]).len(), 1);
+    }
+}
@@ -0,0 +1,330 @@
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::transaction::swap::{SwapIntent, SwapSide, Venue};
+
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+// 同一规则对同一代币默认多少个slot内只发一次信号(约1天)
+const DEFAULT_DEDUP_SLOTS: u64 = 216_000;
+
+// 规则文件加载失败原因
+#[derive(Debug)]
+pub enum RuleError {
+    Io(PathBuf, std::io::Error),
+    Json(PathBuf, String),
+    // 第几条规则(从0开始)的哪个字段非法
+    Invalid(usize, String),
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleError::Io(path, error) => write!(f, "读取{}失败: {}", path.display(), error),
+            RuleError::Json(path, error) => write!(f, "{}不是合法的JSON: {}", path.display(), error),
+            RuleError::Invalid(index, error) => write!(f, "第{}条规则非法: {}", index, error),
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+/// 单条跟单规则: 指定钱包在指定场所的买入或卖出满足数量和市值条件时发出信号
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub name: String,
+    // 为空时匹配所有交易者
+    pub wallets: Vec<Pubkey>,
+    // 为空时匹配所有场所
+    pub venues: Vec<Venue>,
+    pub side: SwapSide,
+    // SOL一侧数量的范围(lamports)
+    pub min_sol: u64,
+    pub max_sol: Option<u64>,
+    // 设置后必须能估计出市值且不超过该值(SOL)
+    pub max_market_cap_sol: Option<f64>,
+    // 规则发出信号后多少个slot内不再发出
+    pub cooldown_slots: u64,
+    // 同一代币多少个slot内只发一次
+    pub dedup_slots: u64,
+    // 信号数量 = 观察到的SOL数量 × size_scale，再限制在[min_size, max_size]内(lamports)
+    pub size_scale: f64,
+    pub min_size: u64,
+    pub max_size: Option<u64>,
+}
+
+impl Rule {
+    /// 交易意图是否满足规则的钱包、场所、方向和数量条件，市值条件由调用方检查
+    pub fn matches(&self, intent: &SwapIntent) -> bool {
+        if !self.wallets.is_empty() && !self.wallets.contains(&intent.trader) {
+            return false;
+        }
+        if !self.venues.is_empty() && !self.venues.contains(&intent.venue) {
+            return false;
+        }
+        if intent.side != Some(self.side) || intent.token_mint().is_none() {
+            return false;
+        }
+        let Some(sol_amount) = intent.sol_amount() else {
+            return false;
+        };
+        sol_amount >= self.min_sol && self.max_sol.is_none_or(|max_sol| sol_amount <= max_sol)
+    }
+
+    /// 市值是否满足条件，未设置上限时总是满足
+    pub fn accepts_market_cap(&self, market_cap_sol: Option<f64>) -> bool {
+        match (self.max_market_cap_sol, market_cap_sol) {
+            (None, _) => true,
+            (Some(max), Some(market_cap_sol)) => market_cap_sol <= max,
+            (Some(_), None) => false,
+        }
+    }
+
+    /// 按观察到的SOL数量计算信号数量(lamports)
+    pub fn scaled_size(&self, sol_amount: u64) -> u64 {
+        let scaled = (sol_amount as f64 * self.size_scale).round() as u64;
+        let scaled = scaled.max(self.min_size);
+        self.max_size.map_or(scaled, |max_size| scaled.min(max_size))
+    }
+}
+
+/// 从规则文件加载的规则集
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    pub rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    /// 加载JSON规则文件
+    ///
+    /// 文件为 `{"rules": [...]}` 或规则数组，每条规则的字段:
+    /// - `name`: 规则名，默认为 `rule-<序号>`
+    /// - `wallets`: 钱包地址数组，省略或为空时匹配所有交易者
+    /// - `venues`: 场所数组，可选 `pump`、`pump_amm`、`raydium_amm`、`raydium_cpmm`、`meteora_dlmm`、
+    ///   `orca_whirlpool`、`jupiter`，省略时只匹配 `pump`，`["any"]` 匹配所有场所
+    /// - `side`: `buy`(默认)或 `sell`
+    /// - `min_sol`/`max_sol`: SOL一侧数量的范围
+    /// - `max_market_cap_sol`: 市值上限，设置后市值未知的兑换不会触发
+    /// - `cooldown_slots`: 规则触发后的冷却slot数，默认为0
+    /// - `dedup_slots`: 同一代币的去重窗口，默认约1天
+    /// - `size_scale`: 信号数量相对观察数量的比例，默认为1
+    /// - `min_size_sol`/`max_size_sol`: 信号数量的范围
+    pub fn load(path: &Path) -> Result<Self, RuleError> {
+        let text = fs::read_to_string(path).map_err(|e| RuleError::Io(path.to_path_buf(), e))?;
+        let value: Value = serde_json::from_str(&text).map_err(|e| RuleError::Json(path.to_path_buf(), e.to_string()))?;
+        Self::from_json(&value)
+    }
+
+    /// 从已解析的JSON构造规则集
+    pub fn from_json(value: &Value) -> Result<Self, RuleError> {
+        let items = match value {
+            Value::Array(items) => items,
+            Value::Object(object) => match object.get("rules") {
+                Some(Value::Array(items)) => items,
+                _ => return Err(RuleError::Invalid(0, "缺少rules数组".to_string())),
+            },
+            _ => return Err(RuleError::Invalid(0, "规则文件必须是对象或数组".to_string())),
+        };
+
+        let rules = items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| parse_rule(index, item).map_err(|error| RuleError::Invalid(index, error)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { rules })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// 规则中指定的所有钱包，管线需要解码它们的交易
+    pub fn wallets(&self) -> Vec<Pubkey> {
+        let mut wallets: Vec<Pubkey> = self.rules.iter().flat_map(|rule| rule.wallets.iter().copied()).collect();
+        wallets.sort();
+        wallets.dedup();
+        wallets
+    }
+}
+
+fn parse_rule(index: usize, item: &Value) -> Result<Rule, String> {
+    let object = item.as_object().ok_or("规则必须是对象")?;
+
+    let wallets = match object.get("wallets") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| {
+                let text = item.as_str().ok_or("wallets必须是字符串数组")?;
+                Pubkey::from_str(text.trim()).map_err(|_| format!("非法的钱包地址: {}", text))
+            })
+            .collect::<Result<Vec<_>, String>>()?,
+        Some(_) => return Err("wallets必须是数组".to_string()),
+    };
+
+    let venues = match object.get("venues") {
+        None | Some(Value::Null) => vec![Venue::Pump],
+        Some(Value::Array(items)) => {
+            let names = items
+                .iter()
+                .map(|item| item.as_str().ok_or("venues必须是字符串数组"))
+                .collect::<Result<Vec<_>, _>>()?;
+            if names.iter().any(|name| name.eq_ignore_ascii_case("any")) {
+                Vec::new()
+            } else {
+                names
+                    .into_iter()
+                    .map(|name| parse_venue(name).ok_or_else(|| format!("未知的场所: {}", name)))
+                    .collect::<Result<Vec<_>, _>>()?
+            }
+        }
+        Some(_) => return Err("venues必须是数组".to_string()),
+    };
+
+    let side = match object.get("side").and_then(Value::as_str) {
+        None => SwapSide::Buy,
+        Some(side) if side.eq_ignore_ascii_case("buy") => SwapSide::Buy,
+        Some(side) if side.eq_ignore_ascii_case("sell") => SwapSide::Sell,
+        Some(side) => return Err(format!("未知的方向: {}", side)),
+    };
+
+    let size_scale = number(object.get("size_scale"), "size_scale")?.unwrap_or(1.0);
+    if size_scale <= 0.0 {
+        return Err("size_scale必须大于0".to_string());
+    }
+
+    Ok(Rule {
+        name: object
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("rule-{}", index)),
+        wallets,
+        venues,
+        side,
+        min_sol: number(object.get("min_sol"), "min_sol")?.map(sol_to_lamports).unwrap_or(0),
+        max_sol: number(object.get("max_sol"), "max_sol")?.map(sol_to_lamports),
+        max_market_cap_sol: number(object.get("max_market_cap_sol"), "max_market_cap_sol")?,
+        cooldown_slots: number(object.get("cooldown_slots"), "cooldown_slots")?.map(|v| v as u64).unwrap_or(0),
+        dedup_slots: number(object.get("dedup_slots"), "dedup_slots")?
+            .map(|v| v as u64)
+            .unwrap_or(DEFAULT_DEDUP_SLOTS),
+        size_scale,
+        min_size: number(object.get("min_size_sol"), "min_size_sol")?.map(sol_to_lamports).unwrap_or(0),
+        max_size: number(object.get("max_size_sol"), "max_size_sol")?.map(sol_to_lamports),
+    })
+}
+
+// 读取非负数字段，缺省或为null时返回None
+fn number(value: Option<&Value>, field: &str) -> Result<Option<f64>, String> {
+    match value {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => match value.as_f64() {
+            Some(number) if number >= 0.0 => Ok(Some(number)),
+            _ => Err(format!("{}必须是非负数", field)),
+        },
+    }
+}
+
+fn sol_to_lamports(sol: f64) -> u64 {
+    (sol * LAMPORTS_PER_SOL).round() as u64
+}
+
+fn parse_venue(name: &str) -> Option<Venue> {
+    let venue = match name.to_ascii_lowercase().as_str() {
+        "pump" => Venue::Pump,
+        "pump_amm" => Venue::PumpAmm,
+        "raydium_amm" => Venue::RaydiumAmm,
+        "raydium_cpmm" => Venue::RaydiumCpmm,
+        "meteora_dlmm" => Venue::MeteoraDlmm,
+        "orca_whirlpool" => Venue::OrcaWhirlpool,
+        "jupiter" => Venue::Jupiter,
+        // 自定义解码器的兑换按程序ID匹配
+        _ => return Pubkey::from_str(name.trim()).ok().map(Venue::Program),
+    };
+    Some(venue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rule(value: Value) -> Rule {
+        RuleSet::from_json(&json!([value])).expect("合法的规则").rules.remove(0)
+    }
+
+    fn invalid(value: Value) -> (usize, String) {
+        match RuleSet::from_json(&value) {
+            Err(RuleError::Invalid(index, error)) => (index, error),
+            other => panic!("期望Invalid错误，得到 {:?}", other),
+        }
+    }
+
+    #[test]
+    fn defaults() {
+        let rule = rule(json!({}));
+        assert_eq!(rule.name, "rule-0");
+        assert!(rule.wallets.is_empty());
+        assert_eq!(rule.venues, vec![Venue::Pump]);
+        assert_eq!(rule.side, SwapSide::Buy);
+        assert_eq!(rule.cooldown_slots, 0);
+        assert_eq!(rule.dedup_slots, DEFAULT_DEDUP_SLOTS);
+        assert_eq!(rule.size_scale, 1.0);
+    }
+
+    #[test]
+    fn rules_object_and_any_venue() {
+        let rules = RuleSet::from_json(&json!({"rules": [{"name": "a", "venues": ["any"], "side": "SELL", "min_sol": 0.5}]}))
+            .expect("合法的规则");
+        let rule = &rules.rules[0];
+        assert_eq!(rule.name, "a");
+        assert!(rule.venues.is_empty());
+        assert_eq!(rule.side, SwapSide::Sell);
+        assert_eq!(rule.min_sol, 500_000_000);
+    }
+
+    #[test]
+    fn from_json_errors() {
+        assert_eq!(invalid(json!({})), (0, "缺少rules数组".to_string()));
+        assert_eq!(invalid(json!("rules")), (0, "规则文件必须是对象或数组".to_string()));
+        assert_eq!(invalid(json!([{}, 1])), (1, "规则必须是对象".to_string()));
+        assert_eq!(invalid(json!([{"wallets": "x"}])).1, "wallets必须是数组");
+        assert_eq!(invalid(json!([{"wallets": [1]}])).1, "wallets必须是字符串数组");
+        assert_eq!(invalid(json!([{"wallets": ["not-a-key"]}])).1, "非法的钱包地址: not-a-key");
+        assert_eq!(invalid(json!([{"venues": ["uniswap"]}])).1, "未知的场所: uniswap");
+        assert_eq!(invalid(json!([{"side": "hold"}])).1, "未知的方向: hold");
+        assert_eq!(invalid(json!([{"min_sol": -1}])).1, "min_sol必须是非负数");
+        assert_eq!(invalid(json!([{"max_sol": "1"}])).1, "max_sol必须是非负数");
+        assert_eq!(invalid(json!([{"size_scale": 0}])).1, "size_scale必须大于0");
+    }
+
+    #[test]
+    fn scaled_size_clamps() {
+        let rule = rule(json!({"size_scale": 0.5, "min_size_sol": 0.1, "max_size_sol": 1}));
+        assert_eq!(rule.scaled_size(1_000_000_000), 500_000_000);
+        // 低于下限和高于上限时取边界
+        assert_eq!(rule.scaled_size(1_000), 100_000_000);
+        assert_eq!(rule.scaled_size(10_000_000_000), 1_000_000_000);
+
+        let unbounded = Rule { max_size: None, ..rule };
+        assert_eq!(unbounded.scaled_size(10_000_000_000), 5_000_000_000);
+    }
+
+    #[test]
+    fn accepts_market_cap() {
+        let unlimited = rule(json!({}));
+        assert!(unlimited.accepts_market_cap(None));
+        assert!(unlimited.accepts_market_cap(Some(1e9)));
+
+        let limited = rule(json!({"max_market_cap_sol": 100}));
+        assert!(limited.accepts_market_cap(Some(100.0)));
+        assert!(!limited.accepts_market_cap(Some(100.5)));
+        // 设置了上限时市值未知的兑换不触发
+        assert!(!limited.accepts_market_cap(None));
+    }
+}
@@ -0,0 +1,436 @@
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+
+use crate::accounts::BondingCurveAccount;
+use crate::transaction::pump_parser::{BondingCurveInfo, PumpCreate, PumpTrade};
+
+// Pump代币精度为6位，SOL为9位
+const TOKEN_DECIMALS_FACTOR: f64 = 1_000_000.0;
+const LAMPORTS_PER_SOL: f64 = 1_000_000_000.0;
+
+// 超过这么多slot没有成交的曲线会被清理(约1天)
+const MAX_IDLE_SLOTS: u64 = 216_000;
+// 每隔多少个slot清理一次
+const PRUNE_INTERVAL_SLOTS: u64 = 1_000;
+
+/// Pump `Global` 账户中与曲线相关的参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlobalParams {
+    pub initial_virtual_token_reserves: u64,
+    pub initial_virtual_sol_reserves: u64,
+    pub initial_real_token_reserves: u64,
+    pub token_total_supply: u64,
+    pub fee_basis_points: u64,
+    // creator fee更新后每笔交易额外付给曲线创建者的费率，更新前的Global账户没有这个字段
+    pub creator_fee_basis_points: u64,
+}
+
+impl Default for GlobalParams {
+    // 主网Global账户的当前取值
+    fn default() -> Self {
+        Self {
+            initial_virtual_token_reserves: 1_073_000_000_000_000,
+            initial_virtual_sol_reserves: 30_000_000_000,
+            initial_real_token_reserves: 793_100_000_000_000,
+            token_total_supply: 1_000_000_000_000_000,
+            fee_basis_points: 100,
+            creator_fee_basis_points: 5,
+        }
+    }
+}
+
+impl GlobalParams {
+    fn fee(&self, lamports: u64) -> u64 {
+        (lamports as u128 * self.fee_basis_points as u128 / 10_000) as u64
+    }
+
+    fn creator_fee(&self, lamports: u64) -> u64 {
+        (lamports as u128 * self.creator_fee_basis_points as u128 / 10_000) as u64
+    }
+}
+
+// 曲线状态的来源，决定估计值是否可信
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurveSource {
+    // 看到了create指令，从Global初始参数开始精确推算
+    Create,
+    // 从账户快照加载
+    Snapshot,
+    // 启动前已存在的曲线，按初始参数假设，储备值仅供参考
+    Assumed,
+}
+
+// 单个曲线的模拟状态
+#[derive(Debug, Clone)]
+pub struct CurveState {
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub virtual_token_reserves: u64,
+    pub virtual_sol_reserves: u64,
+    pub real_token_reserves: u64,
+    pub real_sol_reserves: u64,
+    pub token_total_supply: u64,
+    pub complete: bool,
+    pub source: CurveSource,
+    pub trades: u64,
+    pub last_slot: u64,
+}
+
+// 一笔交易应用到曲线后的结果
+#[derive(Debug, Clone, Copy)]
+pub struct AppliedTrade {
+    // 实际成交的代币数量
+    pub token_amount: u64,
+    // 不含手续费的SOL数量
+    pub sol_amount: u64,
+    // 协议手续费
+    pub fee: u64,
+    // 付给曲线创建者的手续费
+    pub creator_fee: u64,
+    // 估算的成交金额超出滑点限制，交易大概率失败，未应用到状态
+    pub likely_failed: bool,
+}
+
+impl CurveState {
+    fn new(params: &GlobalParams, mint: Pubkey, bonding_curve: Pubkey, source: CurveSource, slot: u64) -> Self {
+        Self {
+            mint,
+            bonding_curve,
+            virtual_token_reserves: params.initial_virtual_token_reserves,
+            virtual_sol_reserves: params.initial_virtual_sol_reserves,
+            real_token_reserves: params.initial_real_token_reserves,
+            real_sol_reserves: 0,
+            token_total_supply: params.token_total_supply,
+            complete: false,
+            source,
+            trades: 0,
+            last_slot: slot,
+        }
+    }
+
+    fn from_account(account: &BondingCurveAccount, mint: Pubkey, bonding_curve: Pubkey, slot: u64) -> Self {
+        Self {
+            mint,
+            bonding_curve,
+            virtual_token_reserves: account.virtual_token_reserves,
+            virtual_sol_reserves: account.virtual_sol_reserves,
+            real_token_reserves: account.real_token_reserves,
+            real_sol_reserves: account.real_sol_reserves,
+            token_total_supply: account.token_total_supply,
+            complete: account.complete,
+            source: CurveSource::Snapshot,
+            trades: 0,
+            last_slot: slot,
+        }
+    }
+
+    /// 买入指定数量代币需要的SOL(不含手续费)
+    pub fn buy_quote(&self, token_amount: u64) -> u64 {
+        if token_amount == 0 || token_amount >= self.virtual_token_reserves {
+            return 0;
+        }
+        let product = self.virtual_sol_reserves as u128 * self.virtual_token_reserves as u128;
+        let new_token_reserves = (self.virtual_token_reserves - token_amount) as u128;
+        let new_sol_reserves = product / new_token_reserves + 1;
+        (new_sol_reserves - self.virtual_sol_reserves as u128) as u64
+    }
+
+    /// 卖出指定数量代币得到的SOL(不含手续费)
+    pub fn sell_quote(&self, token_amount: u64) -> u64 {
+        if token_amount == 0 {
+            return 0;
+        }
+        let numerator = token_amount as u128 * self.virtual_sol_reserves as u128;
+        let denominator = self.virtual_token_reserves as u128 + token_amount as u128;
+        (numerator / denominator) as u64
+    }
+
+    /// 当前价格，单位为 SOL/代币
+    pub fn price_sol(&self) -> f64 {
+        if self.virtual_token_reserves == 0 {
+            return 0.0;
+        }
+        (self.virtual_sol_reserves as f64 / LAMPORTS_PER_SOL)
+            / (self.virtual_token_reserves as f64 / TOKEN_DECIMALS_FACTOR)
+    }
+
+    /// 按总供应量计算的市值，单位为SOL
+    pub fn market_cap_sol(&self) -> f64 {
+        self.price_sol() * self.token_total_supply as f64 / TOKEN_DECIMALS_FACTOR
+    }
+
+    /// 完成进度(0.0 ~ 1.0)，按已售出的实际代币储备计算
+    pub fn progress(&self, params: &GlobalParams) -> f64 {
+        if self.complete {
+            return 1.0;
+        }
+        if params.initial_real_token_reserves == 0 {
+            return 0.0;
+        }
+        let sold = params.initial_real_token_reserves.saturating_sub(self.real_token_reserves);
+        sold as f64 / params.initial_real_token_reserves as f64
+    }
+
+    /// 转换为解析器使用的曲线信息
+    pub fn to_info(&self) -> BondingCurveInfo {
+        BondingCurveInfo {
+            mint: self.mint,
+            curve_account: self.bonding_curve,
+            is_complete: self.complete,
+            virtual_token_reserves: Some(self.virtual_token_reserves),
+            virtual_sol_reserves: Some(self.virtual_sol_reserves),
+            real_token_reserves: Some(self.real_token_reserves),
+            real_sol_reserves: Some(self.real_sol_reserves),
+        }
+    }
+
+    fn apply_buy(&mut self, params: &GlobalParams, requested: u64, max_sol_cost: u64) -> AppliedTrade {
+        let token_amount = requested.min(self.real_token_reserves);
+        let sol_amount = self.buy_quote(token_amount);
+        let fee = params.fee(sol_amount);
+        let creator_fee = params.creator_fee(sol_amount);
+
+        if self.complete || token_amount == 0 || sol_amount.saturating_add(fee).saturating_add(creator_fee) > max_sol_cost {
+            return AppliedTrade { token_amount, sol_amount, fee, creator_fee, likely_failed: true };
+        }
+
+        self.virtual_token_reserves -= token_amount;
+        self.real_token_reserves -= token_amount;
+        self.virtual_sol_reserves += sol_amount;
+        self.real_sol_reserves += sol_amount;
+        if self.real_token_reserves == 0 {
+            self.complete = true;
+        }
+
+        AppliedTrade { token_amount, sol_amount, fee, creator_fee, likely_failed: false }
+    }
+
+    fn apply_sell(&mut self, params: &GlobalParams, token_amount: u64, min_sol_output: u64) -> AppliedTrade {
+        let sol_amount = self.sell_quote(token_amount).min(self.real_sol_reserves);
+        let fee = params.fee(sol_amount);
+        let creator_fee = params.creator_fee(sol_amount);
+
+        if self.complete || token_amount == 0 || sol_amount.saturating_sub(fee).saturating_sub(creator_fee) < min_sol_output {
+            return AppliedTrade { token_amount, sol_amount, fee, creator_fee, likely_failed: true };
+        }
+
+        self.virtual_token_reserves += token_amount;
+        self.real_token_reserves += token_amount;
+        self.virtual_sol_reserves -= sol_amount;
+        self.real_sol_reserves -= sol_amount;
+
+        AppliedTrade { token_amount, sol_amount, fee, creator_fee, likely_failed: false }
+    }
+}
+
+/// 按mint维护的Pump曲线状态模拟器
+///
+/// 从create指令(或Global默认参数)开始，按观察到的每笔buy/sell推算储备。
+/// shredstream只能看到交易本身，看不到执行结果，所以超出滑点限制的交易按失败处理；
+/// 曲线账户快照按曲线地址保存，第一次看到该曲线的交易时(此时才知道mint)转为精确的起始状态。
+#[derive(Debug, Default)]
+pub struct BondingCurveEngine {
+    params: GlobalParams,
+    curves: HashMap<Pubkey, CurveState>,
+    // 尚未观察到交易的快照，按曲线地址索引
+    snapshots: HashMap<Pubkey, BondingCurveAccount>,
+    last_prune_slot: u64,
+}
+
+impl BondingCurveEngine {
+    pub fn new(params: GlobalParams) -> Self {
+        Self {
+            params,
+            ..Default::default()
+        }
+    }
+
+    pub fn params(&self) -> &GlobalParams {
+        &self.params
+    }
+
+    /// 更新Global参数，只影响之后新建的曲线
+    pub fn set_params(&mut self, params: GlobalParams) {
+        self.params = params;
+    }
+
+    pub fn curve(&self, mint: &Pubkey) -> Option<&CurveState> {
+        self.curves.get(mint)
+    }
+
+    /// 加载曲线账户快照，已完成的曲线不会再有交易，直接忽略
+    pub fn seed_curve(&mut self, bonding_curve: Pubkey, account: BondingCurveAccount) -> bool {
+        if account.complete {
+            return false;
+        }
+        self.snapshots.insert(bonding_curve, account);
+        true
+    }
+
+    pub fn on_create(&mut self, create: &PumpCreate, slot: u64) -> &CurveState {
+        self.maybe_prune(slot);
+        self.snapshots.remove(&create.bonding_curve);
+        let state = CurveState::new(&self.params, create.mint, create.bonding_curve, CurveSource::Create, slot);
+        self.curves.insert(create.mint, state);
+        &self.curves[&create.mint]
+    }
+
+    pub fn on_trade(&mut self, trade: &PumpTrade, slot: u64) -> (&CurveState, AppliedTrade) {
+        self.maybe_prune(slot);
+        let params = self.params;
+        let snapshots = &mut self.snapshots;
+        let state = self.curves.entry(trade.mint).or_insert_with(|| match snapshots.remove(&trade.bonding_curve) {
+            Some(account) => CurveState::from_account(&account, trade.mint, trade.bonding_curve, slot),
+            None => CurveState::new(&params, trade.mint, trade.bonding_curve, CurveSource::Assumed, slot),
+        });
+
+        let applied = if trade.is_buy {
+            state.apply_buy(&params, trade.token_amount, trade.sol_limit)
+        } else {
+            state.apply_sell(&params, trade.token_amount, trade.sol_limit)
+        };
+        if !applied.likely_failed {
+            state.trades += 1;
+        }
+        state.last_slot = slot;
+
+        (state, applied)
+    }
+
+    /// 曲线完成并被迁移账户提取流动性，之后不会再有交易
+    pub fn on_complete(&mut self, mint: &Pubkey, slot: u64) -> Option<&CurveState> {
+        self.maybe_prune(slot);
+        let state = self.curves.get_mut(mint)?;
+        state.complete = true;
+        state.last_slot = slot;
+        Some(state)
+    }
+
+    // 清理已完成或长时间没有成交的曲线
+    fn maybe_prune(&mut self, slot: u64) {
+        if slot < self.last_prune_slot + PRUNE_INTERVAL_SLOTS {
+            return;
+        }
+        self.last_prune_slot = slot;
+        self.curves
+            .retain(|_, state| !state.complete && slot.saturating_sub(state.last_slot) < MAX_IDLE_SLOTS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create(mint: Pubkey) -> PumpCreate {
+        let user = Pubkey::new_unique();
+        PumpCreate {
+            mint,
+            bonding_curve: Pubkey::new_unique(),
+            associated_bonding_curve: Pubkey::new_unique(),
+            user,
+            creator: user,
+            token_program: Pubkey::new_unique(),
+            name: String::new(),
+            symbol: String::new(),
+            uri: String::new(),
+        }
+    }
+
+    fn trade(create: &PumpCreate, is_buy: bool, token_amount: u64, sol_limit: u64) -> PumpTrade {
+        PumpTrade {
+            mint: create.mint,
+            bonding_curve: create.bonding_curve,
+            user: Pubkey::new_unique(),
+            creator_vault: None,
+            is_buy,
+            token_amount,
+            sol_limit,
+        }
+    }
+
+    fn reserves(state: &CurveState) -> (u64, u64, u64, u64) {
+        (state.virtual_token_reserves, state.virtual_sol_reserves, state.real_token_reserves, state.real_sol_reserves)
+    }
+
+    #[test]
+    fn buy_then_sell_from_initial_reserves() {
+        let mut engine = BondingCurveEngine::default();
+        let create = create(Pubkey::new_unique());
+        engine.on_create(&create, 1);
+
+        // 从主网初始储备买入100万个代币: 30e9 * 1.073e15 / 1.072e15 向下取整加1
+        let (state, applied) = engine.on_trade(&trade(&create, true, 1_000_000_000_000, u64::MAX), 2);
+        assert_eq!(
+            (applied.token_amount, applied.sol_amount, applied.fee, applied.creator_fee, applied.likely_failed),
+            (1_000_000_000_000, 27_985_075, 279_850, 13_992, false)
+        );
+        assert_eq!(reserves(state), (1_072_000_000_000_000, 30_027_985_075, 792_100_000_000_000, 27_985_075));
+
+        // 卖回同样数量，买入时多付的1 lamport留在曲线中
+        let (state, applied) = engine.on_trade(&trade(&create, false, 1_000_000_000_000, 0), 3);
+        assert_eq!(
+            (applied.sol_amount, applied.fee, applied.creator_fee, applied.likely_failed),
+            (27_985_074, 279_850, 13_992, false)
+        );
+        assert_eq!(reserves(state), (1_073_000_000_000_000, 30_000_000_001, 793_100_000_000_000, 1));
+        assert_eq!(state.trades, 2);
+    }
+
+    #[test]
+    fn buying_all_real_reserves_completes_the_curve() {
+        let mut engine = BondingCurveEngine::default();
+        let create = create(Pubkey::new_unique());
+        engine.on_create(&create, 1);
+
+        // 请求数量超过实际储备时按实际储备成交，整条曲线约85 SOL
+        let (state, applied) = engine.on_trade(&trade(&create, true, u64::MAX / 2, u64::MAX), 2);
+        assert_eq!((applied.token_amount, applied.sol_amount), (793_100_000_000_000, 85_005_359_057));
+        assert!(state.complete);
+        assert_eq!(reserves(state), (279_900_000_000_000, 115_005_359_057, 0, 85_005_359_057));
+        assert_eq!(state.progress(&GlobalParams::default()), 1.0);
+
+        let (_, applied) = engine.on_trade(&trade(&create, false, 1_000_000, 0), 3);
+        assert!(applied.likely_failed);
+    }
+
+    #[test]
+    fn slippage_failure_leaves_reserves_unchanged() {
+        let mut engine = BondingCurveEngine::default();
+        let create = create(Pubkey::new_unique());
+        engine.on_create(&create, 1);
+
+        // 含手续费需要 27_985_075 + 279_850 + 13_992 lamports
+        let (state, applied) = engine.on_trade(&trade(&create, true, 1_000_000_000_000, 28_278_916), 2);
+        assert!(applied.likely_failed);
+        assert_eq!(reserves(state), (1_073_000_000_000_000, 30_000_000_000, 793_100_000_000_000, 0));
+        let (state, applied) = engine.on_trade(&trade(&create, true, 1_000_000_000_000, 28_278_917), 3);
+        assert!(!applied.likely_failed);
+        assert_eq!(state.trades, 1);
+    }
+
+    #[test]
+    fn snapshot_seeds_first_trade() {
+        let mut engine = BondingCurveEngine::default();
+        let create = create(Pubkey::new_unique());
+        let account = BondingCurveAccount {
+            virtual_token_reserves: 1_072_000_000_000_000,
+            virtual_sol_reserves: 30_027_985_075,
+            real_token_reserves: 792_100_000_000_000,
+            real_sol_reserves: 27_985_075,
+            token_total_supply: 1_000_000_000_000_000,
+            complete: false,
+            creator: None,
+        };
+        assert!(engine.seed_curve(create.bonding_curve, account));
+
+        let (state, applied) = engine.on_trade(&trade(&create, false, 1_000_000_000_000, 0), 2);
+        assert_eq!(state.source, CurveSource::Snapshot);
+        assert_eq!(applied.sol_amount, 27_985_074);
+        assert_eq!(reserves(state), (1_073_000_000_000_000, 30_000_000_001, 793_100_000_000_000, 1));
+
+        // 没有快照也没有create的曲线按初始参数假设
+        let unknown = self::create(Pubkey::new_unique());
+        let (state, _) = engine.on_trade(&trade(&unknown, true, 1_000_000_000_000, u64::MAX), 3);
+        assert_eq!(state.source, CurveSource::Assumed);
+    }
+}
@@ -0,0 +1,549 @@
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use tracing::debug;
+
+use crate::accounts::snapshot::AccountSnapshot;
+use crate::accounts::{decode_account, DecodedAccount, TokenAccount};
+use crate::detector::graduation::GraduationTracker;
+use crate::detector::tip::TipTracker;
+use crate::detector::wallet::WalletTracker;
+use crate::signal::{CopySignal, CopyTradeEngine};
+use crate::transaction::pump_parser::{lamports_to_sol_string, PumpAction};
+use crate::transaction::pumpamm_parser::PumpAmmAction;
+use crate::transaction::swap::SwapIntent;
+use crate::transaction::DecodedTransaction;
+
+pub mod bonding_curve;
+use bonding_curve::{BondingCurveEngine, CurveSource, CurveState, GlobalParams};
+pub mod pool;
+use pool::{FeeConfig, PoolRegistry, PoolSource, PoolState};
+
+// 账户快照的加载结果
+#[derive(Debug, Default)]
+pub struct SnapshotSummary {
+    pub globals: usize,
+    pub global_configs: usize,
+    pub curves: usize,
+    pub pools: usize,
+    // 金库余额也在快照中、储备已知的池
+    pub pools_with_reserves: usize,
+    // 池账户记录的金库在快照中，但mint或owner与池不符
+    pub mismatched_vaults: usize,
+    pub token_accounts: usize,
+    pub unrecognized: usize,
+    pub failed: usize,
+}
+
+/// 按接收顺序维护的链上状态估计
+///
+/// worker并行解码交易，状态更新必须按顺序进行，所以状态引擎运行在管线的排序阶段。
+/// 需要跨交易关联的事件检测(曲线毕业、按slot汇总小费、关注钱包)和跟单规则匹配也在这里进行。
+#[derive(Debug, Default)]
+pub struct StateEngine {
+    pub curves: BondingCurveEngine,
+    pub pools: PoolRegistry,
+    pub graduations: GraduationTracker,
+    pub tips: TipTracker,
+    pub wallets: WalletTracker,
+    pub signals: CopyTradeEngine,
+}
+
+impl StateEngine {
+    pub fn new(params: GlobalParams, fees: FeeConfig) -> Self {
+        Self {
+            curves: BondingCurveEngine::new(params),
+            pools: PoolRegistry::new(fees),
+            graduations: GraduationTracker::default(),
+            tips: TipTracker::default(),
+            wallets: WalletTracker::default(),
+            signals: CopyTradeEngine::default(),
+        }
+    }
+
+    /// 用账户快照初始化Global参数、费率、曲线和池状态
+    pub fn load_snapshot(&mut self, snapshot: &AccountSnapshot) -> SnapshotSummary {
+        let mut summary = SnapshotSummary::default();
+
+        let mut decoded = Vec::with_capacity(snapshot.accounts.len());
+        for account in &snapshot.accounts {
+            match decode_account(account.owner.as_ref(), &account.data) {
+                Some(Ok(parsed)) => decoded.push((account.pubkey, parsed)),
+                Some(Err(e)) => {
+                    debug!(pubkey = %account.pubkey, error = %e, "快照账户解析失败");
+                    summary.failed += 1;
+                }
+                None => summary.unrecognized += 1,
+            }
+        }
+
+        // 池储备在代币金库中，先收集所有代币账户
+        let token_accounts: HashMap<Pubkey, TokenAccount> = decoded
+            .iter()
+            .filter_map(|(pubkey, account)| match account {
+                DecodedAccount::Token(token) => Some((*pubkey, *token)),
+                _ => None,
+            })
+            .collect();
+
+        for (pubkey, account) in decoded {
+            match account {
+                DecodedAccount::Global(global) => {
+                    self.curves.set_params(global.params);
+                    summary.globals += 1;
+                }
+                DecodedAccount::GlobalConfig(config) => {
+                    self.pools.set_fees(config.fees);
+                    summary.global_configs += 1;
+                }
+                DecodedAccount::BondingCurve(curve) => {
+                    if self.curves.seed_curve(pubkey, curve) {
+                        summary.curves += 1;
+                    }
+                }
+                DecodedAccount::Pool(pool) => {
+                    // 金库必须是池持有的对应mint的代币账户，否则余额不是池的储备
+                    let mut vault = |address: &Pubkey, mint: &Pubkey| {
+                        let token = token_accounts.get(address)?;
+                        if token.mint != *mint || token.owner != pubkey {
+                            debug!(pool = %pubkey, vault = %address, mint = %token.mint, owner = %token.owner, "池金库与池账户不符");
+                            summary.mismatched_vaults += 1;
+                            return None;
+                        }
+                        Some(token.amount)
+                    };
+                    let base = vault(&pool.pool_base_token_account, &pool.base_mint);
+                    let quote = vault(&pool.pool_quote_token_account, &pool.quote_mint);
+                    let reserves = base.zip(quote);
+                    if reserves.is_some() {
+                        summary.pools_with_reserves += 1;
+                    }
+                    self.pools.seed_pool(pubkey, &pool, reserves);
+                    summary.pools += 1;
+                }
+                DecodedAccount::Token(_) => summary.token_accounts += 1,
+            }
+        }
+
+        summary
+    }
+
+    /// 应用一批解码后的交易，命中目标账户的交易输出状态估计，跟单规则触发的信号追加到 `signals`
+    pub fn apply(
+        &mut self,
+        transactions: &[DecodedTransaction],
+        out: &mut String,
+        signals: &mut Vec<CopySignal>,
+    ) -> fmt::Result {
+        let params = *self.curves.params();
+        for transaction in transactions {
+            for slot_tips in self.tips.on_transaction(transaction) {
+                slot_tips.log();
+            }
+
+            let intents = transaction.swap_intents();
+            for intent in &intents {
+                log_swap_intent(transaction, intent);
+            }
+            for alert in self.wallets.on_transaction(transaction, &intents) {
+                alert.log();
+                alert.write(out)?;
+            }
+
+            for action in &transaction.pump_actions {
+                match action {
+                    PumpAction::Create(create) => {
+                        let state = self.curves.on_create(create, transaction.slot);
+                        if transaction.matched {
+                            writeln!(out, "\nPump曲线状态估计 (交易 {}):", transaction.signature)?;
+                            writeln!(
+                                out,
+                                "  新建曲线: 名称=\"{}\", 符号=\"{}\", URI=\"{}\", 创建者={}",
+                                create.name, create.symbol, create.uri, create.creator
+                            )?;
+                            writeln!(out, "  关联曲线代币账户: {}", create.associated_bonding_curve)?;
+                            write_curve_state(out, state, &params)?;
+                        }
+                    }
+                    PumpAction::Trade(trade) => {
+                        let (state, applied) = self.curves.on_trade(trade, transaction.slot);
+                        if transaction.matched {
+                            writeln!(out, "\nPump曲线状态估计 (交易 {}):", transaction.signature)?;
+                            writeln!(
+                                out,
+                                "  {}: 用户={}, 代币数量={}, SOL={}, 手续费={}, 创建者手续费={}, 优先费={}, 小费={}{}",
+                                if trade.is_buy { "买入" } else { "卖出" },
+                                trade.user,
+                                applied.token_amount,
+                                lamports_to_sol_string(applied.sol_amount),
+                                lamports_to_sol_string(applied.fee),
+                                lamports_to_sol_string(applied.creator_fee),
+                                lamports_to_sol_string(transaction.compute_budget.priority_fee_lamports()),
+                                lamports_to_sol_string(transaction.tip_lamports()),
+                                if applied.likely_failed { " (超出滑点限制，可能失败，未计入状态)" } else { "" }
+                            )?;
+                            write_curve_state(out, state, &params)?;
+                        }
+                    }
+                    PumpAction::Withdraw(withdraw) => {
+                        self.curves.on_complete(&withdraw.mint, transaction.slot);
+                        let completed = self.graduations.on_withdraw(withdraw, transaction.signature, transaction.slot);
+                        completed.log();
+                        completed.write(out)?;
+                    }
+                    PumpAction::Migrate(migrate) => {
+                        self.curves.on_complete(&migrate.mint, transaction.slot);
+                        let graduated = self.graduations.on_migrate(migrate, transaction.signature, transaction.slot);
+                        graduated.log();
+                        graduated.write(out)?;
+                    }
+                }
+            }
+
+            for action in &transaction.pumpamm_actions {
+                self.apply_pumpamm_action(transaction, action, out)?;
+            }
+
+            // 在曲线和池状态应用这笔交易之后匹配，市值按成交后的状态估计
+            for signal in self.signals.on_transaction(transaction, &intents, &self.curves, &self.pools) {
+                signal.log();
+                signals.push(signal);
+            }
+
+            if transaction.has_dex_actions() {
+                log_dex_actions(transaction);
+                if transaction.matched {
+                    write_dex_actions(out, transaction)?;
+                }
+            }
+
+            if transaction.has_pump_routes() {
+                log_pump_routes(transaction);
+                if transaction.matched {
+                    write_pump_routes(out, transaction)?;
+                }
+            }
+
+            if transaction.matched
+                && (!transaction.pump_actions.is_empty()
+                    || !transaction.pumpamm_actions.is_empty()
+                    || transaction.has_dex_actions()
+                    || transaction.has_pump_routes())
+            {
+                transaction.write_flows(out)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn apply_pumpamm_action(
+        &mut self,
+        transaction: &DecodedTransaction,
+        action: &PumpAmmAction,
+        out: &mut String,
+    ) -> fmt::Result {
+        let slot = transaction.slot;
+        match action {
+            PumpAmmAction::CreatePool(create) => {
+                let curves = &self.curves;
+                let graduated = self.graduations.on_create_pool(create, transaction.signature, slot, |mint| {
+                    curves.curve(mint).filter(|state| state.complete).map(|state| state.bonding_curve)
+                });
+                if let Some(graduated) = graduated {
+                    graduated.log();
+                    graduated.write(out)?;
+                }
+
+                let fees = *self.pools.fees();
+                let state = self.pools.on_create(create, slot);
+                if transaction.matched {
+                    writeln!(out, "\nPump AMM池状态估计 (交易 {}):", transaction.signature)?;
+                    writeln!(out, "  新建池: 索引={}", create.index)?;
+                    write_pool_state(out, state, &fees)?;
+                }
+            }
+            PumpAmmAction::Swap(swap) => {
+                let fees = *self.pools.fees();
+                let (state, applied) = self.pools.on_swap(swap, slot);
+                if transaction.matched {
+                    writeln!(out, "\nPump AMM池状态估计 (交易 {}):", transaction.signature)?;
+                    let side = if swap.is_buy { "买入" } else { "卖出" };
+                    match applied {
+                        Some(applied) => {
+                            let quote = applied.quote;
+                            writeln!(
+                                out,
+                                "  {}: 用户={}, 基础代币={}, 报价代币={}, LP手续费={}, 协议手续费={}, 创建者手续费={}, 优先费={}, 小费={}{}",
+                                side,
+                                swap.user,
+                                quote.base_amount,
+                                lamports_to_sol_string(quote.quote_amount),
+                                lamports_to_sol_string(quote.lp_fee),
+                                lamports_to_sol_string(quote.protocol_fee),
+                                lamports_to_sol_string(quote.coin_creator_fee),
+                                lamports_to_sol_string(transaction.compute_budget.priority_fee_lamports()),
+                                lamports_to_sol_string(transaction.tip_lamports()),
+                                if applied.likely_failed { " (超出滑点限制，可能失败，未计入状态)" } else { "" }
+                            )?;
+                        }
+                        None => writeln!(out, "  {}: 用户={}, 基础代币={} (池储备未知)", side, swap.user, swap.base_amount)?,
+                    }
+                    write_pool_state(out, state, &fees)?;
+                }
+            }
+            PumpAmmAction::Liquidity(liquidity) => {
+                let fees = *self.pools.fees();
+                let (state, applied) = self.pools.on_liquidity(liquidity, slot);
+                if transaction.matched {
+                    writeln!(out, "\nPump AMM池状态估计 (交易 {}):", transaction.signature)?;
+                    let side = if liquidity.is_deposit { "存入流动性" } else { "提取流动性" };
+                    match applied {
+                        Some(applied) => writeln!(
+                            out,
+                            "  {}: 用户={}, LP={}, 基础代币={}, 报价代币={}{}",
+                            side,
+                            liquidity.user,
+                            liquidity.lp_token_amount,
+                            applied.base_amount,
+                            lamports_to_sol_string(applied.quote_amount),
+                            if applied.likely_failed { " (超出滑点限制，可能失败，未计入状态)" } else { "" }
+                        )?,
+                        None => writeln!(
+                            out,
+                            "  {}: 用户={}, LP={} (池储备未知)",
+                            side, liquidity.user, liquidity.lp_token_amount
+                        )?,
+                    }
+                    write_pool_state(out, state, &fees)?;
+                }
+            }
+            PumpAmmAction::UpdateFeeConfig {
+                lp_fee_basis_points,
+                protocol_fee_basis_points,
+            } => {
+                // update_fee_config的创建者费率不在解析的参数中，沿用当前值
+                let fees = FeeConfig {
+                    lp_fee_basis_points: *lp_fee_basis_points,
+                    protocol_fee_basis_points: *protocol_fee_basis_points,
+                    ..*self.pools.fees()
+                };
+                self.pools.set_fees(fees);
+                if transaction.matched {
+                    writeln!(
+                        out,
+                        "\nPump AMM费率更新 (交易 {}): LP={}bps, 协议={}bps",
+                        transaction.signature, lp_fee_basis_points, protocol_fee_basis_points
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// 输出单个曲线的估计状态
+fn write_curve_state(out: &mut String, state: &CurveState, params: &GlobalParams) -> fmt::Result {
+    let info = state.to_info();
+    let source = match state.source {
+        CurveSource::Create => "从创建开始推算",
+        CurveSource::Assumed => "起始状态未知，按初始参数假设",
+        CurveSource::Snapshot => "从账户快照开始推算",
+    };
+
+    writeln!(out, "  代币Mint: {}", info.mint)?;
+    writeln!(out, "  曲线账户: {}", info.curve_account)?;
+    writeln!(out, "  数据来源: {}", source)?;
+    if let (Some(virtual_sol), Some(virtual_token)) = (info.virtual_sol_reserves, info.virtual_token_reserves) {
+        writeln!(out, "  虚拟储备: SOL={}, 代币={}", lamports_to_sol_string(virtual_sol), virtual_token)?;
+    }
+    if let (Some(real_sol), Some(real_token)) = (info.real_sol_reserves, info.real_token_reserves) {
+        writeln!(out, "  实际储备: SOL={}, 代币={}", lamports_to_sol_string(real_sol), real_token)?;
+    }
+    writeln!(out, "  价格: {:.10} SOL", state.price_sol())?;
+    writeln!(out, "  市值: {:.2} SOL", state.market_cap_sol())?;
+    writeln!(out, "  完成进度: {:.2}%", state.progress(params) * 100.0)?;
+    writeln!(out, "  状态: {}", if info.is_complete { "已完成" } else { "进行中" })
+}
+
+// 统一的兑换记录，供下游按同一种格式消费
+fn log_swap_intent(transaction: &DecodedTransaction, intent: &SwapIntent) {
+    debug!(
+        event = "swap_intent",
+        slot = transaction.slot,
+        signature = %transaction.signature,
+        venue = %intent.venue,
+        side = intent.side.map(|side| side.to_string()),
+        trader = %intent.trader,
+        pool = intent.pool.map(|pool| pool.to_string()),
+        mint_in = intent.mint_in.map(|mint| mint.to_string()),
+        mint_out = intent.mint_out.map(|mint| mint.to_string()),
+        token_mint = intent.token_mint().map(|mint| mint.to_string()),
+        exact_in = intent.exact_in,
+        amount = intent.amount,
+        limit_amount = intent.limit_amount,
+        sol_amount = intent.sol_amount(),
+        token_amount = intent.token_amount(),
+    );
+}
+
+// 其他DEX的交易不参与状态推算，所有交易只记录debug事件
+fn log_dex_actions(transaction: &DecodedTransaction) {
+    for action in &transaction.raydium_amm_actions {
+        debug!(event = "raydium_amm", slot = transaction.slot, signature = %transaction.signature, action = %action);
+    }
+    for action in &transaction.raydium_cpmm_actions {
+        debug!(event = "raydium_cpmm", slot = transaction.slot, signature = %transaction.signature, action = %action);
+    }
+    for swap in &transaction.dlmm_swaps {
+        debug!(event = "meteora_dlmm", slot = transaction.slot, signature = %transaction.signature, action = %swap);
+    }
+    for swap in &transaction.whirlpool_swaps {
+        debug!(event = "orca_whirlpool", slot = transaction.slot, signature = %transaction.signature, action = %swap);
+    }
+}
+
+// 输出命中目标账户的其他DEX兑换和新池
+fn write_dex_actions(out: &mut String, transaction: &DecodedTransaction) -> fmt::Result {
+    writeln!(out, "\nDEX操作 (交易 {}):", transaction.signature)?;
+    for action in &transaction.raydium_amm_actions {
+        writeln!(out, "  Raydium AMM v4 {}", action)?;
+    }
+    for action in &transaction.raydium_cpmm_actions {
+        writeln!(out, "  Raydium CPMM {}", action)?;
+    }
+    for swap in &transaction.dlmm_swaps {
+        writeln!(out, "  Meteora DLMM {}", swap)?;
+    }
+    for swap in &transaction.whirlpool_swaps {
+        writeln!(out, "  Orca Whirlpool {}", swap)?;
+    }
+    writeln!(
+        out,
+        "  优先费={}, 小费={}",
+        lamports_to_sol_string(transaction.compute_budget.priority_fee_lamports()),
+        lamports_to_sol_string(transaction.tip_lamports())
+    )
+}
+
+// 经Jupiter路由的Pump兑换不在顶层指令中，数量只有路由整体的输入输出，不参与状态推算
+fn log_pump_routes(transaction: &DecodedTransaction) {
+    for route in transaction.jupiter_routes.iter().filter(|route| route.routes_via_pump()) {
+        let pools: Vec<String> = route.pumpamm_hops().filter_map(|hop| hop.pool).map(|pool| pool.to_string()).collect();
+        debug!(
+            event = "jupiter_pump_route",
+            slot = transaction.slot,
+            signature = %transaction.signature,
+            user = %route.user,
+            pools = %pools.join(","),
+            route = %route,
+        );
+    }
+}
+
+// 输出命中目标账户、经Jupiter路由到Pump的兑换
+fn write_pump_routes(out: &mut String, transaction: &DecodedTransaction) -> fmt::Result {
+    writeln!(out, "\n经Jupiter路由的Pump兑换 (交易 {}):", transaction.signature)?;
+    for route in transaction.jupiter_routes.iter().filter(|route| route.routes_via_pump()) {
+        writeln!(out, "  {}", route)?;
+        for hop in route.pumpamm_hops() {
+            if let (Some(pool), Some(base_mint), Some(quote_mint)) = (hop.pool, hop.base_mint, hop.quote_mint) {
+                writeln!(out, "  Pump AMM池: {}, 基础代币: {}, 报价代币: {}", pool, base_mint, quote_mint)?;
+            }
+        }
+    }
+    writeln!(
+        out,
+        "  优先费={}, 小费={}",
+        lamports_to_sol_string(transaction.compute_budget.priority_fee_lamports()),
+        lamports_to_sol_string(transaction.tip_lamports())
+    )
+}
+
+// 输出单个池的估计状态
+fn write_pool_state(out: &mut String, state: &PoolState, fees: &FeeConfig) -> fmt::Result {
+    writeln!(out, "  池账户: {}", state.pool)?;
+    if let (Some(base_mint), Some(quote_mint)) = (state.base_mint, state.quote_mint) {
+        writeln!(out, "  基础代币: {}, 报价代币: {}", base_mint, quote_mint)?;
+    }
+    if let Some(lp_mint) = state.lp_mint {
+        writeln!(out, "  LP Mint: {}", lp_mint)?;
+    }
+    if let Some(creator) = state.creator {
+        writeln!(out, "  创建者: {}", creator)?;
+    }
+    let source = match state.source {
+        PoolSource::Create => "从创建开始推算",
+        PoolSource::Snapshot => "从账户快照开始推算",
+        PoolSource::Unknown => "启动前已存在，储备未知",
+    };
+    writeln!(out, "  数据来源: {}", source)?;
+    if !state.has_reserves() {
+        return Ok(());
+    }
+    writeln!(
+        out,
+        "  储备: 基础代币={}, 报价代币={}, LP供应量={}",
+        state.base_reserves,
+        lamports_to_sol_string(state.quote_reserves),
+        state.lp_supply
+    )?;
+    writeln!(out, "  价格: {:.10}", state.price())?;
+    writeln!(
+        out,
+        "  费率: LP={}bps, 协议={}bps, 创建者={}bps, 合计={}bps",
+        fees.lp_fee_basis_points,
+        fees.protocol_fee_basis_points,
+        fees.coin_creator_fee_basis_points,
+        fees.total_basis_points()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::snapshot::SnapshotAccount;
+    use crate::accounts::POOL_DISCRIMINATOR;
+
+    fn pool_data(base_mint: &Pubkey, quote_mint: &Pubkey, base_vault: &Pubkey, quote_vault: &Pubkey) -> Vec<u8> {
+        let mut data = POOL_DISCRIMINATOR.to_vec();
+        data.push(255);
+        data.extend_from_slice(&0u16.to_le_bytes());
+        for key in [&Pubkey::new_unique(), base_mint, quote_mint, &Pubkey::new_unique(), base_vault, quote_vault] {
+            data.extend_from_slice(key.as_ref());
+        }
+        data.extend_from_slice(&1_000u64.to_le_bytes());
+        data
+    }
+
+    fn token_account(pubkey: Pubkey, mint: &Pubkey, owner: &Pubkey, amount: u64) -> SnapshotAccount {
+        let mut data = mint.to_bytes().to_vec();
+        data.extend_from_slice(owner.as_ref());
+        data.extend_from_slice(&amount.to_le_bytes());
+        data.resize(165, 0);
+        SnapshotAccount { pubkey, owner: None, data }
+    }
+
+    #[test]
+    fn pool_reserves_need_matching_vaults() {
+        let (base_mint, quote_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (good_pool, bad_pool) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let vaults: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let snapshot = AccountSnapshot {
+            accounts: vec![
+                SnapshotAccount { pubkey: good_pool, owner: None, data: pool_data(&base_mint, &quote_mint, &vaults[0], &vaults[1]) },
+                token_account(vaults[0], &base_mint, &good_pool, 200_000_000_000_000),
+                token_account(vaults[1], &quote_mint, &good_pool, 80_000_000_000),
+                // 第二个池的基础金库由别的账户持有，报价金库的mint不对
+                SnapshotAccount { pubkey: bad_pool, owner: None, data: pool_data(&base_mint, &quote_mint, &vaults[2], &vaults[3]) },
+                token_account(vaults[2], &base_mint, &Pubkey::new_unique(), 1),
+                token_account(vaults[3], &base_mint, &bad_pool, 1),
+            ],
+            skipped: 0,
+        };
+
+        let summary = StateEngine::default().load_snapshot(&snapshot);
+        assert_eq!((summary.pools, summary.token_accounts), (2, 4));
+        assert_eq!(summary.pools_with_reserves, 1);
+        assert_eq!(summary.mismatched_vaults, 2);
+    }
+}
@@ -0,0 +1,511 @@
+use solana_sdk::pubkey::Pubkey;
+use std::collections::HashMap;
+
+use crate::accounts::PoolAccount;
+use crate::transaction::pumpamm_parser::{PoolCreate, PoolLiquidity, PoolSwap};
+
+// Pump迁移出来的池都是6位精度代币/WSOL
+const BASE_DECIMALS_FACTOR: f64 = 1_000_000.0;
+const QUOTE_DECIMALS_FACTOR: f64 = 1_000_000_000.0;
+
+// create_pool时永久锁定的LP数量
+const MINIMUM_LIQUIDITY: u64 = 100;
+
+// 超过这么多slot没有操作的池会被清理(约1天)
+const MAX_IDLE_SLOTS: u64 = 216_000;
+// 每隔多少个slot清理一次
+const PRUNE_INTERVAL_SLOTS: u64 = 1_000;
+
+/// Pump AMM `GlobalConfig` 中的费率，单位为基点
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeConfig {
+    pub lp_fee_basis_points: u64,
+    pub protocol_fee_basis_points: u64,
+    // creator fee更新后付给池代币创建者的费率，更新前的GlobalConfig没有这个字段
+    pub coin_creator_fee_basis_points: u64,
+}
+
+impl Default for FeeConfig {
+    // 主网GlobalConfig的当前取值
+    fn default() -> Self {
+        Self {
+            lp_fee_basis_points: 20,
+            protocol_fee_basis_points: 5,
+            coin_creator_fee_basis_points: 5,
+        }
+    }
+}
+
+impl FeeConfig {
+    fn lp_fee(&self, amount: u64) -> Option<u64> {
+        u64::try_from((amount as u128 * self.lp_fee_basis_points as u128).div_ceil(10_000)).ok()
+    }
+
+    fn protocol_fee(&self, amount: u64) -> Option<u64> {
+        u64::try_from((amount as u128 * self.protocol_fee_basis_points as u128).div_ceil(10_000)).ok()
+    }
+
+    fn coin_creator_fee(&self, amount: u64) -> Option<u64> {
+        u64::try_from((amount as u128 * self.coin_creator_fee_basis_points as u128).div_ceil(10_000)).ok()
+    }
+
+    pub fn total_basis_points(&self) -> u64 {
+        self.lp_fee_basis_points
+            .saturating_add(self.protocol_fee_basis_points)
+            .saturating_add(self.coin_creator_fee_basis_points)
+    }
+}
+
+// 池状态的来源，决定估计值是否可信
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolSource {
+    // 看到了create_pool指令，从初始注入的流动性开始推算
+    Create,
+    // 从Pool账户和代币金库的快照加载
+    Snapshot,
+    // 启动前已存在的池，储备未知，无法估计
+    Unknown,
+}
+
+/// 一笔兑换的报价，数量均为最小单位
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SwapQuote {
+    pub base_amount: u64,
+    // 不含手续费的报价代币数量
+    pub quote_amount: u64,
+    pub lp_fee: u64,
+    pub protocol_fee: u64,
+    pub coin_creator_fee: u64,
+}
+
+impl SwapQuote {
+    /// 三项手续费之和，LP手续费留在池中，其余转出
+    pub fn total_fee(&self) -> Option<u64> {
+        self.lp_fee.checked_add(self.protocol_fee)?.checked_add(self.coin_creator_fee)
+    }
+
+    /// 买入时用户支付的报价代币总额，超出u64时为None
+    pub fn total_in(&self) -> Option<u64> {
+        self.quote_amount.checked_add(self.total_fee()?)
+    }
+
+    /// 卖出时用户实际得到的报价代币
+    pub fn total_out(&self) -> u64 {
+        self.total_fee().map_or(0, |fee| self.quote_amount.saturating_sub(fee))
+    }
+}
+
+// 一笔兑换应用到池后的结果
+#[derive(Debug, Clone, Copy)]
+pub struct AppliedSwap {
+    pub quote: SwapQuote,
+    // 估算的成交金额超出滑点限制，交易大概率失败，未应用到状态
+    pub likely_failed: bool,
+}
+
+// 一笔流动性变化应用到池后的结果
+#[derive(Debug, Clone, Copy)]
+pub struct AppliedLiquidity {
+    pub base_amount: u64,
+    pub quote_amount: u64,
+    pub likely_failed: bool,
+}
+
+// 单个池的估计状态
+#[derive(Debug, Clone)]
+pub struct PoolState {
+    pub pool: Pubkey,
+    pub base_mint: Option<Pubkey>,
+    pub quote_mint: Option<Pubkey>,
+    pub lp_mint: Option<Pubkey>,
+    pub creator: Option<Pubkey>,
+    pub base_reserves: u64,
+    pub quote_reserves: u64,
+    pub lp_supply: u64,
+    pub source: PoolSource,
+    pub swaps: u64,
+    pub last_slot: u64,
+}
+
+impl PoolState {
+    fn unknown(pool: Pubkey, slot: u64) -> Self {
+        Self {
+            pool,
+            base_mint: None,
+            quote_mint: None,
+            lp_mint: None,
+            creator: None,
+            base_reserves: 0,
+            quote_reserves: 0,
+            lp_supply: 0,
+            source: PoolSource::Unknown,
+            swaps: 0,
+            last_slot: slot,
+        }
+    }
+
+    /// 储备是否已知
+    pub fn has_reserves(&self) -> bool {
+        self.source != PoolSource::Unknown && self.base_reserves > 0 && self.quote_reserves > 0
+    }
+
+    /// 买入指定数量基础代币的报价，无法报价或计算溢出时为空报价
+    pub fn buy_quote(&self, base_amount: u64, fees: &FeeConfig) -> SwapQuote {
+        if !self.has_reserves() || base_amount == 0 || base_amount >= self.base_reserves {
+            return SwapQuote::default();
+        }
+        let numerator = self.quote_reserves as u128 * base_amount as u128;
+        let denominator = (self.base_reserves - base_amount) as u128;
+        Self::quote(base_amount, numerator.div_ceil(denominator), fees)
+    }
+
+    /// 卖出指定数量基础代币的报价，无法报价或计算溢出时为空报价
+    pub fn sell_quote(&self, base_amount: u64, fees: &FeeConfig) -> SwapQuote {
+        if !self.has_reserves() || base_amount == 0 {
+            return SwapQuote::default();
+        }
+        let numerator = self.quote_reserves as u128 * base_amount as u128;
+        let denominator = self.base_reserves as u128 + base_amount as u128;
+        Self::quote(base_amount, numerator / denominator, fees)
+    }
+
+    fn quote(base_amount: u64, quote_amount: u128, fees: &FeeConfig) -> SwapQuote {
+        let quote = u64::try_from(quote_amount).ok().and_then(|quote_amount| {
+            Some(SwapQuote {
+                base_amount,
+                quote_amount,
+                lp_fee: fees.lp_fee(quote_amount)?,
+                protocol_fee: fees.protocol_fee(quote_amount)?,
+                coin_creator_fee: fees.coin_creator_fee(quote_amount)?,
+            })
+        });
+        quote.unwrap_or_default()
+    }
+
+    /// 现货价格，单位为 报价代币/基础代币
+    pub fn price(&self) -> f64 {
+        if !self.has_reserves() {
+            return 0.0;
+        }
+        (self.quote_reserves as f64 / QUOTE_DECIMALS_FACTOR) / (self.base_reserves as f64 / BASE_DECIMALS_FACTOR)
+    }
+
+    /// 按给定的基础代币总供应量(最小单位)计算的市值，单位为报价代币
+    pub fn market_cap(&self, base_total_supply: u64) -> f64 {
+        self.price() * base_total_supply as f64 / BASE_DECIMALS_FACTOR
+    }
+
+    // 计算溢出的兑换视为大概率失败，不修改状态
+    fn apply_swap(&mut self, swap: &PoolSwap, fees: &FeeConfig) -> AppliedSwap {
+        if swap.is_buy {
+            let quote = self.buy_quote(swap.base_amount, fees);
+            let within_limit = quote.total_in().is_some_and(|total_in| total_in <= swap.quote_limit);
+            // LP手续费留在池中，协议手续费转给接收方
+            let reserves = self.base_reserves.checked_sub(quote.base_amount).zip(
+                quote
+                    .quote_amount
+                    .checked_add(quote.lp_fee)
+                    .and_then(|added| self.quote_reserves.checked_add(added)),
+            );
+            match reserves {
+                Some((base_reserves, quote_reserves)) if quote.quote_amount > 0 && within_limit => {
+                    self.base_reserves = base_reserves;
+                    self.quote_reserves = quote_reserves;
+                    AppliedSwap { quote, likely_failed: false }
+                }
+                _ => AppliedSwap { quote, likely_failed: true },
+            }
+        } else {
+            let quote = self.sell_quote(swap.base_amount, fees);
+            let reserves = self.base_reserves.checked_add(quote.base_amount).zip(
+                quote
+                    .quote_amount
+                    .checked_sub(quote.lp_fee)
+                    .and_then(|removed| self.quote_reserves.checked_sub(removed)),
+            );
+            match reserves {
+                Some((base_reserves, quote_reserves))
+                    if quote.quote_amount > 0 && quote.total_out() >= swap.quote_limit =>
+                {
+                    self.base_reserves = base_reserves;
+                    self.quote_reserves = quote_reserves;
+                    AppliedSwap { quote, likely_failed: false }
+                }
+                _ => AppliedSwap { quote, likely_failed: true },
+            }
+        }
+    }
+
+    fn apply_liquidity(&mut self, liquidity: &PoolLiquidity) -> AppliedLiquidity {
+        let failed = AppliedLiquidity { base_amount: 0, quote_amount: 0, likely_failed: true };
+        let lp_amount = liquidity.lp_token_amount as u128;
+        let lp_supply = self.lp_supply as u128;
+        if !self.has_reserves() || lp_amount == 0 || lp_supply == 0 {
+            return failed;
+        }
+
+        if liquidity.is_deposit {
+            let amounts = u64::try_from((self.base_reserves as u128 * lp_amount).div_ceil(lp_supply))
+                .ok()
+                .zip(u64::try_from((self.quote_reserves as u128 * lp_amount).div_ceil(lp_supply)).ok());
+            let Some((base_amount, quote_amount)) = amounts else {
+                return failed;
+            };
+            let updated = self
+                .base_reserves
+                .checked_add(base_amount)
+                .zip(self.quote_reserves.checked_add(quote_amount))
+                .zip(self.lp_supply.checked_add(liquidity.lp_token_amount));
+            let likely_failed = base_amount > liquidity.base_limit || quote_amount > liquidity.quote_limit;
+            match updated {
+                Some(((base_reserves, quote_reserves), lp_supply)) if !likely_failed => {
+                    self.base_reserves = base_reserves;
+                    self.quote_reserves = quote_reserves;
+                    self.lp_supply = lp_supply;
+                    AppliedLiquidity { base_amount, quote_amount, likely_failed }
+                }
+                _ => AppliedLiquidity { base_amount, quote_amount, likely_failed: true },
+            }
+        } else {
+            if lp_amount > lp_supply {
+                return failed;
+            }
+            // 取出的LP不超过供应量，按比例取出的储备不会超过现有储备
+            let base_amount = (self.base_reserves as u128 * lp_amount / lp_supply) as u64;
+            let quote_amount = (self.quote_reserves as u128 * lp_amount / lp_supply) as u64;
+            let likely_failed = base_amount < liquidity.base_limit || quote_amount < liquidity.quote_limit;
+            if !likely_failed {
+                self.base_reserves -= base_amount;
+                self.quote_reserves -= quote_amount;
+                self.lp_supply -= liquidity.lp_token_amount;
+            }
+            AppliedLiquidity { base_amount, quote_amount, likely_failed }
+        }
+    }
+}
+
+/// 按池地址维护的Pump AMM池状态估计
+///
+/// 从create_pool注入的流动性开始，按观察到的buy/sell/deposit/withdraw推算储备和LP供应量，
+/// 费率来自GlobalConfig，观察到update_fee_config时随之更新。
+/// 启动前已存在的池储备未知，只记录操作，不做估计，除非加载了该池的账户快照。
+#[derive(Debug, Default)]
+pub struct PoolRegistry {
+    fees: FeeConfig,
+    pools: HashMap<Pubkey, PoolState>,
+    // 尚未观察到操作的快照，第一次操作时转入pools
+    snapshots: HashMap<Pubkey, PoolState>,
+    last_prune_slot: u64,
+}
+
+impl PoolRegistry {
+    pub fn new(fees: FeeConfig) -> Self {
+        Self {
+            fees,
+            ..Default::default()
+        }
+    }
+
+    pub fn fees(&self) -> &FeeConfig {
+        &self.fees
+    }
+
+    pub fn set_fees(&mut self, fees: FeeConfig) {
+        self.fees = fees;
+    }
+
+    pub fn pool(&self, pool: &Pubkey) -> Option<&PoolState> {
+        self.pools.get(pool)
+    }
+
+    /// 加载Pool账户快照，reserves为两个代币金库的余额(基础, 报价)，金库不在快照中时为None
+    pub fn seed_pool(&mut self, pool: Pubkey, account: &PoolAccount, reserves: Option<(u64, u64)>) {
+        let (base_reserves, quote_reserves) = reserves.unwrap_or_default();
+        let state = PoolState {
+            pool,
+            base_mint: Some(account.base_mint),
+            quote_mint: Some(account.quote_mint),
+            lp_mint: Some(account.lp_mint),
+            creator: Some(account.creator),
+            base_reserves,
+            quote_reserves,
+            lp_supply: account.lp_supply,
+            source: if reserves.is_some() { PoolSource::Snapshot } else { PoolSource::Unknown },
+            swaps: 0,
+            last_slot: 0,
+        };
+        self.snapshots.insert(pool, state);
+    }
+
+    // 取出池状态，不存在时优先使用快照
+    fn entry(&mut self, pool: Pubkey, slot: u64) -> &mut PoolState {
+        let snapshots = &mut self.snapshots;
+        self.pools.entry(pool).or_insert_with(|| {
+            snapshots
+                .remove(&pool)
+                .map(|state| PoolState { last_slot: slot, ..state })
+                .unwrap_or_else(|| PoolState::unknown(pool, slot))
+        })
+    }
+
+    pub fn on_create(&mut self, create: &PoolCreate, slot: u64) -> &PoolState {
+        self.maybe_prune(slot);
+        self.snapshots.remove(&create.pool);
+        // 初始LP数量为 sqrt(base * quote)，其中MINIMUM_LIQUIDITY永久锁定
+        let initial_liquidity = (create.base_amount_in as u128 * create.quote_amount_in as u128).isqrt() as u64;
+        let state = PoolState {
+            pool: create.pool,
+            base_mint: Some(create.base_mint),
+            quote_mint: Some(create.quote_mint),
+            lp_mint: Some(create.lp_mint),
+            creator: Some(create.creator),
+            base_reserves: create.base_amount_in,
+            quote_reserves: create.quote_amount_in,
+            lp_supply: initial_liquidity.saturating_sub(MINIMUM_LIQUIDITY),
+            source: PoolSource::Create,
+            swaps: 0,
+            last_slot: slot,
+        };
+        self.pools.insert(create.pool, state);
+        &self.pools[&create.pool]
+    }
+
+    /// 应用一笔兑换，池储备未知时返回None
+    pub fn on_swap(&mut self, swap: &PoolSwap, slot: u64) -> (&PoolState, Option<AppliedSwap>) {
+        self.maybe_prune(slot);
+        let fees = self.fees;
+        let state = self.entry(swap.pool, slot);
+        state.base_mint.get_or_insert(swap.base_mint);
+        state.quote_mint.get_or_insert(swap.quote_mint);
+        state.last_slot = slot;
+
+        if !state.has_reserves() {
+            return (state, None);
+        }
+        let applied = state.apply_swap(swap, &fees);
+        if !applied.likely_failed {
+            state.swaps += 1;
+        }
+        (state, Some(applied))
+    }
+
+    /// 应用一笔存入或提取，池储备未知时返回None
+    pub fn on_liquidity(&mut self, liquidity: &PoolLiquidity, slot: u64) -> (&PoolState, Option<AppliedLiquidity>) {
+        self.maybe_prune(slot);
+        let state = self.entry(liquidity.pool, slot);
+        state.last_slot = slot;
+
+        if !state.has_reserves() {
+            return (state, None);
+        }
+        let applied = state.apply_liquidity(liquidity);
+        (state, Some(applied))
+    }
+
+    // 清理长时间没有操作的池
+    fn maybe_prune(&mut self, slot: u64) {
+        if slot < self.last_prune_slot + PRUNE_INTERVAL_SLOTS {
+            return;
+        }
+        self.last_prune_slot = slot;
+        self.pools.retain(|_, state| slot.saturating_sub(state.last_slot) < MAX_IDLE_SLOTS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 20万亿基础代币(2亿个) / 80 SOL，接近Pump曲线迁移出来的池
+    fn registry() -> (PoolRegistry, PoolCreate) {
+        let create = PoolCreate {
+            pool: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            base_mint: Pubkey::new_unique(),
+            quote_mint: Pubkey::new_unique(),
+            lp_mint: Pubkey::new_unique(),
+            index: 0,
+            base_amount_in: 200_000_000_000_000,
+            quote_amount_in: 80_000_000_000,
+        };
+        let mut registry = PoolRegistry::default();
+        registry.on_create(&create, 1);
+        (registry, create)
+    }
+
+    fn swap(create: &PoolCreate, is_buy: bool, base_amount: u64, quote_limit: u64) -> PoolSwap {
+        PoolSwap {
+            pool: create.pool,
+            user: Pubkey::new_unique(),
+            base_mint: create.base_mint,
+            quote_mint: create.quote_mint,
+            coin_creator_vault_authority: None,
+            is_buy,
+            base_amount,
+            quote_limit,
+        }
+    }
+
+    #[test]
+    fn create_pool_reserves_and_price() {
+        let (registry, create) = registry();
+        let state = registry.pool(&create.pool).expect("新建的池");
+        assert_eq!((state.base_reserves, state.quote_reserves), (200_000_000_000_000, 80_000_000_000));
+        // sqrt(2e14 * 8e10) - MINIMUM_LIQUIDITY
+        assert_eq!(state.lp_supply, 3_999_999_999_900);
+        assert!((state.price() - 0.000_000_4).abs() < 1e-15);
+        assert!((state.market_cap(1_000_000_000_000_000) - 400.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn constant_product_buy_and_sell() {
+        let (mut registry, create) = registry();
+        let k_before = 200_000_000_000_000u128 * 80_000_000_000u128;
+
+        // 80e9 * 1e12 / 199e12 向上取整，三项手续费按基点向上取整
+        let (state, applied) = registry.on_swap(&swap(&create, true, 1_000_000_000_000, u64::MAX), 2);
+        let quote = applied.expect("储备已知").quote;
+        assert_eq!(
+            (quote.quote_amount, quote.lp_fee, quote.protocol_fee, quote.coin_creator_fee),
+            (402_010_051, 804_021, 201_006, 201_006)
+        );
+        assert_eq!(quote.total_in(), Some(403_216_084));
+        // LP手续费留在池中
+        assert_eq!((state.base_reserves, state.quote_reserves), (199_000_000_000_000, 80_402_814_072));
+        assert!(state.base_reserves as u128 * state.quote_reserves as u128 >= k_before);
+
+        let (state, applied) = registry.on_swap(&swap(&create, false, 1_000_000_000_000, 0), 3);
+        let quote = applied.expect("储备已知").quote;
+        assert_eq!(
+            (quote.quote_amount, quote.lp_fee, quote.protocol_fee, quote.coin_creator_fee),
+            (402_014_070, 804_029, 201_008, 201_008)
+        );
+        assert_eq!(quote.total_out(), 400_808_025);
+        assert_eq!((state.base_reserves, state.quote_reserves), (200_000_000_000_000, 80_001_604_031));
+        assert_eq!(state.swaps, 2);
+    }
+
+    #[test]
+    fn slippage_failure_leaves_reserves_unchanged() {
+        let (mut registry, create) = registry();
+        let (state, applied) = registry.on_swap(&swap(&create, true, 1_000_000_000_000, 403_216_083), 2);
+        assert!(applied.expect("储备已知").likely_failed);
+        assert_eq!((state.base_reserves, state.quote_reserves), (200_000_000_000_000, 80_000_000_000));
+
+        let (state, applied) = registry.on_swap(&swap(&create, false, 1_000_000_000_000, 396_815_921), 3);
+        let applied = applied.expect("储备已知");
+        assert!(applied.likely_failed);
+        assert_eq!(applied.quote.total_out(), 396_815_920);
+        assert_eq!(state.swaps, 0);
+    }
+
+    #[test]
+    fn unknown_pool_has_no_quote() {
+        let (mut registry, create) = registry();
+        let other = PoolCreate { pool: Pubkey::new_unique(), ..create };
+        let (state, applied) = registry.on_swap(&swap(&other, true, 1_000, u64::MAX), 2);
+        assert!(applied.is_none());
+        assert_eq!(state.source, PoolSource::Unknown);
+        assert_eq!(state.buy_quote(1_000, &FeeConfig::default()).quote_amount, 0);
+    }
+}
@@ -0,0 +1,120 @@
+use solana_sdk::{pubkey::Pubkey, transaction::VersionedTransaction};
+use std::fmt;
+
+use super::instruction_account;
+use super::registry::{Decoded, ProgramDecoder};
+
+// Associated Token Account程序ID
+pub const ATA_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+
+// 指令序号，旧版create不带数据
+pub const CREATE_IX: u8 = 0;
+pub const CREATE_IDEMPOTENT_IX: u8 = 1;
+
+// 解析后的ATA指令
+#[derive(Debug)]
+pub struct ParsedAtaInstruction {
+    pub name: String,
+    pub params: String,
+}
+
+// 创建关联代币账户
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtaCreate {
+    pub funder: Pubkey,
+    pub associated_account: Pubkey,
+    pub wallet: Pubkey,
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    // createIdempotent在账户已存在时不报错
+    pub idempotent: bool,
+}
+
+impl fmt::Display for AtaCreate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "创建关联代币账户{}: 账户={}, 钱包={}, Mint={}, 出资={}, 代币程序={}",
+            if self.idempotent { "(幂等)" } else { "" },
+            self.associated_account,
+            self.wallet,
+            self.mint,
+            self.funder,
+            self.token_program
+        )
+    }
+}
+
+/// 解码单条ATA指令
+///
+/// 账户顺序: funder, associatedAccount, wallet, mint, systemProgram, tokenProgram(5)。
+pub fn decode_ata_instruction(static_keys: &[Pubkey], accounts: &[u8], data: &[u8]) -> Option<AtaCreate> {
+    let account = |position| instruction_account(static_keys, accounts, position);
+    let idempotent = match data.first() {
+        None | Some(&CREATE_IX) => false,
+        Some(&CREATE_IDEMPOTENT_IX) => true,
+        Some(_) => return None,
+    };
+
+    Some(AtaCreate {
+        funder: account(0)?,
+        associated_account: account(1)?,
+        wallet: account(2)?,
+        mint: account(3)?,
+        token_program: account(5)?,
+        idempotent,
+    })
+}
+
+/// 解析单个ATA指令
+pub fn parse_ata_instruction(transaction: &VersionedTransaction, instruction_index: usize) -> Option<ParsedAtaInstruction> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+    let instruction = message.instructions().get(instruction_index)?;
+    if instruction.program_id(static_keys).to_string() != ATA_PROGRAM_ID {
+        return None;
+    }
+
+    let parsed = match decode_ata_instruction(static_keys, &instruction.accounts, &instruction.data) {
+        Some(create) => ParsedAtaInstruction {
+            name: if create.idempotent { "CreateIdempotent" } else { "Create" }.to_string(),
+            params: create.to_string(),
+        },
+        None => ParsedAtaInstruction {
+            name: "其他ATA指令".to_string(),
+            params: format!("指令序号: {:?}", instruction.data.first()),
+        },
+    };
+    Some(parsed)
+}
+
+/// 解析交易中的所有顶层ATA创建指令
+pub fn decode_ata_actions(transaction: &VersionedTransaction) -> Vec<AtaCreate> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+
+    message
+        .instructions()
+        .iter()
+        .filter(|instruction| instruction.program_id(static_keys).to_string() == ATA_PROGRAM_ID)
+        .filter_map(|instruction| decode_ata_instruction(static_keys, &instruction.accounts, &instruction.data))
+        .collect()
+}
+
+/// 关联代币账户程序的解码器
+pub struct AtaDecoder;
+
+impl ProgramDecoder for AtaDecoder {
+    fn program_id(&self) -> Pubkey {
+        ATA_PROGRAM_ID.parse().expect("合法的ATA程序ID")
+    }
+
+    fn name(&self) -> &str {
+        "Associated Token"
+    }
+
+    fn decode_instruction(&self, transaction: &VersionedTransaction, instruction_index: usize) -> Option<Decoded> {
+        let parsed = parse_ata_instruction(transaction, instruction_index)?;
+        Some(Decoded::new(parsed.name, parsed.params))
+    }
+}
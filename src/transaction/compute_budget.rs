@@ -0,0 +1,179 @@
+use solana_sdk::{pubkey::Pubkey, transaction::VersionedTransaction};
+use std::fmt;
+
+use super::registry::{Decoded, ProgramDecoder};
+
+// 计算预算程序ID
+pub const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+
+// 未设置计算单元限制时，每条非计算预算指令默认分配的计算单元
+pub const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+// 单笔交易的计算单元上限
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+// 计算单元价格的单位是micro-lamports
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// 计算预算指令，按Borsh枚举布局: 首字节为变体序号，后跟小端参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeBudgetInstruction {
+    // 已废弃的旧指令，同时设置计算单元和额外费用
+    RequestUnitsDeprecated { units: u32, additional_fee: u32 },
+    RequestHeapFrame(u32),
+    SetComputeUnitLimit(u32),
+    // micro-lamports / 计算单元
+    SetComputeUnitPrice(u64),
+    SetLoadedAccountsDataSizeLimit(u32),
+    // 无法识别的变体或数据长度不足
+    Unknown(Option<u8>),
+}
+
+impl ComputeBudgetInstruction {
+    pub fn parse(data: &[u8]) -> Self {
+        let Some((&tag, args)) = data.split_first() else {
+            return ComputeBudgetInstruction::Unknown(None);
+        };
+        let read_u32 = |offset: usize| -> Option<u32> {
+            args.get(offset..offset + 4).map(|bytes| u32::from_le_bytes(bytes.try_into().expect("4字节")))
+        };
+
+        let parsed = match tag {
+            0 => read_u32(0)
+                .zip(read_u32(4))
+                .map(|(units, additional_fee)| ComputeBudgetInstruction::RequestUnitsDeprecated { units, additional_fee }),
+            1 => read_u32(0).map(ComputeBudgetInstruction::RequestHeapFrame),
+            2 => read_u32(0).map(ComputeBudgetInstruction::SetComputeUnitLimit),
+            3 => args
+                .get(..8)
+                .map(|bytes| ComputeBudgetInstruction::SetComputeUnitPrice(u64::from_le_bytes(bytes.try_into().expect("8字节")))),
+            4 => read_u32(0).map(ComputeBudgetInstruction::SetLoadedAccountsDataSizeLimit),
+            _ => None,
+        };
+        parsed.unwrap_or(ComputeBudgetInstruction::Unknown(Some(tag)))
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            ComputeBudgetInstruction::RequestUnitsDeprecated { .. } => "RequestUnits",
+            ComputeBudgetInstruction::RequestHeapFrame(_) => "RequestHeapFrame",
+            ComputeBudgetInstruction::SetComputeUnitLimit(_) => "SetComputeUnitLimit",
+            ComputeBudgetInstruction::SetComputeUnitPrice(_) => "SetComputeUnitPrice",
+            ComputeBudgetInstruction::SetLoadedAccountsDataSizeLimit(_) => "SetLoadedAccountsDataSizeLimit",
+            ComputeBudgetInstruction::Unknown(_) => "Unknown",
+        }
+    }
+}
+
+impl fmt::Display for ComputeBudgetInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComputeBudgetInstruction::RequestUnitsDeprecated { units, additional_fee } => {
+                write!(f, "请求计算单元(已废弃): 单元={}, 额外费用={} lamports", units, additional_fee)
+            }
+            ComputeBudgetInstruction::RequestHeapFrame(bytes) => write!(f, "设置堆内存: {}字节", bytes),
+            ComputeBudgetInstruction::SetComputeUnitLimit(units) => write!(f, "设置计算单元限制: {}", units),
+            ComputeBudgetInstruction::SetComputeUnitPrice(price) => {
+                write!(f, "设置计算单元价格: {} micro-lamports", price)
+            }
+            ComputeBudgetInstruction::SetLoadedAccountsDataSizeLimit(bytes) => {
+                write!(f, "设置加载账户数据上限: {}字节", bytes)
+            }
+            ComputeBudgetInstruction::Unknown(Some(tag)) => write!(f, "未知计算预算操作: {}", tag),
+            ComputeBudgetInstruction::Unknown(None) => write!(f, "空计算预算指令"),
+        }
+    }
+}
+
+/// 一笔交易的计算预算设置
+///
+/// 同一种指令出现多次时交易会执行失败，这里按最后一次出现的值记录，只用于分析出价。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ComputeBudget {
+    pub unit_limit: Option<u32>,
+    pub unit_price_micro_lamports: Option<u64>,
+    pub heap_frame_bytes: Option<u32>,
+    pub loaded_accounts_data_size_limit: Option<u32>,
+    // 旧版RequestUnits指令直接指定的额外费用
+    pub deprecated_additional_fee: Option<u32>,
+    // 非计算预算指令的数量，用于计算默认的计算单元限制
+    pub other_instructions: u32,
+}
+
+impl ComputeBudget {
+    pub fn from_transaction(transaction: &VersionedTransaction) -> Self {
+        let message = &transaction.message;
+        let keys = message.static_account_keys();
+        let mut budget = ComputeBudget::default();
+
+        for instruction in message.instructions() {
+            let is_compute_budget = keys
+                .get(instruction.program_id_index as usize)
+                .is_some_and(|program_id| program_id.to_string() == COMPUTE_BUDGET_PROGRAM_ID);
+            if !is_compute_budget {
+                budget.other_instructions += 1;
+                continue;
+            }
+
+            match ComputeBudgetInstruction::parse(&instruction.data) {
+                ComputeBudgetInstruction::RequestUnitsDeprecated { units, additional_fee } => {
+                    budget.unit_limit = Some(units);
+                    budget.deprecated_additional_fee = Some(additional_fee);
+                }
+                ComputeBudgetInstruction::RequestHeapFrame(bytes) => budget.heap_frame_bytes = Some(bytes),
+                ComputeBudgetInstruction::SetComputeUnitLimit(units) => budget.unit_limit = Some(units),
+                ComputeBudgetInstruction::SetComputeUnitPrice(price) => budget.unit_price_micro_lamports = Some(price),
+                ComputeBudgetInstruction::SetLoadedAccountsDataSizeLimit(bytes) => {
+                    budget.loaded_accounts_data_size_limit = Some(bytes)
+                }
+                ComputeBudgetInstruction::Unknown(_) => {}
+            }
+        }
+
+        budget
+    }
+
+    /// 是否包含任何计算预算指令
+    pub fn is_set(&self) -> bool {
+        self.unit_limit.is_some()
+            || self.unit_price_micro_lamports.is_some()
+            || self.heap_frame_bytes.is_some()
+            || self.loaded_accounts_data_size_limit.is_some()
+    }
+
+    /// 实际生效的计算单元限制，未设置时按每条指令20万计算，不超过140万
+    pub fn effective_unit_limit(&self) -> u32 {
+        self.unit_limit
+            .unwrap_or_else(|| self.other_instructions.saturating_mul(DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT))
+            .min(MAX_COMPUTE_UNIT_LIMIT)
+    }
+
+    /// 优先费(lamports) = ceil(计算单元价格 × 计算单元限制 / 1_000_000)
+    ///
+    /// 优先费按申请的计算单元限制收取，与实际消耗无关。旧版指令的额外费用直接计入。
+    pub fn priority_fee_lamports(&self) -> u64 {
+        if let Some(additional_fee) = self.deprecated_additional_fee {
+            return additional_fee as u64;
+        }
+        let price = self.unit_price_micro_lamports.unwrap_or(0) as u128;
+        let fee = (price * self.effective_unit_limit() as u128).div_ceil(MICRO_LAMPORTS_PER_LAMPORT);
+        fee.min(u64::MAX as u128) as u64
+    }
+}
+
+/// 计算预算程序的解码器
+pub struct ComputeBudgetDecoder;
+
+impl ProgramDecoder for ComputeBudgetDecoder {
+    fn program_id(&self) -> Pubkey {
+        COMPUTE_BUDGET_PROGRAM_ID.parse().expect("合法的计算预算程序ID")
+    }
+
+    fn name(&self) -> &str {
+        "计算预算"
+    }
+
+    fn decode_instruction(&self, transaction: &VersionedTransaction, instruction_index: usize) -> Option<Decoded> {
+        let instruction = transaction.message.instructions().get(instruction_index)?;
+        let parsed = ComputeBudgetInstruction::parse(&instruction.data);
+        Some(Decoded::new(parsed.name(), parsed.to_string()))
+    }
+}
@@ -0,0 +1,323 @@
+use solana_sdk::{
+    pubkey::Pubkey,
+    transaction::VersionedTransaction,
+};
+use std::fmt;
+
+use super::ata_parser::AtaCreate;
+use super::meteora_dlmm_parser::METEORA_DLMM_PROGRAM_ID;
+use super::orca_whirlpool_parser::ORCA_WHIRLPOOL_PROGRAM_ID;
+use super::pump_parser::PUMP_PROGRAM_ID;
+use super::pumpamm_parser::PUMPAMM_PROGRAM_ID;
+use super::raydium_amm_parser::RAYDIUM_AMM_PROGRAM_ID;
+use super::raydium_cpmm_parser::RAYDIUM_CPMM_PROGRAM_ID;
+use super::instruction_account;
+use super::registry::{Decoded, ProgramDecoder};
+
+// Jupiter聚合器v6程序ID
+pub const JUPITER_V6_PROGRAM_ID: &str = "JUP6LkbZbjS1jNKccwhEwYZQLuEdYdekPz4wkVgCiZe";
+
+// Jupiter v6指令discriminator (sha256("global:<指令名>")前8字节)
+pub const ROUTE_IX: [u8; 8] = [229, 23, 203, 151, 122, 227, 173, 42];
+pub const SHARED_ACCOUNTS_ROUTE_IX: [u8; 8] = [193, 32, 155, 51, 65, 214, 156, 129];
+pub const EXACT_OUT_ROUTE_IX: [u8; 8] = [208, 51, 239, 151, 123, 43, 237, 92];
+pub const SHARED_ACCOUNTS_EXACT_OUT_ROUTE_IX: [u8; 8] = [176, 209, 105, 168, 154, 125, 69, 62];
+
+// 参数末尾的定长部分: 数量(u64) + 报价数量(u64) + slippage_bps(u16) + platform_fee_bps(u8)
+const ROUTE_TAIL_LEN: usize = 19;
+
+// 路由指令类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JupiterRouteKind {
+    Route,
+    SharedAccountsRoute,
+    ExactOutRoute,
+    SharedAccountsExactOutRoute,
+}
+
+impl JupiterRouteKind {
+    fn from_data(data: &[u8]) -> Option<Self> {
+        let discriminator = data.get(..8)?;
+        if discriminator == ROUTE_IX {
+            Some(Self::Route)
+        } else if discriminator == SHARED_ACCOUNTS_ROUTE_IX {
+            Some(Self::SharedAccountsRoute)
+        } else if discriminator == EXACT_OUT_ROUTE_IX {
+            Some(Self::ExactOutRoute)
+        } else if discriminator == SHARED_ACCOUNTS_EXACT_OUT_ROUTE_IX {
+            Some(Self::SharedAccountsExactOutRoute)
+        } else {
+            None
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Route => "Route",
+            Self::SharedAccountsRoute => "SharedAccountsRoute",
+            Self::ExactOutRoute => "ExactOutRoute",
+            Self::SharedAccountsExactOutRoute => "SharedAccountsExactOutRoute",
+        }
+    }
+
+    pub fn is_exact_out(&self) -> bool {
+        matches!(self, Self::ExactOutRoute | Self::SharedAccountsExactOutRoute)
+    }
+
+    // shared_accounts系列在route_plan前多一个u8的程序权限ID
+    fn route_plan_offset(&self) -> usize {
+        match self {
+            Self::Route | Self::ExactOutRoute => 8,
+            Self::SharedAccountsRoute | Self::SharedAccountsExactOutRoute => 9,
+        }
+    }
+
+    // 固定账户数，之后是各跳DEX的剩余账户
+    fn fixed_account_count(&self) -> usize {
+        match self {
+            Self::Route => 9,
+            Self::ExactOutRoute => 11,
+            Self::SharedAccountsRoute | Self::SharedAccountsExactOutRoute => 13,
+        }
+    }
+
+    // 用户授权账户的位置
+    fn user_position(&self) -> usize {
+        match self {
+            Self::Route | Self::ExactOutRoute => 1,
+            Self::SharedAccountsRoute | Self::SharedAccountsExactOutRoute => 2,
+        }
+    }
+
+    // (输入Mint, 输出Mint)的位置，route指令没有输入Mint账户
+    fn mint_positions(&self) -> (Option<usize>, usize) {
+        match self {
+            Self::Route => (None, 5),
+            Self::ExactOutRoute => (Some(5), 6),
+            Self::SharedAccountsRoute | Self::SharedAccountsExactOutRoute => (Some(7), 8),
+        }
+    }
+
+    // 用户的源和目标代币账户位置
+    fn user_token_account_positions(&self) -> (usize, usize) {
+        match self {
+            Self::Route | Self::ExactOutRoute => (2, 3),
+            Self::SharedAccountsRoute | Self::SharedAccountsExactOutRoute => (3, 6),
+        }
+    }
+}
+
+// 路由中经过的一跳DEX
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JupiterHop {
+    pub program: Pubkey,
+    pub venue: &'static str,
+    // Pump AMM的剩余账户按其buy/sell指令的账户顺序传入，程序ID之后依次为池、用户、GlobalConfig、基础和报价代币
+    pub pool: Option<Pubkey>,
+    pub base_mint: Option<Pubkey>,
+    pub quote_mint: Option<Pubkey>,
+}
+
+// 解码后的Jupiter路由
+#[derive(Debug, Clone)]
+pub struct JupiterRoute {
+    pub kind: JupiterRouteKind,
+    pub user: Pubkey,
+    // route指令中没有输入Mint，只能从同一笔交易的ATA创建指令推断
+    pub input_mint: Option<Pubkey>,
+    pub output_mint: Option<Pubkey>,
+    // 精确输入时为输入数量，精确输出时为输出数量
+    pub amount: u64,
+    // 精确输入时为报价输出数量，精确输出时为报价输入数量
+    pub quoted_amount: u64,
+    pub slippage_bps: u16,
+    pub platform_fee_bps: u8,
+    // route_plan的步数
+    pub steps: u32,
+    pub hops: Vec<JupiterHop>,
+}
+
+impl JupiterRoute {
+    /// 精确输入时的最小输出，精确输出时的最大输入
+    ///
+    /// 滑点按最多10000bps计，超出的指令数据不会让最小输出变成负数。
+    pub fn limit_amount(&self) -> u64 {
+        let slippage = self.quoted_amount as u128 * self.slippage_bps.min(10_000) as u128 / 10_000;
+        if self.kind.is_exact_out() {
+            (self.quoted_amount as u128 + slippage).min(u64::MAX as u128) as u64
+        } else {
+            (self.quoted_amount as u128).saturating_sub(slippage) as u64
+        }
+    }
+
+    /// 路由是否经过Pump曲线或Pump AMM池
+    pub fn routes_via_pump(&self) -> bool {
+        self.hops.iter().any(|hop| {
+            let program = hop.program.to_string();
+            program == PUMP_PROGRAM_ID || program == PUMPAMM_PROGRAM_ID
+        })
+    }
+
+    /// 路由经过的Pump AMM跳
+    pub fn pumpamm_hops(&self) -> impl Iterator<Item = &JupiterHop> {
+        self.hops.iter().filter(|hop| hop.program.to_string() == PUMPAMM_PROGRAM_ID)
+    }
+}
+
+impl fmt::Display for JupiterRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mint = |mint: Option<Pubkey>| mint.map(|mint| mint.to_string()).unwrap_or_else(|| "未知".to_string());
+        write!(f, "{}: 用户={}, {} -> {}", self.kind.name(), self.user, mint(self.input_mint), mint(self.output_mint))?;
+        if self.kind.is_exact_out() {
+            write!(f, ", 输出数量={}, 报价输入数量={}, 最大输入数量={}", self.amount, self.quoted_amount, self.limit_amount())?;
+        } else {
+            write!(f, ", 输入数量={}, 报价输出数量={}, 最小输出数量={}", self.amount, self.quoted_amount, self.limit_amount())?;
+        }
+        write!(f, ", 滑点={}bps, 平台费={}bps, 步数={}", self.slippage_bps, self.platform_fee_bps, self.steps)?;
+        if !self.hops.is_empty() {
+            let venues: Vec<&str> = self.hops.iter().map(|hop| hop.venue).collect();
+            write!(f, ", 经过: {}", venues.join(" -> "))?;
+        }
+        Ok(())
+    }
+}
+
+// 可识别的路由跳所属DEX
+fn venue_name(program: &Pubkey) -> Option<&'static str> {
+    match program.to_string().as_str() {
+        PUMP_PROGRAM_ID => Some("Pump"),
+        PUMPAMM_PROGRAM_ID => Some("Pump AMM"),
+        RAYDIUM_AMM_PROGRAM_ID => Some("Raydium AMM v4"),
+        RAYDIUM_CPMM_PROGRAM_ID => Some("Raydium CPMM"),
+        METEORA_DLMM_PROGRAM_ID => Some("Meteora DLMM"),
+        ORCA_WHIRLPOOL_PROGRAM_ID => Some("Orca Whirlpool"),
+        _ => None,
+    }
+}
+
+// 在剩余账户中按已知程序ID划分出各跳
+fn find_hops(static_keys: &[Pubkey], accounts: &[u8], start: usize) -> Vec<JupiterHop> {
+    let mut hops = Vec::new();
+    for position in start..accounts.len() {
+        let Some(program) = instruction_account(static_keys, accounts, position) else {
+            continue;
+        };
+        let Some(venue) = venue_name(&program) else {
+            continue;
+        };
+        let is_pumpamm = venue == "Pump AMM";
+        let hop_account = |offset| {
+            if is_pumpamm {
+                instruction_account(static_keys, accounts, position + offset)
+            } else {
+                None
+            }
+        };
+        hops.push(JupiterHop {
+            program,
+            venue,
+            pool: hop_account(1),
+            base_mint: hop_account(4),
+            quote_mint: hop_account(5),
+        });
+    }
+    hops
+}
+
+/// 解码单条Jupiter v6路由指令
+///
+/// route_plan中每一步的Swap枚举长度不定，数量、滑点和平台费从参数末尾的定长部分读取。
+/// 账户顺序: route为 tokenProgram, userTransferAuthority(1), userSource(2), userDestination(3), destination,
+/// destinationMint(5), platformFee, eventAuthority, program；exactOutRoute在destination之后多了sourceMint(5)，
+/// 并在platformFee后多了token2022Program；sharedAccounts系列为 tokenProgram, programAuthority,
+/// userTransferAuthority(2), source(3), programSource, programDestination, destination(6), sourceMint(7),
+/// destinationMint(8), platformFee, token2022Program, eventAuthority, program。之后是各跳DEX的剩余账户。
+/// `ata_creates` 为同一笔交易中的ATA创建指令，用于在缺少Mint账户时推断输入/输出代币。
+pub fn decode_jupiter_instruction(
+    static_keys: &[Pubkey],
+    accounts: &[u8],
+    data: &[u8],
+    ata_creates: &[AtaCreate],
+) -> Option<JupiterRoute> {
+    let kind = JupiterRouteKind::from_data(data)?;
+    let plan_offset = kind.route_plan_offset();
+    if data.len() < plan_offset + 4 + ROUTE_TAIL_LEN {
+        return None;
+    }
+    let steps = u32::from_le_bytes(data[plan_offset..plan_offset + 4].try_into().ok()?);
+    let tail = &data[data.len() - ROUTE_TAIL_LEN..];
+
+    let account = |position| instruction_account(static_keys, accounts, position);
+    let mint_of = |position| {
+        let token_account = account(position)?;
+        ata_creates
+            .iter()
+            .find(|create| create.associated_account == token_account)
+            .map(|create| create.mint)
+    };
+    let (input_mint_position, output_mint_position) = kind.mint_positions();
+    let (source_position, destination_position) = kind.user_token_account_positions();
+
+    Some(JupiterRoute {
+        kind,
+        user: account(kind.user_position())?,
+        input_mint: input_mint_position.and_then(account).or_else(|| mint_of(source_position)),
+        output_mint: account(output_mint_position).or_else(|| mint_of(destination_position)),
+        amount: u64::from_le_bytes(tail[..8].try_into().ok()?),
+        quoted_amount: u64::from_le_bytes(tail[8..16].try_into().ok()?),
+        slippage_bps: u16::from_le_bytes(tail[16..18].try_into().ok()?),
+        platform_fee_bps: tail[18],
+        steps,
+        hops: find_hops(static_keys, accounts, kind.fixed_account_count()),
+    })
+}
+
+/// 解析交易中的所有顶层Jupiter v6路由指令
+pub fn decode_jupiter_routes(transaction: &VersionedTransaction, ata_creates: &[AtaCreate]) -> Vec<JupiterRoute> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+
+    message
+        .instructions()
+        .iter()
+        .filter(|instruction| instruction.program_id(static_keys).to_string() == JUPITER_V6_PROGRAM_ID)
+        .filter_map(|instruction| decode_jupiter_instruction(static_keys, &instruction.accounts, &instruction.data, ata_creates))
+        .collect()
+}
+
+/// Jupiter聚合器v6程序的解码器
+pub struct JupiterDecoder;
+
+impl ProgramDecoder for JupiterDecoder {
+    fn program_id(&self) -> Pubkey {
+        JUPITER_V6_PROGRAM_ID.parse().expect("合法的Jupiter程序ID")
+    }
+
+    fn name(&self) -> &str {
+        "Jupiter v6"
+    }
+
+    fn decode_instruction(&self, transaction: &VersionedTransaction, instruction_index: usize) -> Option<Decoded> {
+        let message = &transaction.message;
+        let static_keys = message.static_account_keys();
+        let instruction = message.instructions().get(instruction_index)?;
+
+        let Some(route) = decode_jupiter_instruction(static_keys, &instruction.accounts, &instruction.data, &[]) else {
+            return Some(Decoded::new(
+                "其他Jupiter指令",
+                match instruction.data.get(..8) {
+                    Some(discriminator) => format!("未识别的discriminator: {:?}", discriminator),
+                    None => "数据长度不足".to_string(),
+                },
+            ));
+        };
+
+        let mut decoded = Decoded::new(route.kind.name(), route.to_string());
+        for hop in &route.hops {
+            if let Some(pool) = hop.pool {
+                decoded = decoded.with_field(format!("{}池", hop.venue), pool);
+            }
+        }
+        Some(decoded)
+    }
+}
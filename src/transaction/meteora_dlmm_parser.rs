@@ -0,0 +1,198 @@
+use solana_sdk::{
+    pubkey::Pubkey,
+    transaction::VersionedTransaction,
+};
+use std::fmt;
+
+use super::ata_parser::AtaCreate;
+use super::instruction_account;
+use super::registry::{Decoded, ProgramDecoder};
+
+// Meteora DLMM程序ID
+pub const METEORA_DLMM_PROGRAM_ID: &str = "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo";
+
+// Meteora DLMM兑换指令discriminator (sha256("global:<指令名>")前8字节)
+pub const SWAP_IX: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+pub const SWAP2_IX: [u8; 8] = [65, 75, 63, 76, 235, 91, 91, 136];
+pub const SWAP_EXACT_OUT_IX: [u8; 8] = [250, 73, 101, 33, 38, 207, 75, 184];
+pub const SWAP_EXACT_OUT2_IX: [u8; 8] = [43, 215, 247, 132, 137, 60, 243, 81];
+
+// 解析后的Meteora DLMM指令
+#[derive(Debug)]
+pub struct ParsedMeteoraDlmmInstruction {
+    pub name: String,
+    pub params: String,
+}
+
+// 从swap系列指令中解出的兑换
+//
+// 指令账户中只有交易对的X/Y两种Mint，方向由用户的输入/输出代币账户决定；
+// 同一笔交易创建了其中一个用户代币账户的ATA时可以确定方向。
+#[derive(Debug, Clone)]
+pub struct DlmmSwap {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub token_x_mint: Pubkey,
+    pub token_y_mint: Pubkey,
+    pub input_mint: Option<Pubkey>,
+    pub output_mint: Option<Pubkey>,
+    // swap/swap2为精确输入，swap_exact_out/swap_exact_out2为精确输出
+    pub exact_in: bool,
+    // exact_in时为输入数量，否则为最大输入
+    pub amount_in: u64,
+    // exact_in时为最小输出，否则为输出数量
+    pub amount_out: u64,
+}
+
+impl fmt::Display for DlmmSwap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "兑换: 池={}, 用户={}", self.pool, self.user)?;
+        match (self.input_mint, self.output_mint) {
+            (Some(input_mint), Some(output_mint)) => write!(f, ", {} -> {}", input_mint, output_mint)?,
+            _ => write!(f, ", 交易对={}/{} (方向未知)", self.token_x_mint, self.token_y_mint)?,
+        }
+        if self.exact_in {
+            write!(f, ", 输入数量={}, 最小输出数量={}", self.amount_in, self.amount_out)
+        } else {
+            write!(f, ", 最大输入数量={}, 输出数量={}", self.amount_in, self.amount_out)
+        }
+    }
+}
+
+// 按discriminator返回指令名和是否为精确输入
+fn swap_kind(data: &[u8]) -> Option<(&'static str, bool)> {
+    let discriminator = data.get(..8)?;
+    if discriminator == SWAP_IX {
+        Some(("Swap", true))
+    } else if discriminator == SWAP2_IX {
+        Some(("Swap2", true))
+    } else if discriminator == SWAP_EXACT_OUT_IX {
+        Some(("SwapExactOut", false))
+    } else if discriminator == SWAP_EXACT_OUT2_IX {
+        Some(("SwapExactOut2", false))
+    } else {
+        None
+    }
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    data.get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().expect("8字节")))
+}
+
+/// 解码单条Meteora DLMM兑换指令
+///
+/// 参数为两个u64: 精确输入时为 amount_in, min_amount_out，精确输出时为 max_in_amount, out_amount。
+/// 账户顺序: lbPair(0), binArrayBitmapExtension, reserveX, reserveY, userTokenIn(4), userTokenOut(5),
+/// tokenXMint(6), tokenYMint(7), oracle, hostFeeIn, user(10), ...。
+/// `ata_creates` 为同一笔交易中的ATA创建指令，用于确定兑换方向。
+pub fn decode_dlmm_instruction(
+    static_keys: &[Pubkey],
+    accounts: &[u8],
+    data: &[u8],
+    ata_creates: &[AtaCreate],
+) -> Option<DlmmSwap> {
+    let (_, exact_in) = swap_kind(data)?;
+    let account = |position| instruction_account(static_keys, accounts, position);
+    let token_x_mint = account(6)?;
+    let token_y_mint = account(7)?;
+    let user_token_in = account(4)?;
+    let user_token_out = account(5)?;
+
+    // 已知任意一侧的Mint即可确定另一侧
+    let other = |mint: Pubkey| if mint == token_x_mint { token_y_mint } else { token_x_mint };
+    let mint_of = |token_account: Pubkey| {
+        ata_creates
+            .iter()
+            .find(|create| create.associated_account == token_account)
+            .map(|create| create.mint)
+            .filter(|mint| *mint == token_x_mint || *mint == token_y_mint)
+    };
+    let (input_mint, output_mint) = match (mint_of(user_token_in), mint_of(user_token_out)) {
+        (Some(input_mint), _) => (Some(input_mint), Some(other(input_mint))),
+        (None, Some(output_mint)) => (Some(other(output_mint)), Some(output_mint)),
+        (None, None) => (None, None),
+    };
+
+    Some(DlmmSwap {
+        pool: account(0)?,
+        user: account(10)?,
+        token_x_mint,
+        token_y_mint,
+        input_mint,
+        output_mint,
+        exact_in,
+        amount_in: read_u64(data, 8)?,
+        amount_out: read_u64(data, 16)?,
+    })
+}
+
+/// 解析单个Meteora DLMM指令
+pub fn parse_dlmm_instruction(transaction: &VersionedTransaction, instruction_index: usize) -> Option<ParsedMeteoraDlmmInstruction> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+    let instruction = message.instructions().get(instruction_index)?;
+    if instruction.program_id(static_keys).to_string() != METEORA_DLMM_PROGRAM_ID {
+        return None;
+    }
+
+    let data = &instruction.data;
+    let parsed = match (swap_kind(data), decode_dlmm_instruction(static_keys, &instruction.accounts, data, &[])) {
+        (Some((name, _)), Some(swap)) => ParsedMeteoraDlmmInstruction {
+            name: name.to_string(),
+            params: swap.to_string(),
+        },
+        (Some((name, _)), None) => ParsedMeteoraDlmmInstruction {
+            name: name.to_string(),
+            params: "兑换 (无法解析参数或账户)".to_string(),
+        },
+        (None, _) => ParsedMeteoraDlmmInstruction {
+            name: "其他Meteora DLMM指令".to_string(),
+            params: match data.get(..8) {
+                Some(discriminator) => format!("未识别的discriminator: {:?}", discriminator),
+                None => "数据长度不足".to_string(),
+            },
+        },
+    };
+    Some(parsed)
+}
+
+/// 解析交易中的所有顶层Meteora DLMM兑换指令
+pub fn decode_dlmm_swaps(transaction: &VersionedTransaction, ata_creates: &[AtaCreate]) -> Vec<DlmmSwap> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+
+    message
+        .instructions()
+        .iter()
+        .filter(|instruction| instruction.program_id(static_keys).to_string() == METEORA_DLMM_PROGRAM_ID)
+        .filter_map(|instruction| decode_dlmm_instruction(static_keys, &instruction.accounts, &instruction.data, ata_creates))
+        .collect()
+}
+
+/// Meteora DLMM程序的解码器
+pub struct MeteoraDlmmDecoder;
+
+impl ProgramDecoder for MeteoraDlmmDecoder {
+    fn program_id(&self) -> Pubkey {
+        METEORA_DLMM_PROGRAM_ID.parse().expect("合法的Meteora DLMM程序ID")
+    }
+
+    fn name(&self) -> &str {
+        "Meteora DLMM"
+    }
+
+    fn decode_instruction(&self, transaction: &VersionedTransaction, instruction_index: usize) -> Option<Decoded> {
+        let parsed = parse_dlmm_instruction(transaction, instruction_index)?;
+        let mut decoded = Decoded::new(parsed.name, parsed.params);
+        let message = &transaction.message;
+        let instruction = message.instructions().get(instruction_index)?;
+        if swap_kind(&instruction.data).is_some() {
+            let static_keys = message.static_account_keys();
+            if let Some(pool) = instruction_account(static_keys, &instruction.accounts, 0) {
+                decoded = decoded.with_field("池地址", pool);
+            }
+        }
+        Some(decoded)
+    }
+}
@@ -1,67 +1,203 @@
 use solana_sdk::{
     pubkey::Pubkey,
+    signature::Signature,
     transaction::VersionedTransaction,
     message::VersionedMessage,
 };
 use std::collections::HashMap;
+use std::fmt::{self, Write};
 
 pub mod pump_parser;
-use pump_parser::{parse_pump_transaction, get_mint_from_transaction, get_bonding_curve_info, PUMP_PROGRAM_ID};
+use pump_parser::{parse_pump_transaction, get_bonding_curve_info};
 // 添加Pump AMM协议支持
 pub mod pumpamm_parser;
-use pumpamm_parser::PUMPAMM_PROGRAM_ID;
+pub mod compute_budget;
+use compute_budget::ComputeBudget;
+pub mod tip;
+use tip::JitoTip;
+pub mod system_parser;
+pub mod token_parser;
+pub mod ata_parser;
+pub mod raydium_amm_parser;
+pub mod raydium_cpmm_parser;
+pub mod jupiter_parser;
+pub mod meteora_dlmm_parser;
+pub mod orca_whirlpool_parser;
+pub mod swap;
+pub mod registry;
+pub mod pda;
+use registry::DecoderRegistry;
+use crate::latency::EntryLatency;
 
-pub fn print_transaction_info(transaction: &VersionedTransaction) {
-    println!("\n交易详情:");
-    println!("签名: {}", transaction.signatures[0]);
-    println!("消息版本: {:?}", transaction.message);
+// 按指令内的位置取账户，地址查找表中的账户无法解析
+pub(crate) fn instruction_account(static_keys: &[Pubkey], accounts: &[u8], position: usize) -> Option<Pubkey> {
+    let index = *accounts.get(position)? as usize;
+    static_keys.get(index).copied()
+}
+
+// 解码后的交易事件，在worker中并行生成，按接收顺序交给状态引擎
+#[derive(Debug, Clone)]
+pub struct DecodedTransaction {
+    pub signature: Signature,
+    pub slot: u64,
+    // 是否命中目标账户(命中的交易才会输出详情)
+    pub matched: bool,
+    pub pump_actions: Vec<pump_parser::PumpAction>,
+    pub pumpamm_actions: Vec<pumpamm_parser::PumpAmmAction>,
+    pub raydium_amm_actions: Vec<raydium_amm_parser::RaydiumAmmAction>,
+    pub raydium_cpmm_actions: Vec<raydium_cpmm_parser::RaydiumCpmmAction>,
+    pub dlmm_swaps: Vec<meteora_dlmm_parser::DlmmSwap>,
+    pub whirlpool_swaps: Vec<orca_whirlpool_parser::WhirlpoolSwap>,
+    // 顶层为Jupiter、经CPI调用各DEX的路由交易
+    pub jupiter_routes: Vec<jupiter_parser::JupiterRoute>,
+    pub compute_budget: ComputeBudget,
+    // 转给Jito小费账户的SOL
+    pub tips: Vec<JitoTip>,
+    // Pump交易前后的资金和代币流动
+    pub system_actions: Vec<system_parser::SystemAction>,
+    pub token_actions: Vec<token_parser::TokenAction>,
+    pub ata_actions: Vec<ata_parser::AtaCreate>,
+    // 注册表中自定义解码器提取的兑换意图，内置程序的兑换在上面的各字段中
+    pub registry_swaps: Vec<swap::SwapIntent>,
+    // 所在Entry批次的接收延迟，回放录制数据时为None
+    pub latency: Option<EntryLatency>,
+}
+
+impl DecodedTransaction {
+    pub fn decode(
+        transaction: &VersionedTransaction,
+        slot: u64,
+        matched: bool,
+        tip_accounts: &[Pubkey],
+        registry: &DecoderRegistry,
+    ) -> Self {
+        let ata_actions = ata_parser::decode_ata_actions(transaction);
+        Self {
+            signature: transaction.signatures[0],
+            slot,
+            matched,
+            pump_actions: pump_parser::decode_pump_actions(transaction),
+            pumpamm_actions: pumpamm_parser::decode_pumpamm_actions(transaction),
+            raydium_amm_actions: raydium_amm_parser::decode_raydium_amm_actions(transaction, &ata_actions),
+            raydium_cpmm_actions: raydium_cpmm_parser::decode_raydium_cpmm_actions(transaction),
+            dlmm_swaps: meteora_dlmm_parser::decode_dlmm_swaps(transaction, &ata_actions),
+            whirlpool_swaps: orca_whirlpool_parser::decode_whirlpool_swaps(transaction, &ata_actions),
+            jupiter_routes: jupiter_parser::decode_jupiter_routes(transaction, &ata_actions),
+            compute_budget: ComputeBudget::from_transaction(transaction),
+            tips: tip::find_tips(transaction, tip_accounts),
+            system_actions: system_parser::decode_system_actions(transaction),
+            token_actions: token_parser::decode_token_actions(transaction),
+            ata_actions,
+            registry_swaps: registry.decode_swaps(transaction),
+            latency: None,
+        }
+    }
+
+    /// 附上所在Entry批次的接收延迟，兑换意图、钱包提醒和跟单信号都会带上它
+    pub fn with_latency(mut self, latency: EntryLatency) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// 是否包含需要应用到状态引擎的操作(含按slot统计的小费、关注钱包的Jupiter兑换和自定义解码器的兑换)
+    pub fn has_actions(&self) -> bool {
+        !self.pump_actions.is_empty()
+            || !self.registry_swaps.is_empty()
+            || !self.pumpamm_actions.is_empty()
+            || self.has_dex_actions()
+            || !self.jupiter_routes.is_empty()
+            || !self.tips.is_empty()
+    }
+
+    /// 是否包含经Jupiter路由到Pump曲线或Pump AMM池的兑换
+    pub fn has_pump_routes(&self) -> bool {
+        self.jupiter_routes.iter().any(|route| route.routes_via_pump())
+    }
+
+    /// 是否包含Raydium、Meteora DLMM或Orca Whirlpool操作
+    pub fn has_dex_actions(&self) -> bool {
+        !self.raydium_amm_actions.is_empty()
+            || !self.raydium_cpmm_actions.is_empty()
+            || !self.dlmm_swaps.is_empty()
+            || !self.whirlpool_swaps.is_empty()
+    }
+
+    /// 输出交易中的SOL转账、创建账户、代币转账/铸造/销毁/关闭和ATA创建
+    pub fn write_flows(&self, out: &mut impl Write) -> fmt::Result {
+        if self.system_actions.is_empty() && self.token_actions.is_empty() && self.ata_actions.is_empty() {
+            return Ok(());
+        }
+        writeln!(out, "  资金和代币流动:")?;
+        for action in &self.system_actions {
+            writeln!(out, "    - {}", action)?;
+        }
+        for create in &self.ata_actions {
+            writeln!(out, "    - {}", create)?;
+        }
+        for action in &self.token_actions {
+            writeln!(out, "    - {}", action)?;
+        }
+        Ok(())
+    }
+
+    /// 这笔交易的小费总额(lamports)
+    pub fn tip_lamports(&self) -> u64 {
+        self.tips.iter().map(|tip| tip.lamports).sum()
+    }
+}
+
+pub fn write_transaction_info(out: &mut impl Write, transaction: &VersionedTransaction, registry: &DecoderRegistry) -> fmt::Result {
+    writeln!(out, "\n交易详情:")?;
+    writeln!(out, "签名: {}", transaction.signatures[0])?;
+    writeln!(out, "消息版本: {:?}", transaction.message)?;
     
     let static_keys = transaction.message.static_account_keys();
     
-    // 打印Pump相关的特殊账户
-    println!("\nPump特殊账户:");
+    // 尝试获取Mint地址
+    let mint = pump_parser::get_mint_from_transaction(transaction);
+
+    // 打印按PDA推导识别出的Pump相关账户
+    writeln!(out, "\nPump特殊账户:")?;
     for (i, key) in static_keys.iter().enumerate() {
-        let key_str = key.to_string();
-        if key_str.ends_with("pump") {
-            println!("  {}. {} (可能的Pump账户)", i, key);
+        if let Some(label) = pda::pump_account_label(key, mint.as_ref()) {
+            writeln!(out, "  {}. {} ({})", i, key, label)?;
         }
     }
     
     // 打印签名账户
-    println!("\n签名账户: {}", static_keys[0]);
+    writeln!(out, "\n签名账户: {}", static_keys[0])?;
     
-    // 尝试获取Mint地址
-    if let Some(mint) = pump_parser::get_mint_from_transaction(transaction) {
-        println!("\n识别的代币Mint: {}", mint);
+    if let Some(mint) = mint {
+        writeln!(out, "\n识别的代币Mint: {}", mint)?;
     }
     
     // 尝试获取BondingCurve信息
     if let Some(curve_info) = pump_parser::get_bonding_curve_info(transaction) {
-        println!("识别的曲线账户: {}", curve_info.curve_account);
-        println!("曲线状态: {}", if curve_info.is_complete { "已完成" } else { "进行中" });
+        writeln!(out, "识别的曲线账户: {}", curve_info.curve_account)?;
+        writeln!(out, "曲线状态: {}", if curve_info.is_complete { "已完成" } else { "进行中" })?;
     }
     
     match &transaction.message {
         VersionedMessage::Legacy(msg) => {
-            println!("账户数量: {}", msg.account_keys.len());
-            println!("指令数量: {}", msg.instructions.len());
+            writeln!(out, "账户数量: {}", msg.account_keys.len())?;
+            writeln!(out, "指令数量: {}", msg.instructions.len())?;
             
             for (i, instruction) in msg.instructions.iter().enumerate() {
-                println!("指令 {}:", i);
-                println!("  程序ID索引: {}", instruction.program_id_index);
-                println!("  账户索引: {:?}", instruction.accounts);
-                println!("  数据长度: {}", instruction.data.len());
+                writeln!(out, "指令 {}:", i)?;
+                writeln!(out, "  程序ID索引: {}", instruction.program_id_index)?;
+                writeln!(out, "  账户索引: {:?}", instruction.accounts)?;
+                writeln!(out, "  数据长度: {}", instruction.data.len())?;
             }
         }
         VersionedMessage::V0(msg) => {
-            println!("账户数量: {}", msg.account_keys.len());
-            println!("指令数量: {}", msg.instructions.len());
+            writeln!(out, "账户数量: {}", msg.account_keys.len())?;
+            writeln!(out, "指令数量: {}", msg.instructions.len())?;
             
             for (i, instruction) in msg.instructions.iter().enumerate() {
-                println!("指令 {}:", i);
-                println!("  程序ID索引: {}", instruction.program_id_index);
-                println!("  账户索引: {:?}", instruction.accounts);
-                println!("  数据长度: {}", instruction.data.len());
+                writeln!(out, "指令 {}:", i)?;
+                writeln!(out, "  程序ID索引: {}", instruction.program_id_index)?;
+                writeln!(out, "  账户索引: {:?}", instruction.accounts)?;
+                writeln!(out, "  数据长度: {}", instruction.data.len())?;
             }
         }
     }
@@ -73,87 +209,54 @@ pub fn print_transaction_info(transaction: &VersionedTransaction) {
     } else { 
         format!("多签名交易 ({}个签名)", num_signatures) 
     };
-    println!("交易类型: {}", tx_type);
+    writeln!(out, "交易类型: {}", tx_type)?;
+
+    let budget = ComputeBudget::from_transaction(transaction);
+    writeln!(
+        out,
+        "计算预算: 单元限制={}{}, 单元价格={} micro-lamports, 优先费={}",
+        budget.effective_unit_limit(),
+        if budget.unit_limit.is_some() { "" } else { " (默认)" },
+        budget.unit_price_micro_lamports.unwrap_or(0),
+        pump_parser::lamports_to_sol_string(budget.priority_fee_lamports())
+    )?;
     
-    println!("\n指令详情:");
+    writeln!(out, "\n指令详情:")?;
     for (i, instruction) in message.instructions().iter().enumerate() {
-        let program_id = instruction.program_id(&message.static_account_keys());
+        let program_id = instruction.program_id(message.static_account_keys());
         let accounts = &instruction.accounts;
         
-        println!("  指令 {}:", i);
-        println!("    程序: {}", program_id);
+        writeln!(out, "  指令 {}:", i)?;
+        writeln!(out, "    程序: {}", program_id)?;
         
-        match program_id.to_string().as_str() {
-            "ComputeBudget111111111111111111111111111111" => {
-                println!("    类型: 计算预算指令");
-                if instruction.data.len() > 0 {
-                    match instruction.data[0] {
-                        0 => println!("    操作: 设置计算单元限制"),
-                        1 => println!("    操作: 设置优先级费用"),
-                        2 => println!("    操作: 设置计算单元价格"),
-                        3 => println!("    操作: 设置堆内存"),
-                        _ => println!("    操作: 未知计算预算操作"),
-                    }
-                }
-            },
-            "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL" => {
-                println!("    类型: Associated Token 指令");
-            },
-            pump_id if pump_id == PUMP_PROGRAM_ID => {
-                println!("    类型: Pump协议指令");
-                
-                // 尝试解析pump指令
-                if let Some(parsed) = pump_parser::parse_pump_instruction(transaction, i) {
-                    println!("    操作: {}", parsed.name);
-                    println!("    内容: {}", parsed.params);
-                    
-                    // 尝试获取Mint地址
-                    if let Some(mint) = get_mint_from_transaction(transaction) {
-                        println!("    代币Mint: {}", mint);
-                    }
-                    
-                    // 尝试获取BondingCurve信息
-                    if let Some(curve_info) = get_bonding_curve_info(transaction) {
-                        println!("    曲线账户: {}", curve_info.curve_account);
-                        if curve_info.is_complete {
-                            println!("    曲线状态: 已完成");
-                        }
-                    }
-                }
-            },
-            // 添加对Pump AMM协议的支持
-            pumpamm_id if pumpamm_id == PUMPAMM_PROGRAM_ID => {
-                println!("    类型: Pump AMM协议指令");
-                
-                // 尝试解析Pump AMM指令
-                if let Some(parsed) = pumpamm_parser::parse_pumpamm_instruction(transaction, i) {
-                    println!("    操作: {}", parsed.name);
-                    println!("    内容: {}", parsed.params);
-                    
-                    // 打印相关池信息（如果有）
-                    if let Some(pool) = pumpamm_parser::get_pool_from_instruction(transaction, i) {
-                        println!("    池地址: {}", pool);
-                    }
-                    
-                    // 打印基础代币和报价代币信息（如果有）
-                    if let Some((base_mint, quote_mint)) = pumpamm_parser::get_token_mints_from_instruction(transaction, i) {
-                        println!("    基础代币: {}", base_mint);
-                        println!("    报价代币: {}", quote_mint);
+        // 按程序ID分派到注册的解码器
+        match registry.get(program_id) {
+            Some(decoder) => {
+                writeln!(out, "    类型: {}指令", decoder.name())?;
+                // emit_cpi!自调用的事件指令没有对应的指令解码，按事件数据再试一次
+                let decoded = decoder
+                    .decode_instruction(transaction, i)
+                    .or_else(|| decoder.decode_event(&instruction.data));
+                if let Some(decoded) = decoded {
+                    writeln!(out, "    操作: {}", decoded.name)?;
+                    writeln!(out, "    内容: {}", decoded.params)?;
+                    for (label, value) in &decoded.fields {
+                        writeln!(out, "    {}: {}", label, value)?;
                     }
                 }
             },
-            _ => {
-                println!("    类型: 其他程序指令");
+            None => {
+                writeln!(out, "    类型: 其他程序指令")?;
             }
         }
         
-        println!("    相关账户:");
+        writeln!(out, "    相关账户:")?;
         for account_index in accounts {
             let index = *account_index as usize;
             if index < static_keys.len() {
-                println!("      - {}", static_keys[index]);
+                writeln!(out, "      - {}", static_keys[index])?;
             } else {
-                println!("      - 无效账户索引: {}", index);
+                writeln!(out, "      - 无效账户索引: {}", index)?;
             }
         }
     }
@@ -161,70 +264,74 @@ pub fn print_transaction_info(transaction: &VersionedTransaction) {
     // 添加Pump指令的特殊解析
     let parsed_pump = parse_pump_transaction(transaction);
     if !parsed_pump.is_empty() {
-        println!("\nPump协议交易解析:");
+        writeln!(out, "\nPump协议交易解析:")?;
         for (i, instruction) in parsed_pump.iter().enumerate() {
-            println!("  Pump指令 {}:", i + 1);
-            println!("    类型: {}", instruction.name);
-            println!("    详情: {}", instruction.params);
+            writeln!(out, "  Pump指令 {}:", i + 1)?;
+            writeln!(out, "    类型: {}", instruction.name)?;
+            writeln!(out, "    详情: {}", instruction.params)?;
         }
         
         // 添加曲线信息 
         if let Some(curve_info) = get_bonding_curve_info(transaction) {
-            println!("\n曲线信息:");
-            println!("  代币Mint: {}", curve_info.mint);
-            println!("  曲线账户: {}", curve_info.curve_account);
-            println!("  状态: {}", if curve_info.is_complete { "已完成" } else { "进行中" });
+            writeln!(out, "\n曲线信息:")?;
+            writeln!(out, "  代币Mint: {}", curve_info.mint)?;
+            writeln!(out, "  曲线账户: {}", curve_info.curve_account)?;
+            writeln!(out, "  状态: {}", if curve_info.is_complete { "已完成" } else { "进行中" })?;
         }
     }
     
     // 添加Pump AMM协议交易解析
     let parsed_pumpamm = pumpamm_parser::parse_pumpamm_transaction(transaction);
     if !parsed_pumpamm.is_empty() {
-        println!("\nPump AMM协议交易解析:");
+        writeln!(out, "\nPump AMM协议交易解析:")?;
         for (i, instruction) in parsed_pumpamm.iter().enumerate() {
-            println!("  Pump AMM指令 {}:", i + 1);
-            println!("    类型: {}", instruction.name);
-            println!("    详情: {}", instruction.params);
+            writeln!(out, "  Pump AMM指令 {}:", i + 1)?;
+            writeln!(out, "    类型: {}", instruction.name)?;
+            writeln!(out, "    详情: {}", instruction.params)?;
         }
         
         // 添加池信息
         if let Some(pool_info) = pumpamm_parser::get_pool_info_from_transaction(transaction) {
-            println!("\n池信息:");
-            println!("  池地址: {}", pool_info.pool);
-            println!("  基础代币: {}", pool_info.base_mint);
-            println!("  报价代币: {}", pool_info.quote_mint);
-            if let Some(lp_mint) = pool_info.lp_mint {
-                println!("  LP代币: {}", lp_mint);
-            }
+            writeln!(out, "\n池信息:")?;
+            writeln!(out, "  池地址: {}", pool_info.pool)?;
+            writeln!(out, "  基础代币: {}", pool_info.base_mint)?;
+            writeln!(out, "  报价代币: {}", pool_info.quote_mint)?;
+            writeln!(out, "  LP代币: {}", pool_info.lp_mint)?;
         }
     }
 
-    println!("\n{}", "-".repeat(80));
+    writeln!(out, "\n{}", "-".repeat(80))
 }
 
 pub fn group_transactions_by_accounts<'a>(
     entries: &'a [solana_entry::entry::Entry],
     target_accounts: &'a [Pubkey]
+) -> HashMap<Pubkey, Vec<&'a VersionedTransaction>> {
+    group_by_accounts(entries.iter().flat_map(|entry| &entry.transactions), target_accounts)
+}
+
+/// 按目标账户对任意交易序列分组
+pub fn group_by_accounts<'a>(
+    transactions: impl IntoIterator<Item = &'a VersionedTransaction>,
+    target_accounts: &[Pubkey]
 ) -> HashMap<Pubkey, Vec<&'a VersionedTransaction>> {
     let mut transactions_by_account = HashMap::new();
     
-    for entry in entries {
-        for transaction in &entry.transactions {
-            let accounts: Vec<&Pubkey> = match &transaction.message {
-                VersionedMessage::Legacy(msg) => msg.account_keys.iter().collect(),
-                VersionedMessage::V0(msg) => msg.account_keys.iter().collect(),
-            };
-            
-            for target_account in target_accounts {
-                if accounts.contains(&target_account) {
-                    transactions_by_account
-                        .entry(*target_account)
-                        .or_insert_with(Vec::new)
-                        .push(transaction);
-                }
+    for transaction in transactions {
+        let accounts: Vec<&Pubkey> = match &transaction.message {
+            VersionedMessage::Legacy(msg) => msg.account_keys.iter().collect(),
+            VersionedMessage::V0(msg) => msg.account_keys.iter().collect(),
+        };
+        
+        for target_account in target_accounts {
+            if accounts.contains(&target_account) {
+                transactions_by_account
+                    .entry(*target_account)
+                    .or_insert_with(Vec::new)
+                    .push(transaction);
             }
         }
     }
     
     transactions_by_account
-} 
\ No newline at end of file
+}
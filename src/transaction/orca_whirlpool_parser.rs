@@ -0,0 +1,193 @@
+use solana_sdk::{
+    pubkey::Pubkey,
+    transaction::VersionedTransaction,
+};
+use std::fmt;
+
+use super::ata_parser::AtaCreate;
+use super::instruction_account;
+use super::registry::{Decoded, ProgramDecoder};
+
+// Orca Whirlpool程序ID
+pub const ORCA_WHIRLPOOL_PROGRAM_ID: &str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+
+// Orca Whirlpool兑换指令discriminator (sha256("global:<指令名>")前8字节)
+pub const SWAP_IX: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+pub const SWAP_V2_IX: [u8; 8] = [43, 4, 237, 11, 26, 201, 30, 98];
+
+// 参数: amount(u64) + other_amount_threshold(u64) + sqrt_price_limit(u128) + amount_specified_is_input(bool) + a_to_b(bool)
+const SWAP_ARGS_LEN: usize = 8 + 8 + 16 + 1 + 1;
+
+// 解析后的Orca Whirlpool指令
+#[derive(Debug)]
+pub struct ParsedWhirlpoolInstruction {
+    pub name: String,
+    pub params: String,
+}
+
+// 从swap/swap_v2指令中解出的兑换
+//
+// swap_v2的账户中带两侧Mint；旧版swap只有用户代币账户，Mint只能从同一笔交易的ATA创建推断。
+#[derive(Debug, Clone)]
+pub struct WhirlpoolSwap {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub input_mint: Option<Pubkey>,
+    pub output_mint: Option<Pubkey>,
+    pub a_to_b: bool,
+    // amount_specified_is_input为真时是精确输入
+    pub exact_in: bool,
+    // exact_in时为输入数量，否则为最大输入
+    pub amount_in: u64,
+    // exact_in时为最小输出，否则为输出数量
+    pub amount_out: u64,
+}
+
+impl fmt::Display for WhirlpoolSwap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "兑换: 池={}, 用户={}, 方向={}", self.pool, self.user, if self.a_to_b { "A->B" } else { "B->A" })?;
+        if let (Some(input_mint), Some(output_mint)) = (self.input_mint, self.output_mint) {
+            write!(f, ", {} -> {}", input_mint, output_mint)?;
+        }
+        if self.exact_in {
+            write!(f, ", 输入数量={}, 最小输出数量={}", self.amount_in, self.amount_out)
+        } else {
+            write!(f, ", 最大输入数量={}, 输出数量={}", self.amount_in, self.amount_out)
+        }
+    }
+}
+
+// 按discriminator返回指令名和是否为swap_v2
+fn swap_kind(data: &[u8]) -> Option<(&'static str, bool)> {
+    let discriminator = data.get(..8)?;
+    if discriminator == SWAP_IX {
+        Some(("Swap", false))
+    } else if discriminator == SWAP_V2_IX {
+        Some(("SwapV2", true))
+    } else {
+        None
+    }
+}
+
+// 池账户在swap中为第3个，在swap_v2中为第5个
+fn pool_position(is_v2: bool) -> usize {
+    if is_v2 { 4 } else { 2 }
+}
+
+/// 解码单条Orca Whirlpool兑换指令
+///
+/// 账户顺序: swap为 tokenProgram, tokenAuthority(1), whirlpool(2), tokenOwnerAccountA(3), tokenVaultA,
+/// tokenOwnerAccountB(5), tokenVaultB, tickArray0-2, oracle；swap_v2为 tokenProgramA, tokenProgramB, memoProgram,
+/// tokenAuthority(3), whirlpool(4), tokenMintA(5), tokenMintB(6), tokenOwnerAccountA, tokenVaultA,
+/// tokenOwnerAccountB, tokenVaultB, tickArray0-2, oracle。
+/// `ata_creates` 为同一笔交易中的ATA创建指令，用于推断旧版swap的两侧Mint。
+pub fn decode_whirlpool_instruction(
+    static_keys: &[Pubkey],
+    accounts: &[u8],
+    data: &[u8],
+    ata_creates: &[AtaCreate],
+) -> Option<WhirlpoolSwap> {
+    let (_, is_v2) = swap_kind(data)?;
+    let args = data.get(8..8 + SWAP_ARGS_LEN)?;
+    let amount = u64::from_le_bytes(args[..8].try_into().ok()?);
+    let other_amount_threshold = u64::from_le_bytes(args[8..16].try_into().ok()?);
+    let exact_in = args[32] != 0;
+    let a_to_b = args[33] != 0;
+
+    let account = |position| instruction_account(static_keys, accounts, position);
+    let (mint_a, mint_b) = if is_v2 {
+        (account(5), account(6))
+    } else {
+        let mint_of = |position| {
+            let token_account = account(position)?;
+            ata_creates
+                .iter()
+                .find(|create| create.associated_account == token_account)
+                .map(|create| create.mint)
+        };
+        (mint_of(3), mint_of(5))
+    };
+    let (input_mint, output_mint) = if a_to_b { (mint_a, mint_b) } else { (mint_b, mint_a) };
+    let (amount_in, amount_out) = if exact_in { (amount, other_amount_threshold) } else { (other_amount_threshold, amount) };
+
+    Some(WhirlpoolSwap {
+        pool: account(pool_position(is_v2))?,
+        user: account(if is_v2 { 3 } else { 1 })?,
+        input_mint,
+        output_mint,
+        a_to_b,
+        exact_in,
+        amount_in,
+        amount_out,
+    })
+}
+
+/// 解析单个Orca Whirlpool指令
+pub fn parse_whirlpool_instruction(transaction: &VersionedTransaction, instruction_index: usize) -> Option<ParsedWhirlpoolInstruction> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+    let instruction = message.instructions().get(instruction_index)?;
+    if instruction.program_id(static_keys).to_string() != ORCA_WHIRLPOOL_PROGRAM_ID {
+        return None;
+    }
+
+    let data = &instruction.data;
+    let parsed = match (swap_kind(data), decode_whirlpool_instruction(static_keys, &instruction.accounts, data, &[])) {
+        (Some((name, _)), Some(swap)) => ParsedWhirlpoolInstruction {
+            name: name.to_string(),
+            params: swap.to_string(),
+        },
+        (Some((name, _)), None) => ParsedWhirlpoolInstruction {
+            name: name.to_string(),
+            params: "兑换 (无法解析参数或账户)".to_string(),
+        },
+        (None, _) => ParsedWhirlpoolInstruction {
+            name: "其他Whirlpool指令".to_string(),
+            params: match data.get(..8) {
+                Some(discriminator) => format!("未识别的discriminator: {:?}", discriminator),
+                None => "数据长度不足".to_string(),
+            },
+        },
+    };
+    Some(parsed)
+}
+
+/// 解析交易中的所有顶层Orca Whirlpool兑换指令
+pub fn decode_whirlpool_swaps(transaction: &VersionedTransaction, ata_creates: &[AtaCreate]) -> Vec<WhirlpoolSwap> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+
+    message
+        .instructions()
+        .iter()
+        .filter(|instruction| instruction.program_id(static_keys).to_string() == ORCA_WHIRLPOOL_PROGRAM_ID)
+        .filter_map(|instruction| decode_whirlpool_instruction(static_keys, &instruction.accounts, &instruction.data, ata_creates))
+        .collect()
+}
+
+/// Orca Whirlpool程序的解码器
+pub struct WhirlpoolDecoder;
+
+impl ProgramDecoder for WhirlpoolDecoder {
+    fn program_id(&self) -> Pubkey {
+        ORCA_WHIRLPOOL_PROGRAM_ID.parse().expect("合法的Orca Whirlpool程序ID")
+    }
+
+    fn name(&self) -> &str {
+        "Orca Whirlpool"
+    }
+
+    fn decode_instruction(&self, transaction: &VersionedTransaction, instruction_index: usize) -> Option<Decoded> {
+        let parsed = parse_whirlpool_instruction(transaction, instruction_index)?;
+        let mut decoded = Decoded::new(parsed.name, parsed.params);
+        let message = &transaction.message;
+        let instruction = message.instructions().get(instruction_index)?;
+        if let Some((_, is_v2)) = swap_kind(&instruction.data) {
+            let static_keys = message.static_account_keys();
+            if let Some(pool) = instruction_account(static_keys, &instruction.accounts, pool_position(is_v2)) {
+                decoded = decoded.with_field("池地址", pool);
+            }
+        }
+        Some(decoded)
+    }
+}
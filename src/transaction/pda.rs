@@ -0,0 +1,253 @@
+use solana_sdk::{pubkey::Pubkey, system_program};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+
+use super::ata_parser::ATA_PROGRAM_ID;
+use super::pump_parser::PUMP_PROGRAM_ID;
+use super::pumpamm_parser::PUMPAMM_PROGRAM_ID;
+use super::token_parser::TOKEN_PROGRAM_ID;
+
+// PDA种子，与程序IDL中的常量一致
+pub const GLOBAL_SEED: &[u8] = b"global";
+pub const BONDING_CURVE_SEED: &[u8] = b"bonding-curve";
+pub const MINT_AUTHORITY_SEED: &[u8] = b"mint-authority";
+pub const POOL_SEED: &[u8] = b"pool";
+pub const GLOBAL_CONFIG_SEED: &[u8] = b"global_config";
+pub const LP_MINT_SEED: &[u8] = b"pool_lp_mint";
+// creator fee更新后曲线和池各自的创建者手续费金库，两个程序的种子拼写不同
+pub const CREATOR_VAULT_SEED: &[u8] = b"creator-vault";
+pub const COIN_CREATOR_VAULT_SEED: &[u8] = b"creator_vault";
+// emit_cpi!的事件签名账户，每个Anchor程序各有一个
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+// 交易量累计账户，Pump和Pump AMM的买入都会更新全局和用户两个累计账户
+pub const GLOBAL_VOLUME_ACCUMULATOR_SEED: &[u8] = b"global_volume_accumulator";
+pub const USER_VOLUME_ACCUMULATOR_SEED: &[u8] = b"user_volume_accumulator";
+// 手续费程序中按程序ID区分的费率配置
+pub const FEE_CONFIG_SEED: &[u8] = b"fee_config";
+
+// Pump和Pump AMM共用的手续费程序，买卖指令都把它和对应的fee_config放在账户列表最后
+pub const PUMP_FEE_PROGRAM_ID: &str = "pfeeUxB6jkeY1Hxd7CsFCAjcbHA9rWtchMGdZ6VojVZ";
+
+// 每个线程缓存的mint → 曲线账户数量，超过后清空重新缓存
+const CURVE_CACHE_CAPACITY: usize = 65_536;
+
+thread_local! {
+    // find_program_address最多要尝试多次哈希，同一mint的曲线账户在worker线程内只推导一次
+    static CURVE_CACHE: RefCell<HashMap<Pubkey, Pubkey>> = RefCell::new(HashMap::new());
+}
+
+pub fn pump_program_id() -> Pubkey {
+    PUMP_PROGRAM_ID.parse().expect("合法的Pump程序ID")
+}
+
+pub fn pumpamm_program_id() -> Pubkey {
+    PUMPAMM_PROGRAM_ID.parse().expect("合法的Pump AMM程序ID")
+}
+
+pub fn pump_fee_program_id() -> Pubkey {
+    PUMP_FEE_PROGRAM_ID.parse().expect("合法的手续费程序ID")
+}
+
+/// 钱包在某个mint下的关联代币账户
+pub fn associated_token_address(wallet: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    let ata_program: Pubkey = ATA_PROGRAM_ID.parse().expect("合法的ATA程序ID");
+    Pubkey::find_program_address(&[wallet.as_ref(), token_program.as_ref(), mint.as_ref()], &ata_program).0
+}
+
+/// Pump的 `Global` 账户
+pub fn pump_global() -> Pubkey {
+    Pubkey::find_program_address(&[GLOBAL_SEED], &pump_program_id()).0
+}
+
+/// mint对应的Pump曲线账户
+pub fn bonding_curve(mint: &Pubkey) -> Pubkey {
+    CURVE_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if let Some(curve) = cache.get(mint) {
+            return *curve;
+        }
+        if cache.len() >= CURVE_CACHE_CAPACITY {
+            cache.clear();
+        }
+        let curve = Pubkey::find_program_address(&[BONDING_CURVE_SEED, mint.as_ref()], &pump_program_id()).0;
+        cache.insert(*mint, curve);
+        curve
+    })
+}
+
+/// 曲线持有代币的关联代币账户
+pub fn associated_bonding_curve(mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    associated_token_address(&bonding_curve(mint), mint, token_program)
+}
+
+/// 曲线创建者收取手续费的SOL金库
+pub fn pump_creator_vault(creator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[CREATOR_VAULT_SEED, creator.as_ref()], &pump_program_id()).0
+}
+
+/// Pump创建代币时作为mint权限的账户
+pub fn pump_mint_authority() -> Pubkey {
+    Pubkey::find_program_address(&[MINT_AUTHORITY_SEED], &pump_program_id()).0
+}
+
+/// `curve` 是否为 `mint` 的Pump曲线账户
+pub fn is_bonding_curve(curve: &Pubkey, mint: &Pubkey) -> bool {
+    bonding_curve(mint) == *curve
+}
+
+/// 在账户列表中找出Pump mint和它的曲线账户: 曲线地址由mint推导，两者同时出现才算识别
+///
+/// 跳过程序和固定PDA这些不可能是mint的账户，推导结果按线程缓存。
+pub fn find_pump_mint(keys: &[Pubkey]) -> Option<(Pubkey, Pubkey)> {
+    let key_set: HashSet<&Pubkey> = keys.iter().collect();
+    keys.iter()
+        .filter(|key| !is_known_non_mint(key))
+        .find_map(|key| {
+            let curve = bonding_curve(key);
+            key_set.contains(&curve).then_some((*key, curve))
+        })
+}
+
+// 常见的程序和固定PDA账户
+fn is_known_non_mint(key: &Pubkey) -> bool {
+    static PROGRAMS: OnceLock<Vec<Pubkey>> = OnceLock::new();
+    let programs = PROGRAMS.get_or_init(|| {
+        vec![
+            system_program::id(),
+            pump_program_id(),
+            pumpamm_program_id(),
+            TOKEN_PROGRAM_ID.parse().expect("合法的Token程序ID"),
+            ATA_PROGRAM_ID.parse().expect("合法的ATA程序ID"),
+        ]
+    });
+    programs.contains(key) || fixed_accounts().iter().any(|(account, _)| account == key)
+}
+
+/// Anchor程序的事件签名账户
+pub fn event_authority(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], program_id).0
+}
+
+/// Pump AMM池账户，种子为 "pool", index(u16小端), creator, baseMint, quoteMint
+pub fn pumpamm_pool(index: u16, creator: &Pubkey, base_mint: &Pubkey, quote_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            POOL_SEED,
+            &index.to_le_bytes(),
+            creator.as_ref(),
+            base_mint.as_ref(),
+            quote_mint.as_ref(),
+        ],
+        &pumpamm_program_id(),
+    )
+    .0
+}
+
+/// Pump AMM的 `GlobalConfig` 账户
+pub fn pumpamm_global_config() -> Pubkey {
+    Pubkey::find_program_address(&[GLOBAL_CONFIG_SEED], &pumpamm_program_id()).0
+}
+
+/// 池的LP代币mint
+pub fn pumpamm_lp_mint(pool: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[LP_MINT_SEED, pool.as_ref()], &pumpamm_program_id()).0
+}
+
+/// 池创建者手续费金库的权限账户，手续费存在它持有报价代币的关联代币账户中
+pub fn pumpamm_coin_creator_vault_authority(coin_creator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[COIN_CREATOR_VAULT_SEED, coin_creator.as_ref()], &pumpamm_program_id()).0
+}
+
+/// 手续费程序中 `program_id` (Pump或Pump AMM) 的费率配置账户
+pub fn fee_config(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[FEE_CONFIG_SEED, program_id.as_ref()], &pump_fee_program_id()).0
+}
+
+/// `program_id` (Pump或Pump AMM) 的全局交易量累计账户
+pub fn global_volume_accumulator(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[GLOBAL_VOLUME_ACCUMULATOR_SEED], program_id).0
+}
+
+/// 用户在 `program_id` (Pump或Pump AMM) 下的交易量累计账户
+pub fn user_volume_accumulator(program_id: &Pubkey, user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[USER_VOLUME_ACCUMULATOR_SEED, user.as_ref()], program_id).0
+}
+
+// 不依赖mint的固定账户，只推导一次
+fn fixed_accounts() -> &'static [(Pubkey, &'static str)] {
+    static ACCOUNTS: OnceLock<Vec<(Pubkey, &'static str)>> = OnceLock::new();
+    ACCOUNTS.get_or_init(|| {
+        vec![
+            (pump_global(), "Pump Global"),
+            (pump_mint_authority(), "Pump Mint权限"),
+            (event_authority(&pump_program_id()), "Pump事件签名账户"),
+            (pumpamm_global_config(), "Pump AMM GlobalConfig"),
+            (event_authority(&pumpamm_program_id()), "Pump AMM事件签名账户"),
+            (global_volume_accumulator(&pump_program_id()), "Pump全局交易量累计"),
+            (global_volume_accumulator(&pumpamm_program_id()), "Pump AMM全局交易量累计"),
+            (fee_config(&pump_program_id()), "Pump手续费配置"),
+            (fee_config(&pumpamm_program_id()), "Pump AMM手续费配置"),
+            (pump_fee_program_id(), "手续费程序"),
+        ]
+    })
+}
+
+/// 按PDA推导识别Pump/Pump AMM的固定账户，`mint` 已知时同时识别它的曲线账户和曲线代币账户
+pub fn pump_account_label(key: &Pubkey, mint: Option<&Pubkey>) -> Option<&'static str> {
+    if let Some((_, label)) = fixed_accounts().iter().find(|(account, _)| account == key) {
+        return Some(label);
+    }
+    let mint = mint?;
+    let token_program: Pubkey = TOKEN_PROGRAM_ID.parse().expect("合法的Token程序ID");
+    if key == mint {
+        Some("代币Mint")
+    } else if is_bonding_curve(key, mint) {
+        Some("曲线账户")
+    } else if *key == associated_bonding_curve(mint, &token_program) {
+        Some("曲线代币账户")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn key(address: &str) -> Pubkey {
+        Pubkey::from_str(address).expect("合法的地址")
+    }
+
+    // 期望值为主网上的账户地址
+    #[test]
+    fn fixed_pdas_match_mainnet() {
+        assert_eq!(pump_global(), key("4wTV1YmiEkRvAtNtsSGPtUrqRYQMe5SKy2uB4Jjaxnjf"));
+        assert_eq!(event_authority(&pump_program_id()), key("Ce6TQqeHC9p8KetsN6JsjHK7UTZk7nasjjnr7XxXp9F1"));
+        assert_eq!(pump_mint_authority(), key("TSLvdd1pWpHVjahSpsvCXUbgwsL3JAcvokwaKt1eokM"));
+        assert_eq!(pumpamm_global_config(), key("ADyA8hdefvWN2dbGGWFotbzWxrAvLW83WG6QCVXvJKqw"));
+        assert_eq!(event_authority(&pumpamm_program_id()), key("GS4CU59F31iL7aR2Q8zVS8DRrcRnXX1yjQ66TqNVQnaR"));
+    }
+
+    #[test]
+    fn fee_pdas_match_mainnet() {
+        assert_eq!(fee_config(&pump_program_id()), key("8Wf5TiAheLUqBrKXeYg2JtAFFMWtKdG2BSFgqUcPVwTt"));
+        assert_eq!(fee_config(&pumpamm_program_id()), key("5PHirr8joyTMp9JMm6nW7hNDVyEYdkzDqazxPD7RaTjx"));
+        assert_eq!(global_volume_accumulator(&pump_program_id()), key("Hq2wp8uJ9jCPsYgNHex8RtqdvMPfVGoYwjvF1ATiwn2Y"));
+        assert_eq!(global_volume_accumulator(&pumpamm_program_id()), key("C2aFPdENg4A2HQsmrd5rTw5TaYBX5Ku887cWjbFKtZpw"));
+    }
+
+    #[test]
+    fn find_mint_by_curve_pda() {
+        let mint = Pubkey::new_unique();
+        let curve = bonding_curve(&mint);
+        assert!(is_bonding_curve(&curve, &mint));
+        assert!(!is_bonding_curve(&Pubkey::new_unique(), &mint));
+
+        let keys = [Pubkey::new_unique(), pump_global(), curve, mint, pump_program_id()];
+        assert_eq!(find_pump_mint(&keys), Some((mint, curve)));
+        assert_eq!(find_pump_mint(&keys[..3]), None);
+        assert_eq!(pump_account_label(&curve, Some(&mint)), Some("曲线账户"));
+    }
+}
@@ -4,6 +4,11 @@ use solana_sdk::{
 };
 use borsh::BorshDeserialize;
 
+use super::pda::{bonding_curve, find_pump_mint, is_bonding_curve};
+use super::instruction_account;
+use super::registry::{Decoded, ProgramDecoder};
+use crate::accounts::{decode_bonding_curve, decode_global, BONDING_CURVE_DISCRIMINATOR, GLOBAL_DISCRIMINATOR};
+
 // Pump程序ID
 pub const PUMP_PROGRAM_ID: &str = "6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P";
 
@@ -15,12 +20,36 @@ pub const BUY_IX: u8 = 3;
 pub const SELL_IX: u8 = 4;
 pub const WITHDRAW_IX: u8 = 5;
 pub const CREATE_COIN_IX: u8 = 24;
+pub const CREATE_V2_IX: u8 = 214;
 pub const BUY_TOKENS_IX: u8 = 102;
 pub const SWAP_IX: u8 = 103;
 pub const SELL_TOKENS_IX: u8 = 104;
 pub const EXTENDED_SELL_IX: u8 = 51;
 pub const INIT_IX: u8 = 234;
-pub const COMPLETE_IX: u8 = 200; // 假设200是完成指令的编号，可能需要调整
+pub const MIGRATE_WITHDRAW_IX: u8 = 183;
+pub const MIGRATE_IX: u8 = 155;
+pub const EVENT_IX: u8 = 228;
+
+// Anchor指令discriminator (sha256("global:<指令名>")前8字节)
+// 上面观察到的24/214/102/51/183/155分别是create/create_v2/buy/sell/withdraw/migrate discriminator的第一个字节，
+// 228是emit_cpi!事件指令前缀的第一个字节
+pub const CREATE_DISCRIMINATOR: [u8; 8] = [24, 30, 200, 40, 5, 28, 7, 119];
+// 创建Token-2022代币，不再创建Metaplex元数据账户
+pub const CREATE_V2_DISCRIMINATOR: [u8; 8] = [214, 144, 76, 236, 95, 139, 49, 180];
+pub const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+pub const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+// 曲线完成后由迁移账户调用，取出全部SOL和剩余代币用于创建AMM池
+pub const WITHDRAW_DISCRIMINATOR: [u8; 8] = [183, 18, 70, 156, 148, 109, 161, 34];
+// 取代withdraw的迁移指令，曲线完成后在同一条指令中经CPI创建Pump AMM池并存入流动性
+pub const MIGRATE_DISCRIMINATOR: [u8; 8] = [155, 234, 231, 146, 236, 158, 162, 30];
+
+// Anchor事件discriminator (sha256("event:<事件名>")前8字节)
+pub const COMPLETE_EVENT_DISCRIMINATOR: [u8; 8] = [95, 114, 97, 156, 212, 46, 152, 8];
+pub const CREATE_EVENT_DISCRIMINATOR: [u8; 8] = [27, 114, 169, 77, 222, 235, 99, 118];
+pub const TRADE_EVENT_DISCRIMINATOR: [u8; 8] = [189, 219, 127, 211, 78, 230, 97, 238];
+
+// emit_cpi!事件指令的前缀，事件以自调用指令的形式出现在内部指令中
+pub const EVENT_IX_TAG: [u8; 8] = [228, 69, 165, 46, 81, 203, 154, 29];
 
 // 指令类型
 #[derive(Debug)]
@@ -33,11 +62,14 @@ pub enum PumpInstructionType {
     Withdraw,
     // 自定义/扩展指令
     CreateCoin,    // 24 指令 - 创建代币
+    CreateV2,      // 214 指令 - 创建Token-2022代币
     BuyTokens,     // 102 指令 - 购买代币
     Swap,          // 103 指令 - 交换代币
     SellTokens,    // 104 指令 - 出售代币
     ExtendedSell,  // 51 指令 - 扩展版出售代币
-    Complete,      // 曲线完成指令
+    Complete,      // 228 指令 - CompleteEvent事件(曲线完成)
+    MigrateWithdraw, // 183 指令 - 曲线完成后提取流动性用于迁移
+    Migrate,       // 155 指令 - 曲线完成后迁移到Pump AMM池
     Init,          // 234 指令 - 初始化操作
     Unknown(()),   // 未知指令，使用()替代u8以避免dead_code警告
 }
@@ -57,18 +89,14 @@ pub struct BondingCurveInfo {
     pub mint: Pubkey,
     pub curve_account: Pubkey,
     pub is_complete: bool,
-    #[allow(dead_code)]
     pub virtual_token_reserves: Option<u64>,
-    #[allow(dead_code)]
     pub virtual_sol_reserves: Option<u64>,
-    #[allow(dead_code)]
     pub real_token_reserves: Option<u64>,
-    #[allow(dead_code)]
     pub real_sol_reserves: Option<u64>,
 }
 
 // 解析后的CompleteEvent
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CompleteEvent {
     pub user: Pubkey,
     pub mint: Pubkey,
@@ -85,7 +113,117 @@ struct CompleteEventArgs {
     timestamp: u64,
 }
 
+// 解析后的CreateEvent
+// 事件通过自调用的内部指令发出，shredstream中看不到，需要从其它数据源获取
+#[derive(Debug, Clone)]
+pub struct CreateEvent {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub user: Pubkey,
+}
+
+// CreateEvent反序列化结构体，之后新增的字段按前缀忽略
+#[derive(BorshDeserialize, Debug)]
+struct CreateEventArgs {
+    name: String,
+    symbol: String,
+    uri: String,
+    mint: [u8; 32],
+    bonding_curve: [u8; 32],
+    user: [u8; 32],
+}
+
+// 解析后的TradeEvent，与CreateEvent一样只出现在内部指令中
+#[derive(Debug, Clone)]
+pub struct TradeEvent {
+    pub mint: Pubkey,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    pub is_buy: bool,
+    pub user: Pubkey,
+    pub timestamp: i64,
+    pub virtual_sol_reserves: u64,
+    pub virtual_token_reserves: u64,
+}
+
+// TradeEvent反序列化结构体，之后新增的储备和手续费字段按前缀忽略
+#[derive(BorshDeserialize, Debug)]
+struct TradeEventArgs {
+    mint: [u8; 32],
+    sol_amount: u64,
+    token_amount: u64,
+    is_buy: bool,
+    user: [u8; 32],
+    timestamp: i64,
+    virtual_sol_reserves: u64,
+    virtual_token_reserves: u64,
+}
+
+// 从指令中解出的Pump交易
+#[derive(Debug, Clone)]
+pub struct PumpTrade {
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub user: Pubkey,
+    // 曲线创建者的手续费金库，creator fee更新前的旧交易没有这个账户
+    pub creator_vault: Option<Pubkey>,
+    pub is_buy: bool,
+    // 买入时为期望得到的代币数量，卖出时为卖出的代币数量
+    pub token_amount: u64,
+    // 买入时为最大SOL成本，卖出时为最小SOL收益
+    pub sol_limit: u64,
+}
+
+// 从指令中解出的Pump代币创建
+#[derive(Debug, Clone)]
+pub struct PumpCreate {
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub associated_bonding_curve: Pubkey,
+    // 签名并支付的账户
+    pub user: Pubkey,
+    // create参数中的creator，曲线的创建者手续费归它所有
+    pub creator: Pubkey,
+    // create为SPL Token，create_v2为Token-2022
+    pub token_program: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+// 从withdraw指令中解出的曲线迁移
+#[derive(Debug, Clone)]
+pub struct PumpWithdraw {
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    // 迁移账户
+    pub user: Pubkey,
+}
+
+// 从migrate指令中解出的曲线迁移，池由指令经CPI创建
+#[derive(Debug, Clone)]
+pub struct PumpMigrate {
+    pub mint: Pubkey,
+    pub bonding_curve: Pubkey,
+    pub pool: Pubkey,
+    // 迁移账户
+    pub user: Pubkey,
+}
+
+// 按Anchor discriminator识别出的Pump操作
+#[derive(Debug, Clone)]
+pub enum PumpAction {
+    Create(PumpCreate),
+    Trade(PumpTrade),
+    Withdraw(PumpWithdraw),
+    Migrate(PumpMigrate),
+}
+
 // Borsh反序列化结构体
+// create/create_v2参数的前缀，creator fee更新后追加了creator，create_v2还有之后追加的参数
 #[derive(BorshDeserialize, Debug)]
 struct CreateArgs {
     name: String,
@@ -276,27 +414,52 @@ fn parse_swap_args(data: &[u8]) -> Option<String> {
     Some(format!("交换代币: 输入数量={}, 最小输出数量={}", in_amount, min_out_amount))
 }
 
-/// 解析Complete事件数据
+/// 解析Complete事件数据，数据可以带或不带emit_cpi!的指令前缀
 pub fn parse_complete_event(data: &[u8]) -> Option<CompleteEvent> {
-    if data.len() < 8 + std::mem::size_of::<CompleteEventArgs>() {
-        return None;
-    }
+    let data = data.strip_prefix(&EVENT_IX_TAG[..]).unwrap_or(data);
+    let payload = data.strip_prefix(&COMPLETE_EVENT_DISCRIMINATOR[..])?;
+    let args = CompleteEventArgs::deserialize(&mut &payload[..]).ok()?;
 
-    if let Ok(args) = CompleteEventArgs::try_from_slice(&data[8..]) {
-        let user = Pubkey::new_from_array(args.user);
-        let mint = Pubkey::new_from_array(args.mint);
-        let bonding_curve = Pubkey::new_from_array(args.bonding_curve);
-        let timestamp = args.timestamp;
-        
-        Some(CompleteEvent {
-            user,
-            mint,
-            bonding_curve,
-            timestamp,
-        })
-    } else {
-        None
-    }
+    Some(CompleteEvent {
+        user: Pubkey::new_from_array(args.user),
+        mint: Pubkey::new_from_array(args.mint),
+        bonding_curve: Pubkey::new_from_array(args.bonding_curve),
+        timestamp: args.timestamp,
+    })
+}
+
+/// 解析CreateEvent，数据可以带或不带emit_cpi!的指令前缀
+pub fn parse_create_event(data: &[u8]) -> Option<CreateEvent> {
+    let data = data.strip_prefix(&EVENT_IX_TAG[..]).unwrap_or(data);
+    let payload = data.strip_prefix(&CREATE_EVENT_DISCRIMINATOR[..])?;
+    let args = CreateEventArgs::deserialize(&mut &payload[..]).ok()?;
+
+    Some(CreateEvent {
+        name: args.name,
+        symbol: args.symbol,
+        uri: args.uri,
+        mint: Pubkey::new_from_array(args.mint),
+        bonding_curve: Pubkey::new_from_array(args.bonding_curve),
+        user: Pubkey::new_from_array(args.user),
+    })
+}
+
+/// 解析TradeEvent，数据可以带或不带emit_cpi!的指令前缀
+pub fn parse_trade_event(data: &[u8]) -> Option<TradeEvent> {
+    let data = data.strip_prefix(&EVENT_IX_TAG[..]).unwrap_or(data);
+    let payload = data.strip_prefix(&TRADE_EVENT_DISCRIMINATOR[..])?;
+    let args = TradeEventArgs::deserialize(&mut &payload[..]).ok()?;
+
+    Some(TradeEvent {
+        mint: Pubkey::new_from_array(args.mint),
+        sol_amount: args.sol_amount,
+        token_amount: args.token_amount,
+        is_buy: args.is_buy,
+        user: Pubkey::new_from_array(args.user),
+        timestamp: args.timestamp,
+        virtual_sol_reserves: args.virtual_sol_reserves,
+        virtual_token_reserves: args.virtual_token_reserves,
+    })
 }
 
 /// 格式化时间戳为可读格式
@@ -327,7 +490,7 @@ pub fn parse_pump_instruction(transaction: &VersionedTransaction, instruction_in
     }
     
     let instruction = &instructions[instruction_index];
-    let program_id = instruction.program_id(&message.static_account_keys());
+    let program_id = instruction.program_id(message.static_account_keys());
     
     // 检查是否是Pump程序
     if program_id.to_string() != PUMP_PROGRAM_ID {
@@ -451,6 +614,17 @@ pub fn parse_pump_instruction(transaction: &VersionedTransaction, instruction_in
                 params,
             })
         },
+        CREATE_V2_IX => {
+            // CreateV2 - 参数前缀与create相同
+            let params = parse_create_coin_args(&instruction.data)
+                .unwrap_or_else(|| "创建Token-2022代币 (无法解析参数)".to_string());
+
+            Some(ParsedPumpInstruction {
+                instruction_type: PumpInstructionType::CreateV2,
+                name: "CreateV2".to_string(),
+                params,
+            })
+        },
         BUY_TOKENS_IX => {
             // BuyTokens - 观察到的自定义指令
             if let Ok(args) = BuyArgs::try_from_slice(&instruction.data[8..]) {
@@ -562,39 +736,59 @@ pub fn parse_pump_instruction(transaction: &VersionedTransaction, instruction_in
                 })
             }
         },
-        COMPLETE_IX => {
-            // Complete - 曲线完成指令
-            if let Some(event) = parse_complete_event(&instruction.data) {
-                Some(ParsedPumpInstruction {
+        EVENT_IX => {
+            // 事件指令，只在内部指令中出现
+            match parse_complete_event(&instruction.data) {
+                Some(event) => Some(ParsedPumpInstruction {
                     instruction_type: PumpInstructionType::Complete,
                     name: "Complete".to_string(),
                     params: format!(
                         "曲线完成: 用户={}, 代币={}, 曲线={}, {}",
                         event.user, event.mint, event.bonding_curve, format_timestamp(event.timestamp)
                     ),
-                })
-            } else if let Ok(args) = CompleteEventArgs::try_from_slice(&instruction.data[8..]) {
-                let user = Pubkey::new_from_array(args.user);
-                let mint = Pubkey::new_from_array(args.mint);
-                let bonding_curve = Pubkey::new_from_array(args.bonding_curve);
-                let timestamp = args.timestamp;
-                
-                Some(ParsedPumpInstruction {
-                    instruction_type: PumpInstructionType::Complete,
-                    name: "Complete".to_string(),
-                    params: format!(
-                        "曲线完成: 用户={}, 代币={}, 曲线={}, {}",
-                        user, mint, bonding_curve, format_timestamp(timestamp)
-                    ),
-                })
-            } else {
-                Some(ParsedPumpInstruction {
-                    instruction_type: PumpInstructionType::Complete,
-                    name: "Complete".to_string(),
-                    params: "曲线完成 (数据解析失败)".to_string(),
-                })
+                }),
+                None => Some(ParsedPumpInstruction {
+                    instruction_type: PumpInstructionType::Unknown(()),
+                    name: "Event".to_string(),
+                    params: "事件指令".to_string(),
+                }),
             }
         },
+        MIGRATE_WITHDRAW_IX if instruction.data.starts_with(&WITHDRAW_DISCRIMINATOR) => {
+            // Withdraw - 曲线完成后提取流动性，账户顺序为 global, mint(1), bondingCurve(2), ...
+            let static_keys = message.static_account_keys();
+            let params = match (
+                instruction_account(static_keys, &instruction.accounts, 1),
+                instruction_account(static_keys, &instruction.accounts, 2),
+            ) {
+                (Some(mint), Some(bonding_curve)) => format!("曲线完成，提取流动性用于迁移: 代币={}, 曲线={}", mint, bonding_curve),
+                _ => "曲线完成，提取流动性用于迁移".to_string(),
+            };
+            Some(ParsedPumpInstruction {
+                instruction_type: PumpInstructionType::MigrateWithdraw,
+                name: "MigrateWithdraw".to_string(),
+                params,
+            })
+        },
+        MIGRATE_IX if instruction.data.starts_with(&MIGRATE_DISCRIMINATOR) => {
+            // Migrate - 迁移到Pump AMM池，账户顺序为 global, withdrawAuthority, mint(2), bondingCurve(3), ..., pool(9), ...
+            let static_keys = message.static_account_keys();
+            let params = match (
+                instruction_account(static_keys, &instruction.accounts, 2),
+                instruction_account(static_keys, &instruction.accounts, 3),
+                instruction_account(static_keys, &instruction.accounts, 9),
+            ) {
+                (Some(mint), Some(bonding_curve), Some(pool)) => {
+                    format!("曲线完成，迁移到Pump AMM池: 代币={}, 曲线={}, 池={}", mint, bonding_curve, pool)
+                }
+                _ => "曲线完成，迁移到Pump AMM池".to_string(),
+            };
+            Some(ParsedPumpInstruction {
+                instruction_type: PumpInstructionType::Migrate,
+                name: "Migrate".to_string(),
+                params,
+            })
+        },
         INIT_IX => {
             // Init 初始化指令
             Some(ParsedPumpInstruction {
@@ -611,159 +805,76 @@ pub fn parse_pump_instruction(transaction: &VersionedTransaction, instruction_in
     }
 }
 
-// 获取与交易相关的Mint地址
+// Pump指令中mint和曲线账户的位置，账户顺序见 `decode_pump_actions`
+fn mint_and_curve_positions(data: &[u8]) -> Option<(usize, usize)> {
+    let discriminator = data.get(..8)?;
+    if discriminator == CREATE_DISCRIMINATOR || discriminator == CREATE_V2_DISCRIMINATOR {
+        Some((0, 2))
+    } else if discriminator == BUY_DISCRIMINATOR || discriminator == SELL_DISCRIMINATOR {
+        Some((2, 3))
+    } else if discriminator == WITHDRAW_DISCRIMINATOR {
+        Some((1, 2))
+    } else if discriminator == MIGRATE_DISCRIMINATOR {
+        Some((2, 3))
+    } else {
+        None
+    }
+}
+
+/// 获取与交易相关的Mint地址
+///
+/// 只有曲线账户等于按mint推导的曲线PDA时才认为找到了mint: 先看Pump create/buy/sell/withdraw/migrate指令中
+/// mint和曲线账户所在的位置，再看CompleteEvent，最后在整个账户列表中配对(经其他程序CPI调用Pump的交易)。
 pub fn get_mint_from_transaction(transaction: &VersionedTransaction) -> Option<Pubkey> {
     let message = &transaction.message;
     let static_keys = message.static_account_keys();
-    
-    // 首先尝试在账户中寻找以"pump"结尾的地址，这通常是Pump协议的代币地址
-    for key in static_keys {
-        let key_str = key.to_string();
-        if key_str.ends_with("pump") {
-            return Some(*key);
+
+    for instruction in message.instructions() {
+        if instruction.program_id(static_keys).to_string() != PUMP_PROGRAM_ID {
+            continue;
         }
-    }
-    
-    // 如果没有找到pump结尾的地址，则尝试通过指令内容来查找
-    let instructions = message.instructions();
-    
-    for instruction in instructions {
-        let program_id = instruction.program_id(&static_keys);
-        
-        // 如果是Pump程序指令
-        if program_id.to_string() == PUMP_PROGRAM_ID {
-            // 检查是创建或交易指令
-            if !instruction.data.is_empty() {
-                let discriminator = instruction.data[0];
-                if discriminator == CREATE_IX || discriminator == BUY_IX || discriminator == SELL_IX || 
-                   discriminator == CREATE_COIN_IX || discriminator == EXTENDED_SELL_IX || 
-                   discriminator == BUY_TOKENS_IX || discriminator == SWAP_IX || 
-                   discriminator == SELL_TOKENS_IX {
-                    
-                    // 查找指令的账户列表中与mint相关的账户
-                    // 通常mint是前几个账户之一
-                    if !instruction.accounts.is_empty() {
-                        for (i, &account_index) in instruction.accounts.iter().enumerate().take(3) {
-                            if (account_index as usize) < static_keys.len() {
-                                let potential_mint = static_keys[account_index as usize];
-                                let key_str = potential_mint.to_string();
-                                
-                                // 如果找到以pump结尾的账户，或者是第一个账户（对于CREATE_IX）
-                                if key_str.ends_with("pump") || 
-                                   (discriminator == CREATE_IX && i == 0) {
-                                    return Some(potential_mint);
-                                }
-                            }
-                        }
-                    }
-                } else if discriminator == COMPLETE_IX {
-                    // 在Complete指令中，mint通常在事件参数中
-                    if instruction.data.len() >= 8 + 32*3 {
-                        if let Ok(args) = CompleteEventArgs::try_from_slice(&instruction.data[8..]) {
-                            return Some(Pubkey::new_from_array(args.mint));
-                        }
-                    }
+        let data = &instruction.data;
+        if let Some((mint_position, curve_position)) = mint_and_curve_positions(data) {
+            let account = |position| instruction_account(static_keys, &instruction.accounts, position);
+            if let (Some(mint), Some(curve)) = (account(mint_position), account(curve_position)) {
+                if is_bonding_curve(&curve, &mint) {
+                    return Some(mint);
                 }
             }
+        } else if let Some(event) = parse_complete_event(data) {
+            if is_bonding_curve(&event.bonding_curve, &event.mint) {
+                return Some(event.mint);
+            }
         }
     }
-    
-    None
+
+    find_pump_mint(static_keys).map(|(mint, _)| mint)
 }
 
-// 获取与交易相关的BondingCurve信息
+/// 获取与交易相关的BondingCurve信息，曲线账户由mint推导
 pub fn get_bonding_curve_info(transaction: &VersionedTransaction) -> Option<BondingCurveInfo> {
     let message = &transaction.message;
     let static_keys = message.static_account_keys();
-    
-    // 首先尝试找出mint地址
     let mint = get_mint_from_transaction(transaction)?;
-    
-    // 默认值
-    let mut result = BondingCurveInfo {
+    let curve_account = bonding_curve(&mint);
+
+    // withdraw/migrate指令或CompleteEvent说明曲线已完成
+    let is_complete = message.instructions().iter().any(|instruction| {
+        instruction.program_id(static_keys).to_string() == PUMP_PROGRAM_ID
+            && (instruction.data.starts_with(&WITHDRAW_DISCRIMINATOR)
+                || instruction.data.starts_with(&MIGRATE_DISCRIMINATOR)
+                || parse_complete_event(&instruction.data).is_some_and(|event| event.bonding_curve == curve_account))
+    });
+
+    Some(BondingCurveInfo {
         mint,
-        curve_account: Pubkey::default(),
-        is_complete: false,
+        curve_account,
+        is_complete,
         virtual_token_reserves: None,
         virtual_sol_reserves: None,
         real_token_reserves: None,
         real_sol_reserves: None,
-    };
-    
-    // 找到以"pump"结尾但不是mint的账户，这通常是曲线账户
-    for key in static_keys {
-        let key_str = key.to_string();
-        if key_str.ends_with("pump") && *key != mint {
-            result.curve_account = *key;
-            break;
-        }
-    }
-    
-    // 如果找不到，则尝试通过特定指令查找
-    if result.curve_account == Pubkey::default() {
-        // 查找BondingCurve账户
-        for instruction in message.instructions() {
-            let program_id = instruction.program_id(&static_keys);
-            
-            // 如果是Pump程序指令
-            if program_id.to_string() == PUMP_PROGRAM_ID && !instruction.accounts.is_empty() {
-                let discriminator = if instruction.data.is_empty() { 0 } else { instruction.data[0] };
-                
-                match discriminator {
-                    // 创建代币
-                    CREATE_IX | CREATE_COIN_IX => {
-                        if instruction.accounts.len() > 3 {
-                            let curve_index = instruction.accounts[2] as usize;
-                            if curve_index < static_keys.len() {
-                                result.curve_account = static_keys[curve_index];
-                            }
-                        }
-                    },
-                    // 买入代币
-                    BUY_IX | BUY_TOKENS_IX => {
-                        if instruction.accounts.len() > 3 {
-                            let curve_index = instruction.accounts[3] as usize;
-                            if curve_index < static_keys.len() {
-                                result.curve_account = static_keys[curve_index];
-                            }
-                        }
-                    },
-                    // 卖出代币
-                    SELL_IX | EXTENDED_SELL_IX | SELL_TOKENS_IX => {
-                        if instruction.accounts.len() > 3 {
-                            let curve_index = instruction.accounts[3] as usize;
-                            if curve_index < static_keys.len() {
-                                result.curve_account = static_keys[curve_index];
-                            }
-                        }
-                    },
-                    // 交换代币
-                    SWAP_IX => {
-                        if instruction.accounts.len() > 3 {
-                            let curve_index = instruction.accounts[3] as usize;
-                            if curve_index < static_keys.len() {
-                                result.curve_account = static_keys[curve_index];
-                            }
-                        }
-                    },
-                    // 曲线完成
-                    COMPLETE_IX => {
-                        if let Ok(args) = CompleteEventArgs::try_from_slice(&instruction.data[8..]) {
-                            result.is_complete = true;
-                            result.curve_account = Pubkey::new_from_array(args.bonding_curve);
-                        }
-                    },
-                    _ => {}
-                }
-            }
-        }
-    }
-    
-    if result.curve_account != Pubkey::default() {
-        Some(result)
-    } else {
-        None
-    }
+    })
 }
 
 // 解析交易中的Pump指令
@@ -779,4 +890,316 @@ pub fn parse_pump_transaction(transaction: &VersionedTransaction) -> Vec<ParsedP
     }
     
     parsed_instructions
-}
\ No newline at end of file
+}
+
+/// 按Anchor discriminator解析交易中的Pump create/buy/sell/withdraw/migrate指令
+///
+/// 账户顺序来自2025年5月creator fee更新后的IDL:
+/// create为 mint, mintAuthority, bondingCurve, associatedBondingCurve, global, mplTokenMetadata, metadata, user(7),
+/// systemProgram, tokenProgram(9)；create_v2没有两个元数据账户，user(5), systemProgram, tokenProgram(7)；
+/// 两者的参数都是 name, symbol, uri, creator。
+/// buy/sell为 global, feeRecipient, mint(2), bondingCurve(3), associatedBondingCurve, associatedUser, user(6), systemProgram,
+/// 之后buy为 tokenProgram, creatorVault(9)，sell为 creatorVault(8), tokenProgram；
+/// withdraw为 global, mint(1), bondingCurve(2), associatedBondingCurve, associatedUser, user(5)；
+/// migrate为 global, withdrawAuthority, mint(2), bondingCurve(3), associatedBondingCurve, user(5), systemProgram,
+/// tokenProgram, pumpAmm, pool(9), poolAuthority, ...。
+pub fn decode_pump_actions(transaction: &VersionedTransaction) -> Vec<PumpAction> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+    let mut actions = Vec::new();
+
+    for instruction in message.instructions() {
+        let program_id = instruction.program_id(static_keys);
+        if program_id.to_string() != PUMP_PROGRAM_ID || instruction.data.len() < 8 {
+            continue;
+        }
+
+        let data = &instruction.data;
+        let accounts = &instruction.accounts;
+        let account = |position| instruction_account(static_keys, accounts, position);
+
+        if data[..8] == CREATE_DISCRIMINATOR || data[..8] == CREATE_V2_DISCRIMINATOR {
+            // create_v2没有Metaplex的两个元数据账户
+            let (user_position, token_program_position) =
+                if data[..8] == CREATE_DISCRIMINATOR { (7, 9) } else { (5, 7) };
+            let (Some(mint), Some(bonding_curve), Some(associated_bonding_curve), Some(user), Some(token_program)) = (
+                account(0),
+                account(2),
+                account(3),
+                account(user_position),
+                account(token_program_position),
+            ) else {
+                continue;
+            };
+            let mut args = &data[8..];
+            let Ok(parsed) = CreateArgs::deserialize(&mut args) else {
+                continue;
+            };
+            // 更新前的create没有creator参数，创建者就是签名用户
+            let creator = <[u8; 32]>::deserialize(&mut args).map_or(user, Pubkey::new_from_array);
+            actions.push(PumpAction::Create(PumpCreate {
+                mint,
+                bonding_curve,
+                associated_bonding_curve,
+                user,
+                creator,
+                token_program,
+                name: parsed.name,
+                symbol: parsed.symbol,
+                uri: parsed.uri,
+            }));
+        } else if data[..8] == BUY_DISCRIMINATOR || data[..8] == SELL_DISCRIMINATOR {
+            let is_buy = data[..8] == BUY_DISCRIMINATOR;
+            let (Some(mint), Some(bonding_curve), Some(user)) = (account(2), account(3), account(6)) else {
+                continue;
+            };
+            let creator_vault = account(if is_buy { 9 } else { 8 });
+            // buy和sell的参数布局相同: amount + SOL限额
+            let Some(args) = data.get(8..24).and_then(|args| BuyArgs::try_from_slice(args).ok()) else {
+                continue;
+            };
+            actions.push(PumpAction::Trade(PumpTrade {
+                mint,
+                bonding_curve,
+                user,
+                creator_vault,
+                is_buy,
+                token_amount: args.amount,
+                sol_limit: args.max_sol_cost,
+            }));
+        } else if data[..8] == WITHDRAW_DISCRIMINATOR {
+            let (Some(mint), Some(bonding_curve), Some(user)) = (account(1), account(2), account(5)) else {
+                continue;
+            };
+            actions.push(PumpAction::Withdraw(PumpWithdraw { mint, bonding_curve, user }));
+        } else if data[..8] == MIGRATE_DISCRIMINATOR {
+            let (Some(mint), Some(bonding_curve), Some(user), Some(pool)) = (account(2), account(3), account(5), account(9)) else {
+                continue;
+            };
+            actions.push(PumpAction::Migrate(PumpMigrate { mint, bonding_curve, pool, user }));
+        }
+    }
+
+    actions
+}
+
+/// Pump程序的解码器
+pub struct PumpDecoder;
+
+impl ProgramDecoder for PumpDecoder {
+    fn program_id(&self) -> Pubkey {
+        PUMP_PROGRAM_ID.parse().expect("合法的Pump程序ID")
+    }
+
+    fn name(&self) -> &str {
+        "Pump协议"
+    }
+
+    fn decode_instruction(&self, transaction: &VersionedTransaction, instruction_index: usize) -> Option<Decoded> {
+        let parsed = parse_pump_instruction(transaction, instruction_index)?;
+        let mut decoded = Decoded::new(parsed.name, parsed.params);
+        if let Some(mint) = get_mint_from_transaction(transaction) {
+            decoded = decoded.with_field("代币Mint", mint);
+        }
+        if let Some(curve_info) = get_bonding_curve_info(transaction) {
+            decoded = decoded.with_field("曲线账户", curve_info.curve_account);
+            if curve_info.is_complete {
+                decoded = decoded.with_field("曲线状态", "已完成");
+            }
+        }
+        Some(decoded)
+    }
+
+    fn decode_event(&self, data: &[u8]) -> Option<Decoded> {
+        if let Some(event) = parse_trade_event(data) {
+            return Some(
+                Decoded::new(
+                    "TradeEvent",
+                    format!(
+                        "{}: 代币数量={}, SOL={}, 虚拟储备: SOL={}, 代币={}",
+                        if event.is_buy { "买入" } else { "卖出" },
+                        event.token_amount,
+                        lamports_to_sol_string(event.sol_amount),
+                        lamports_to_sol_string(event.virtual_sol_reserves),
+                        event.virtual_token_reserves
+                    ),
+                )
+                .with_field("代币Mint", event.mint)
+                .with_field("用户", event.user),
+            );
+        }
+        if let Some(event) = parse_create_event(data) {
+            return Some(
+                Decoded::new("CreateEvent", format!("名称=\"{}\", 符号=\"{}\", URI=\"{}\"", event.name, event.symbol, event.uri))
+                    .with_field("代币Mint", event.mint)
+                    .with_field("曲线账户", event.bonding_curve)
+                    .with_field("创建者", event.user),
+            );
+        }
+        let event = parse_complete_event(data)?;
+        Some(
+            Decoded::new("CompleteEvent", format_timestamp(event.timestamp))
+                .with_field("代币Mint", event.mint)
+                .with_field("曲线账户", event.bonding_curve)
+                .with_field("用户", event.user),
+        )
+    }
+
+    fn decode_account(&self, data: &[u8]) -> Option<Decoded> {
+        if data.starts_with(&GLOBAL_DISCRIMINATOR) {
+            let global = decode_global(data).ok()?;
+            return Some(
+                Decoded::new(
+                    if global.initialized { "Global" } else { "Global(未初始化)" },
+                    format!(
+                        "初始虚拟储备: SOL={}, 代币={}, 手续费={}bps, 创建者手续费={}bps",
+                        lamports_to_sol_string(global.params.initial_virtual_sol_reserves),
+                        global.params.initial_virtual_token_reserves,
+                        global.params.fee_basis_points,
+                        global.params.creator_fee_basis_points
+                    ),
+                )
+                .with_field("管理员", global.authority)
+                .with_field("手续费接收", global.fee_recipient),
+            );
+        }
+        if data.starts_with(&BONDING_CURVE_DISCRIMINATOR) {
+            let curve = decode_bonding_curve(data).ok()?;
+            let decoded = Decoded::new(
+                "BondingCurve",
+                format!(
+                    "虚拟储备: SOL={}, 代币={}, 实际储备: SOL={}, 代币={}, {}",
+                    lamports_to_sol_string(curve.virtual_sol_reserves),
+                    curve.virtual_token_reserves,
+                    lamports_to_sol_string(curve.real_sol_reserves),
+                    curve.real_token_reserves,
+                    if curve.complete { "已完成" } else { "进行中" }
+                ),
+            );
+            return Some(match curve.creator {
+                Some(creator) => decoded.with_field("创建者", creator),
+                None => decoded,
+            });
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::token_parser::{TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_sdk::message::{Message, VersionedMessage};
+    use solana_sdk::signature::Signature;
+
+    fn transaction(instruction: Instruction, payer: &Pubkey) -> VersionedTransaction {
+        VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::Legacy(Message::new(&[instruction], Some(payer))),
+        }
+    }
+
+    fn create_data(discriminator: &[u8; 8], creator: Option<&Pubkey>) -> Vec<u8> {
+        let mut data = discriminator.to_vec();
+        for text in ["Token", "TKN", "https://example.com/token.json"] {
+            data.extend_from_slice(&(text.len() as u32).to_le_bytes());
+            data.extend_from_slice(text.as_bytes());
+        }
+        if let Some(creator) = creator {
+            data.extend_from_slice(creator.as_ref());
+        }
+        data
+    }
+
+    // 按 `decode_pump_actions` 文档中的账户顺序填充，user和tokenProgram以外的账户随机生成
+    fn create_instruction(
+        data: Vec<u8>,
+        account_count: usize,
+        user: (usize, Pubkey),
+        token_program: (usize, Pubkey),
+    ) -> Instruction {
+        let accounts = (0..account_count)
+            .map(|position| match position {
+                _ if position == user.0 => AccountMeta::new(user.1, true),
+                _ if position == token_program.0 => AccountMeta::new_readonly(token_program.1, false),
+                _ => AccountMeta::new(Pubkey::new_unique(), position == 0),
+            })
+            .collect();
+        Instruction { program_id: pump_program_id(), accounts, data }
+    }
+
+    fn pump_program_id() -> Pubkey {
+        PUMP_PROGRAM_ID.parse().expect("合法的Pump程序ID")
+    }
+
+    fn decode_create(transaction: &VersionedTransaction) -> PumpCreate {
+        match decode_pump_actions(transaction).as_slice() {
+            [PumpAction::Create(create)] => create.clone(),
+            actions => panic!("期望一个create，得到 {:?}", actions),
+        }
+    }
+
+    #[test]
+    fn create_with_creator_arg() {
+        let user = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        let token_program: Pubkey = TOKEN_PROGRAM_ID.parse().expect("合法的Token程序ID");
+        let data = create_data(&CREATE_DISCRIMINATOR, Some(&creator));
+        let instruction = create_instruction(data, 14, (7, user), (9, token_program));
+        let mint = instruction.accounts[0].pubkey;
+
+        let create = decode_create(&transaction(instruction, &user));
+        assert_eq!(create.mint, mint);
+        assert_eq!(create.user, user);
+        assert_eq!(create.creator, creator);
+        assert_eq!(create.token_program, token_program);
+        assert_eq!((create.name.as_str(), create.symbol.as_str()), ("Token", "TKN"));
+    }
+
+    #[test]
+    fn legacy_create_without_creator() {
+        let user = Pubkey::new_unique();
+        let token_program: Pubkey = TOKEN_PROGRAM_ID.parse().expect("合法的Token程序ID");
+        let instruction = create_instruction(create_data(&CREATE_DISCRIMINATOR, None), 14, (7, user), (9, token_program));
+
+        assert_eq!(decode_create(&transaction(instruction, &user)).creator, user);
+    }
+
+    #[test]
+    fn create_v2_for_token_2022() {
+        let user = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        let token_program: Pubkey = TOKEN_2022_PROGRAM_ID.parse().expect("合法的Token-2022程序ID");
+        let mut data = create_data(&CREATE_V2_DISCRIMINATOR, Some(&creator));
+        // is_mayhem_mode
+        data.push(0);
+        let instruction = create_instruction(data, 16, (5, user), (7, token_program));
+        let (mint, curve) = (instruction.accounts[0].pubkey, instruction.accounts[2].pubkey);
+
+        let create = decode_create(&transaction(instruction, &user));
+        assert_eq!((create.mint, create.bonding_curve), (mint, curve));
+        assert_eq!(create.user, user);
+        assert_eq!(create.creator, creator);
+        assert_eq!(create.token_program, token_program);
+    }
+
+    #[test]
+    fn migrate_to_pump_amm_pool() {
+        let user = Pubkey::new_unique();
+        let accounts: Vec<AccountMeta> = (0..24)
+            .map(|position| AccountMeta::new(if position == 5 { user } else { Pubkey::new_unique() }, position == 5))
+            .collect();
+        let (mint, curve, pool) = (accounts[2].pubkey, accounts[3].pubkey, accounts[9].pubkey);
+        let instruction = Instruction { program_id: pump_program_id(), accounts, data: MIGRATE_DISCRIMINATOR.to_vec() };
+        let transaction = transaction(instruction, &user);
+
+        let migrate = match decode_pump_actions(&transaction).as_slice() {
+            [PumpAction::Migrate(migrate)] => migrate.clone(),
+            actions => panic!("期望一个migrate，得到 {:?}", actions),
+        };
+        assert_eq!((migrate.mint, migrate.bonding_curve, migrate.pool, migrate.user), (mint, curve, pool, user));
+        let parsed = parse_pump_instruction(&transaction, 0).expect("可以解析的指令");
+        assert!(matches!(parsed.instruction_type, PumpInstructionType::Migrate));
+    }
+}
@@ -4,6 +4,12 @@ use solana_sdk::{
 };
 use borsh::BorshDeserialize;
 
+use super::pump_parser::EVENT_IX_TAG;
+use super::pda::pumpamm_lp_mint;
+use super::instruction_account;
+use super::registry::{Decoded, ProgramDecoder};
+use crate::accounts::{decode_global_config, decode_pool, GLOBAL_CONFIG_DISCRIMINATOR, POOL_DISCRIMINATOR};
+
 // Pump AMM程序ID
 pub const PUMPAMM_PROGRAM_ID: &str = "pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA";
 
@@ -13,6 +19,13 @@ pub const DEPOSIT_IX: [u8; 8] = [242, 35, 198, 137, 82, 225, 242, 182];
 pub const BUY_IX: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
 pub const SELL_IX: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
 pub const WITHDRAW_IX: [u8; 8] = [183, 18, 70, 156, 148, 109, 161, 34];
+pub const UPDATE_FEE_CONFIG_IX: [u8; 8] = [104, 184, 103, 242, 88, 151, 107, 20];
+
+// Anchor事件discriminator (sha256("event:<事件名>")前8字节)
+// 与Pump一样通过emit_cpi!以内部指令发出，shredstream中看不到
+pub const BUY_EVENT_DISCRIMINATOR: [u8; 8] = [103, 244, 82, 31, 44, 245, 119, 119];
+pub const SELL_EVENT_DISCRIMINATOR: [u8; 8] = [62, 47, 55, 10, 165, 3, 220, 42];
+pub const UPDATE_FEE_CONFIG_EVENT_DISCRIMINATOR: [u8; 8] = [90, 23, 65, 35, 62, 244, 188, 208];
 
 // 指令类型
 #[derive(Debug)]
@@ -40,7 +53,116 @@ pub struct PoolInfo {
     pub pool: Pubkey,
     pub base_mint: Pubkey,
     pub quote_mint: Pubkey,
-    pub lp_mint: Option<Pubkey>,
+    pub lp_mint: Pubkey,
+}
+
+// 从create_pool指令中解出的新池
+#[derive(Debug, Clone)]
+pub struct PoolCreate {
+    pub pool: Pubkey,
+    pub creator: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub index: u16,
+    pub base_amount_in: u64,
+    pub quote_amount_in: u64,
+}
+
+// 从buy/sell指令中解出的兑换
+#[derive(Debug, Clone)]
+pub struct PoolSwap {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    // 池创建者手续费金库的权限账户，creator fee更新前的旧交易没有这个账户
+    pub coin_creator_vault_authority: Option<Pubkey>,
+    pub is_buy: bool,
+    // 买入时为期望得到的基础代币数量，卖出时为卖出的基础代币数量
+    pub base_amount: u64,
+    // 买入时为最大报价代币输入，卖出时为最小报价代币输出
+    pub quote_limit: u64,
+}
+
+// 从deposit/withdraw指令中解出的流动性变化
+#[derive(Debug, Clone)]
+pub struct PoolLiquidity {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub is_deposit: bool,
+    // 存入时为得到的LP数量，提取时为销毁的LP数量
+    pub lp_token_amount: u64,
+    // 存入时为最大输入，提取时为最小输出
+    pub base_limit: u64,
+    pub quote_limit: u64,
+}
+
+// 按discriminator识别出的Pump AMM操作
+#[derive(Debug, Clone)]
+pub enum PumpAmmAction {
+    CreatePool(PoolCreate),
+    Swap(PoolSwap),
+    Liquidity(PoolLiquidity),
+    // 管理员更新GlobalConfig中的费率
+    UpdateFeeConfig {
+        lp_fee_basis_points: u64,
+        protocol_fee_basis_points: u64,
+    },
+}
+
+// 解析后的BuyEvent/SellEvent，池储备为交易前的值
+#[derive(Debug, Clone)]
+pub struct SwapEvent {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub is_buy: bool,
+    pub base_amount: u64,
+    // 买入时为不含手续费的报价代币输入，卖出时为不含手续费的报价代币输出
+    pub quote_amount: u64,
+    pub pool_base_token_reserves: u64,
+    pub pool_quote_token_reserves: u64,
+    pub lp_fee_basis_points: u64,
+    pub lp_fee: u64,
+    pub protocol_fee_basis_points: u64,
+    pub protocol_fee: u64,
+}
+
+// BuyEvent和SellEvent的公共前缀
+#[derive(BorshDeserialize, Debug)]
+struct SwapEventHead {
+    _timestamp: i64,
+    base_amount: u64,
+    _quote_limit: u64,
+    _user_base_token_reserves: u64,
+    _user_quote_token_reserves: u64,
+    pool_base_token_reserves: u64,
+    pool_quote_token_reserves: u64,
+    quote_amount: u64,
+    lp_fee_basis_points: u64,
+    lp_fee: u64,
+    protocol_fee_basis_points: u64,
+    protocol_fee: u64,
+    _quote_amount_with_fee: u64,
+    _user_quote_amount: u64,
+    pool: [u8; 32],
+    user: [u8; 32],
+}
+
+// UpdateFeeConfigEvent的费率部分
+#[derive(BorshDeserialize, Debug)]
+struct UpdateFeeConfigEventHead {
+    _timestamp: i64,
+    _admin: [u8; 32],
+    lp_fee_basis_points: u64,
+    protocol_fee_basis_points: u64,
+}
+
+// update_fee_config指令的费率参数，之后是8个协议费接收地址
+#[derive(BorshDeserialize, Debug)]
+struct FeeConfigArgs {
+    lp_fee_basis_points: u64,
+    protocol_fee_basis_points: u64,
 }
 
 // CreatePool参数
@@ -182,7 +304,7 @@ pub fn parse_pumpamm_instruction(transaction: &VersionedTransaction, instruction
     }
     
     let instruction = &instructions[instruction_index];
-    let program_id = instruction.program_id(&message.static_account_keys());
+    let program_id = instruction.program_id(message.static_account_keys());
     
     // 检查是否是Pump AMM程序
     if program_id.to_string() != PUMPAMM_PROGRAM_ID {
@@ -285,7 +407,7 @@ pub fn get_pool_from_instruction(transaction: &VersionedTransaction, instruction
     }
     
     let instruction = &instructions[instruction_index];
-    let program_id = instruction.program_id(&message.static_account_keys());
+    let program_id = instruction.program_id(message.static_account_keys());
     
     // 检查是否是Pump AMM程序
     if program_id.to_string() != PUMPAMM_PROGRAM_ID {
@@ -324,7 +446,7 @@ pub fn get_token_mints_from_instruction(transaction: &VersionedTransaction, inst
     }
     
     let instruction = &instructions[instruction_index];
-    let program_id = instruction.program_id(&message.static_account_keys());
+    let program_id = instruction.program_id(message.static_account_keys());
     
     // 检查是否是Pump AMM程序
     if program_id.to_string() != PUMPAMM_PROGRAM_ID {
@@ -369,7 +491,7 @@ pub fn get_pool_info_from_transaction(transaction: &VersionedTransaction) -> Opt
     let instructions = message.instructions();
     
     for (i, instruction) in instructions.iter().enumerate() {
-        let program_id = instruction.program_id(&message.static_account_keys());
+        let program_id = instruction.program_id(message.static_account_keys());
         
         // 检查是否是Pump AMM程序
         if program_id.to_string() != PUMPAMM_PROGRAM_ID {
@@ -382,28 +504,224 @@ pub fn get_pool_info_from_transaction(transaction: &VersionedTransaction) -> Opt
         // 获取代币铸造地址
         let (base_mint, quote_mint) = get_token_mints_from_instruction(transaction, i)?;
         
-        // 尝试获取LP代币铸造地址（如果适用）
-        let mut lp_mint = None;
-        if is_instruction_match(&instruction.data, &CREATE_POOL_IX) || 
-           is_instruction_match(&instruction.data, &DEPOSIT_IX) || 
-           is_instruction_match(&instruction.data, &WITHDRAW_IX) {
-            
-            let accounts = &instruction.accounts;
-            let static_keys = message.static_account_keys();
-            
-            // LP代币铸造地址通常在创建池、存款和提款指令中的第6个账户
-            if accounts.len() > 5 && (accounts[5] as usize) < static_keys.len() {
-                lp_mint = Some(static_keys[accounts[5] as usize]);
-            }
-        }
-        
+        // 每个池的LP代币mint都由池地址推导，兑换指令中没有这个账户
         return Some(PoolInfo {
             pool,
             base_mint,
             quote_mint,
-            lp_mint,
+            lp_mint: pumpamm_lp_mint(&pool),
         });
     }
     
     None
-} 
\ No newline at end of file
+} 
+
+/// 按discriminator解析交易中的Pump AMM指令
+///
+/// 账户顺序来自2025年5月creator fee更新后的IDL:
+/// buy/sell为 pool, user, globalConfig, baseMint(3), quoteMint(4), ..., program, coinCreatorVaultAta, coinCreatorVaultAuthority(18)；
+/// create_pool/deposit/withdraw为 pool, globalConfig, user(2), baseMint, quoteMint, lpMint(5)。
+/// 参数用前缀反序列化，容忍新版本追加的参数。
+pub fn decode_pumpamm_actions(transaction: &VersionedTransaction) -> Vec<PumpAmmAction> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+    let mut actions = Vec::new();
+
+    for instruction in message.instructions() {
+        let program_id = instruction.program_id(static_keys);
+        if program_id.to_string() != PUMPAMM_PROGRAM_ID || instruction.data.len() < 8 {
+            continue;
+        }
+
+        let data = &instruction.data;
+        let mut args = &data[8..];
+        let accounts = &instruction.accounts;
+        let account = |position| instruction_account(static_keys, accounts, position);
+
+        if is_instruction_match(data, &CREATE_POOL_IX) {
+            let (Some(pool), Some(creator), Some(base_mint), Some(quote_mint), Some(lp_mint)) =
+                (account(0), account(2), account(3), account(4), account(5))
+            else {
+                continue;
+            };
+            if let Ok(parsed) = CreatePoolArgs::deserialize(&mut args) {
+                actions.push(PumpAmmAction::CreatePool(PoolCreate {
+                    pool,
+                    creator,
+                    base_mint,
+                    quote_mint,
+                    lp_mint,
+                    index: parsed.index,
+                    base_amount_in: parsed.base_amount_in,
+                    quote_amount_in: parsed.quote_amount_in,
+                }));
+            }
+        } else if is_instruction_match(data, &BUY_IX) || is_instruction_match(data, &SELL_IX) {
+            let is_buy = is_instruction_match(data, &BUY_IX);
+            let (Some(pool), Some(user), Some(base_mint), Some(quote_mint)) =
+                (account(0), account(1), account(3), account(4))
+            else {
+                continue;
+            };
+            // buy和sell的参数布局相同: 基础代币数量 + 报价代币限额
+            let Ok(parsed) = BuyArgs::deserialize(&mut args) else {
+                continue;
+            };
+            actions.push(PumpAmmAction::Swap(PoolSwap {
+                pool,
+                user,
+                base_mint,
+                quote_mint,
+                coin_creator_vault_authority: account(18),
+                is_buy,
+                base_amount: parsed.base_amount_out,
+                quote_limit: parsed.max_quote_amount_in,
+            }));
+        } else if is_instruction_match(data, &DEPOSIT_IX) || is_instruction_match(data, &WITHDRAW_IX) {
+            let is_deposit = is_instruction_match(data, &DEPOSIT_IX);
+            let (Some(pool), Some(user)) = (account(0), account(2)) else {
+                continue;
+            };
+            // deposit和withdraw的参数布局相同: LP数量 + 基础/报价代币限额
+            let Ok(parsed) = DepositArgs::deserialize(&mut args) else {
+                continue;
+            };
+            actions.push(PumpAmmAction::Liquidity(PoolLiquidity {
+                pool,
+                user,
+                is_deposit,
+                lp_token_amount: parsed.lp_token_amount_out,
+                base_limit: parsed.max_base_amount_in,
+                quote_limit: parsed.max_quote_amount_in,
+            }));
+        } else if is_instruction_match(data, &UPDATE_FEE_CONFIG_IX) {
+            if let Ok(parsed) = FeeConfigArgs::deserialize(&mut args) {
+                actions.push(PumpAmmAction::UpdateFeeConfig {
+                    lp_fee_basis_points: parsed.lp_fee_basis_points,
+                    protocol_fee_basis_points: parsed.protocol_fee_basis_points,
+                });
+            }
+        }
+    }
+
+    actions
+}
+
+/// 解析BuyEvent或SellEvent，数据可以带或不带emit_cpi!的指令前缀
+pub fn parse_swap_event(data: &[u8]) -> Option<SwapEvent> {
+    let data = data.strip_prefix(&EVENT_IX_TAG[..]).unwrap_or(data);
+    let (is_buy, payload) = if let Some(payload) = data.strip_prefix(&BUY_EVENT_DISCRIMINATOR[..]) {
+        (true, payload)
+    } else {
+        (false, data.strip_prefix(&SELL_EVENT_DISCRIMINATOR[..])?)
+    };
+    let head = SwapEventHead::deserialize(&mut &payload[..]).ok()?;
+
+    Some(SwapEvent {
+        pool: Pubkey::new_from_array(head.pool),
+        user: Pubkey::new_from_array(head.user),
+        is_buy,
+        base_amount: head.base_amount,
+        quote_amount: head.quote_amount,
+        pool_base_token_reserves: head.pool_base_token_reserves,
+        pool_quote_token_reserves: head.pool_quote_token_reserves,
+        lp_fee_basis_points: head.lp_fee_basis_points,
+        lp_fee: head.lp_fee,
+        protocol_fee_basis_points: head.protocol_fee_basis_points,
+        protocol_fee: head.protocol_fee,
+    })
+}
+
+/// 解析UpdateFeeConfigEvent，返回 (LP费率, 协议费率)，单位为基点
+pub fn parse_update_fee_config_event(data: &[u8]) -> Option<(u64, u64)> {
+    let data = data.strip_prefix(&EVENT_IX_TAG[..]).unwrap_or(data);
+    let payload = data.strip_prefix(&UPDATE_FEE_CONFIG_EVENT_DISCRIMINATOR[..])?;
+    let head = UpdateFeeConfigEventHead::deserialize(&mut &payload[..]).ok()?;
+    Some((head.lp_fee_basis_points, head.protocol_fee_basis_points))
+}
+
+/// Pump AMM程序的解码器
+pub struct PumpAmmDecoder;
+
+impl ProgramDecoder for PumpAmmDecoder {
+    fn program_id(&self) -> Pubkey {
+        PUMPAMM_PROGRAM_ID.parse().expect("合法的Pump AMM程序ID")
+    }
+
+    fn name(&self) -> &str {
+        "Pump AMM协议"
+    }
+
+    fn decode_instruction(&self, transaction: &VersionedTransaction, instruction_index: usize) -> Option<Decoded> {
+        let parsed = parse_pumpamm_instruction(transaction, instruction_index)?;
+        let mut decoded = Decoded::new(parsed.name, parsed.params);
+        if let Some(pool) = get_pool_from_instruction(transaction, instruction_index) {
+            decoded = decoded.with_field("池地址", pool);
+        }
+        if let Some((base_mint, quote_mint)) = get_token_mints_from_instruction(transaction, instruction_index) {
+            decoded = decoded.with_field("基础代币", base_mint).with_field("报价代币", quote_mint);
+        }
+        Some(decoded)
+    }
+
+    fn decode_event(&self, data: &[u8]) -> Option<Decoded> {
+        if let Some(event) = parse_swap_event(data) {
+            return Some(
+                Decoded::new(
+                    if event.is_buy { "BuyEvent" } else { "SellEvent" },
+                    format!(
+                        "基础代币={}, 报价代币={}, 交易前储备: 基础代币={}, 报价代币={}, LP手续费={}({}bps), 协议手续费={}({}bps)",
+                        event.base_amount,
+                        event.quote_amount,
+                        event.pool_base_token_reserves,
+                        event.pool_quote_token_reserves,
+                        event.lp_fee,
+                        event.lp_fee_basis_points,
+                        event.protocol_fee,
+                        event.protocol_fee_basis_points
+                    ),
+                )
+                .with_field("池地址", event.pool)
+                .with_field("用户", event.user),
+            );
+        }
+        let (lp_fee_basis_points, protocol_fee_basis_points) = parse_update_fee_config_event(data)?;
+        Some(Decoded::new(
+            "UpdateFeeConfigEvent",
+            format!("LP={}bps, 协议={}bps", lp_fee_basis_points, protocol_fee_basis_points),
+        ))
+    }
+
+    fn decode_account(&self, data: &[u8]) -> Option<Decoded> {
+        if data.starts_with(&GLOBAL_CONFIG_DISCRIMINATOR) {
+            let config = decode_global_config(data).ok()?;
+            return Some(
+                Decoded::new(
+                    "GlobalConfig",
+                    format!(
+                        "LP={}bps, 协议={}bps, 创建者={}bps, 禁用标志={:#07b}",
+                        config.fees.lp_fee_basis_points,
+                        config.fees.protocol_fee_basis_points,
+                        config.fees.coin_creator_fee_basis_points,
+                        config.disable_flags
+                    ),
+                )
+                .with_field("管理员", config.admin),
+            );
+        }
+        if data.starts_with(&POOL_DISCRIMINATOR) {
+            let pool = decode_pool(data).ok()?;
+            let decoded =
+                Decoded::new("Pool", format!("索引={}, bump={}, LP供应量={}", pool.index, pool.pool_bump, pool.lp_supply))
+                    .with_field("基础代币", pool.base_mint)
+                    .with_field("报价代币", pool.quote_mint)
+                    .with_field("LP Mint", pool.lp_mint)
+                    .with_field("创建者", pool.creator);
+            return Some(match pool.coin_creator {
+                Some(coin_creator) => decoded.with_field("代币创建者", coin_creator),
+                None => decoded,
+            });
+        }
+        None
+    }
+}
@@ -0,0 +1,354 @@
+use solana_sdk::{
+    pubkey::Pubkey,
+    transaction::VersionedTransaction,
+};
+use borsh::BorshDeserialize;
+use std::fmt;
+
+use super::ata_parser::AtaCreate;
+use super::instruction_account;
+use super::registry::{Decoded, ProgramDecoder};
+
+// Raydium AMM v4程序ID
+pub const RAYDIUM_AMM_PROGRAM_ID: &str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
+
+// Raydium AMM v4指令编号常量(首字节，非Anchor程序)
+pub const INITIALIZE2_IX: u8 = 1;
+pub const SWAP_BASE_IN_IX: u8 = 9;
+pub const SWAP_BASE_OUT_IX: u8 = 11;
+
+// 指令类型
+#[derive(Debug)]
+pub enum RaydiumAmmInstructionType {
+    Initialize2,
+    SwapBaseIn,
+    SwapBaseOut,
+    Unknown,
+}
+
+// 解析后的Raydium AMM v4指令
+#[derive(Debug)]
+pub struct ParsedRaydiumAmmInstruction {
+    #[allow(dead_code)]
+    pub instruction_type: RaydiumAmmInstructionType,
+    pub name: String,
+    pub params: String,
+}
+
+// 从initialize2指令中解出的新池
+#[derive(Debug, Clone)]
+pub struct RaydiumAmmPoolCreate {
+    pub pool: Pubkey,
+    pub creator: Pubkey,
+    pub coin_mint: Pubkey,
+    pub pc_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub open_time: u64,
+    pub init_coin_amount: u64,
+    pub init_pc_amount: u64,
+}
+
+// 从swap_base_in/swap_base_out指令中解出的交易
+//
+// 指令账户中没有mint，交易方向只能从用户代币账户推断；
+// 同一笔交易中创建了用户代币账户的ATA指令时，可以得到对应的mint。
+#[derive(Debug, Clone)]
+pub struct RaydiumAmmSwap {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub user_source: Pubkey,
+    pub user_destination: Pubkey,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub source_mint: Option<Pubkey>,
+    pub destination_mint: Option<Pubkey>,
+    // swap_base_in为精确输入，swap_base_out为精确输出
+    pub exact_in: bool,
+    // exact_in时为输入数量，否则为最大输入
+    pub amount_in: u64,
+    // exact_in时为最小输出，否则为输出数量
+    pub amount_out: u64,
+}
+
+// 按指令编号识别出的Raydium AMM v4操作
+#[derive(Debug, Clone)]
+pub enum RaydiumAmmAction {
+    CreatePool(RaydiumAmmPoolCreate),
+    Swap(RaydiumAmmSwap),
+}
+
+impl fmt::Display for RaydiumAmmAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RaydiumAmmAction::CreatePool(create) => write!(
+                f,
+                "创建AMM池: 池={}, coin={}, pc={}, LP={}, 金库={}/{}, 初始coin数量={}, 初始pc数量={}, 开放时间={}, 创建者={}",
+                create.pool,
+                create.coin_mint,
+                create.pc_mint,
+                create.lp_mint,
+                create.coin_vault,
+                create.pc_vault,
+                create.init_coin_amount,
+                create.init_pc_amount,
+                create.open_time,
+                create.creator
+            ),
+            RaydiumAmmAction::Swap(swap) => {
+                if swap.exact_in {
+                    write!(f, "兑换: 池={}, 用户={}, 输入数量={}, 最小输出数量={}", swap.pool, swap.user, swap.amount_in, swap.amount_out)?;
+                } else {
+                    write!(f, "兑换: 池={}, 用户={}, 最大输入数量={}, 输出数量={}", swap.pool, swap.user, swap.amount_in, swap.amount_out)?;
+                }
+                write!(
+                    f,
+                    ", 用户账户={} -> {}, 金库={}/{}",
+                    swap.user_source, swap.user_destination, swap.coin_vault, swap.pc_vault
+                )?;
+                if let Some(mint) = swap.source_mint {
+                    write!(f, ", 输入代币={}", mint)?;
+                }
+                if let Some(mint) = swap.destination_mint {
+                    write!(f, ", 输出代币={}", mint)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// Initialize2参数结构体
+#[derive(BorshDeserialize, Debug)]
+struct Initialize2Args {
+    #[allow(dead_code)]
+    nonce: u8,
+    open_time: u64,
+    init_pc_amount: u64,
+    init_coin_amount: u64,
+}
+
+// swap_base_in和swap_base_out的参数布局相同: 两个u64
+#[derive(BorshDeserialize, Debug)]
+struct SwapArgs {
+    first: u64,
+    second: u64,
+}
+
+/// 解析单个Raydium AMM v4指令
+pub fn parse_raydium_amm_instruction(transaction: &VersionedTransaction, instruction_index: usize) -> Option<ParsedRaydiumAmmInstruction> {
+    let message = &transaction.message;
+    let instruction = message.instructions().get(instruction_index)?;
+    let program_id = instruction.program_id(message.static_account_keys());
+
+    // 检查是否是Raydium AMM v4程序
+    if program_id.to_string() != RAYDIUM_AMM_PROGRAM_ID {
+        return None;
+    }
+
+    let Some((&tag, mut args)) = instruction.data.split_first() else {
+        return Some(ParsedRaydiumAmmInstruction {
+            instruction_type: RaydiumAmmInstructionType::Unknown,
+            name: "未知".to_string(),
+            params: "数据长度不足".to_string(),
+        });
+    };
+
+    let parsed = match tag {
+        INITIALIZE2_IX => ParsedRaydiumAmmInstruction {
+            instruction_type: RaydiumAmmInstructionType::Initialize2,
+            name: "Initialize2".to_string(),
+            params: match Initialize2Args::deserialize(&mut args) {
+                Ok(args) => format!(
+                    "创建池: 开放时间={}, 初始coin数量={}, 初始pc数量={}",
+                    args.open_time, args.init_coin_amount, args.init_pc_amount
+                ),
+                Err(_) => "创建池 (无法解析参数)".to_string(),
+            },
+        },
+        SWAP_BASE_IN_IX => ParsedRaydiumAmmInstruction {
+            instruction_type: RaydiumAmmInstructionType::SwapBaseIn,
+            name: "SwapBaseIn".to_string(),
+            params: match SwapArgs::deserialize(&mut args) {
+                Ok(args) => format!("精确输入兑换: 输入数量={}, 最小输出数量={}", args.first, args.second),
+                Err(_) => "精确输入兑换 (无法解析参数)".to_string(),
+            },
+        },
+        SWAP_BASE_OUT_IX => ParsedRaydiumAmmInstruction {
+            instruction_type: RaydiumAmmInstructionType::SwapBaseOut,
+            name: "SwapBaseOut".to_string(),
+            params: match SwapArgs::deserialize(&mut args) {
+                Ok(args) => format!("精确输出兑换: 最大输入数量={}, 输出数量={}", args.first, args.second),
+                Err(_) => "精确输出兑换 (无法解析参数)".to_string(),
+            },
+        },
+        _ => ParsedRaydiumAmmInstruction {
+            instruction_type: RaydiumAmmInstructionType::Unknown,
+            name: "未知Raydium AMM指令".to_string(),
+            params: format!("指令编号: {}", tag),
+        },
+    };
+    Some(parsed)
+}
+
+// swap账户中用户账户相对末尾的位置: source, destination, owner
+// 新版本省略了target_orders，共17个账户，旧版本为18个，用户账户始终在最后三个
+fn swap_user_accounts(static_keys: &[Pubkey], accounts: &[u8]) -> Option<(Pubkey, Pubkey, Pubkey)> {
+    let len = accounts.len();
+    if len < 17 {
+        return None;
+    }
+    Some((
+        instruction_account(static_keys, accounts, len - 3)?,
+        instruction_account(static_keys, accounts, len - 2)?,
+        instruction_account(static_keys, accounts, len - 1)?,
+    ))
+}
+
+// swap账户中两个池金库的位置，18个账户时多了target_orders
+fn swap_vault_positions(account_count: usize) -> (usize, usize) {
+    if account_count >= 18 { (5, 6) } else { (4, 5) }
+}
+
+/// 从指令中提取池地址，initialize2为第5个账户，swap为第2个账户
+pub fn get_pool_from_instruction(transaction: &VersionedTransaction, instruction_index: usize) -> Option<Pubkey> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+    let instruction = message.instructions().get(instruction_index)?;
+    if instruction.program_id(static_keys).to_string() != RAYDIUM_AMM_PROGRAM_ID {
+        return None;
+    }
+
+    match instruction.data.first()? {
+        &INITIALIZE2_IX => instruction_account(static_keys, &instruction.accounts, 4),
+        &SWAP_BASE_IN_IX | &SWAP_BASE_OUT_IX => instruction_account(static_keys, &instruction.accounts, 1),
+        _ => None,
+    }
+}
+
+/// 从initialize2指令中提取coin和pc代币地址，swap指令的账户中没有mint
+pub fn get_token_mints_from_instruction(transaction: &VersionedTransaction, instruction_index: usize) -> Option<(Pubkey, Pubkey)> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+    let instruction = message.instructions().get(instruction_index)?;
+    if instruction.program_id(static_keys).to_string() != RAYDIUM_AMM_PROGRAM_ID
+        || instruction.data.first() != Some(&INITIALIZE2_IX)
+    {
+        return None;
+    }
+
+    Some((
+        instruction_account(static_keys, &instruction.accounts, 8)?,
+        instruction_account(static_keys, &instruction.accounts, 9)?,
+    ))
+}
+
+/// 按指令编号解析交易中的Raydium AMM v4指令
+///
+/// 账户顺序: initialize2为 tokenProgram, ataProgram, systemProgram, rent, amm(4), authority, openOrders,
+/// lpMint(7), coinMint(8), pcMint(9), coinVault(10), pcVault(11), ..., userWallet(17)；
+/// swap为 tokenProgram, amm(1), authority, openOrders, [targetOrders], coinVault, pcVault, serum账户...,
+/// userSource, userDestination, userOwner(最后三个)。
+/// `ata_creates` 为同一笔交易中的ATA创建指令，用于推断swap两侧的mint。
+pub fn decode_raydium_amm_actions(transaction: &VersionedTransaction, ata_creates: &[AtaCreate]) -> Vec<RaydiumAmmAction> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+    let mut actions = Vec::new();
+    let mint_of = |token_account: &Pubkey| {
+        ata_creates
+            .iter()
+            .find(|create| create.associated_account == *token_account)
+            .map(|create| create.mint)
+    };
+
+    for instruction in message.instructions() {
+        let program_id = instruction.program_id(static_keys);
+        if program_id.to_string() != RAYDIUM_AMM_PROGRAM_ID {
+            continue;
+        }
+        let Some((&tag, mut args)) = instruction.data.split_first() else {
+            continue;
+        };
+        let accounts = &instruction.accounts;
+        let account = |position| instruction_account(static_keys, accounts, position);
+
+        match tag {
+            INITIALIZE2_IX => {
+                let (Some(pool), Some(lp_mint), Some(coin_mint), Some(pc_mint), Some(coin_vault), Some(pc_vault), Some(creator)) =
+                    (account(4), account(7), account(8), account(9), account(10), account(11), account(17))
+                else {
+                    continue;
+                };
+                let Ok(args) = Initialize2Args::deserialize(&mut args) else {
+                    continue;
+                };
+                actions.push(RaydiumAmmAction::CreatePool(RaydiumAmmPoolCreate {
+                    pool,
+                    creator,
+                    coin_mint,
+                    pc_mint,
+                    lp_mint,
+                    coin_vault,
+                    pc_vault,
+                    open_time: args.open_time,
+                    init_coin_amount: args.init_coin_amount,
+                    init_pc_amount: args.init_pc_amount,
+                }));
+            }
+            SWAP_BASE_IN_IX | SWAP_BASE_OUT_IX => {
+                let Some((user_source, user_destination, user)) = swap_user_accounts(static_keys, accounts) else {
+                    continue;
+                };
+                let (coin_position, pc_position) = swap_vault_positions(accounts.len());
+                let (Some(pool), Some(coin_vault), Some(pc_vault)) = (account(1), account(coin_position), account(pc_position)) else {
+                    continue;
+                };
+                let Ok(args) = SwapArgs::deserialize(&mut args) else {
+                    continue;
+                };
+                actions.push(RaydiumAmmAction::Swap(RaydiumAmmSwap {
+                    pool,
+                    user,
+                    user_source,
+                    user_destination,
+                    coin_vault,
+                    pc_vault,
+                    source_mint: mint_of(&user_source),
+                    destination_mint: mint_of(&user_destination),
+                    exact_in: tag == SWAP_BASE_IN_IX,
+                    amount_in: args.first,
+                    amount_out: args.second,
+                }));
+            }
+            _ => {}
+        }
+    }
+
+    actions
+}
+
+/// Raydium AMM v4程序的解码器
+pub struct RaydiumAmmDecoder;
+
+impl ProgramDecoder for RaydiumAmmDecoder {
+    fn program_id(&self) -> Pubkey {
+        RAYDIUM_AMM_PROGRAM_ID.parse().expect("合法的Raydium AMM程序ID")
+    }
+
+    fn name(&self) -> &str {
+        "Raydium AMM v4"
+    }
+
+    fn decode_instruction(&self, transaction: &VersionedTransaction, instruction_index: usize) -> Option<Decoded> {
+        let parsed = parse_raydium_amm_instruction(transaction, instruction_index)?;
+        let mut decoded = Decoded::new(parsed.name, parsed.params);
+        if let Some(pool) = get_pool_from_instruction(transaction, instruction_index) {
+            decoded = decoded.with_field("池地址", pool);
+        }
+        if let Some((coin_mint, pc_mint)) = get_token_mints_from_instruction(transaction, instruction_index) {
+            decoded = decoded.with_field("coin代币", coin_mint).with_field("pc代币", pc_mint);
+        }
+        Some(decoded)
+    }
+}
@@ -0,0 +1,324 @@
+use solana_sdk::{
+    pubkey::Pubkey,
+    transaction::VersionedTransaction,
+};
+use borsh::BorshDeserialize;
+use std::fmt;
+
+use super::instruction_account;
+use super::registry::{Decoded, ProgramDecoder};
+
+// Raydium CPMM程序ID
+pub const RAYDIUM_CPMM_PROGRAM_ID: &str = "CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C";
+
+// Raydium CPMM指令discriminator (sha256("global:<指令名>")前8字节)
+pub const INITIALIZE_IX: [u8; 8] = [175, 175, 109, 31, 13, 152, 155, 237];
+pub const SWAP_BASE_INPUT_IX: [u8; 8] = [143, 190, 90, 218, 196, 30, 51, 222];
+pub const SWAP_BASE_OUTPUT_IX: [u8; 8] = [55, 217, 98, 86, 163, 74, 180, 173];
+
+// 指令类型
+#[derive(Debug)]
+pub enum RaydiumCpmmInstructionType {
+    Initialize,
+    SwapBaseInput,
+    SwapBaseOutput,
+    Unknown,
+}
+
+// 解析后的Raydium CPMM指令
+#[derive(Debug)]
+pub struct ParsedRaydiumCpmmInstruction {
+    #[allow(dead_code)]
+    pub instruction_type: RaydiumCpmmInstructionType,
+    pub name: String,
+    pub params: String,
+}
+
+// 从initialize指令中解出的新池
+#[derive(Debug, Clone)]
+pub struct RaydiumCpmmPoolCreate {
+    pub pool: Pubkey,
+    pub creator: Pubkey,
+    pub token_0_mint: Pubkey,
+    pub token_1_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub token_0_vault: Pubkey,
+    pub token_1_vault: Pubkey,
+    pub init_amount_0: u64,
+    pub init_amount_1: u64,
+    pub open_time: u64,
+}
+
+// 从swap_base_input/swap_base_output指令中解出的交易
+#[derive(Debug, Clone)]
+pub struct RaydiumCpmmSwap {
+    pub pool: Pubkey,
+    pub user: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub input_vault: Pubkey,
+    pub output_vault: Pubkey,
+    // swap_base_input为精确输入，swap_base_output为精确输出
+    pub exact_in: bool,
+    // exact_in时为输入数量，否则为最大输入
+    pub amount_in: u64,
+    // exact_in时为最小输出，否则为输出数量
+    pub amount_out: u64,
+}
+
+// 按Anchor discriminator识别出的Raydium CPMM操作
+#[derive(Debug, Clone)]
+pub enum RaydiumCpmmAction {
+    CreatePool(RaydiumCpmmPoolCreate),
+    Swap(RaydiumCpmmSwap),
+}
+
+impl fmt::Display for RaydiumCpmmAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RaydiumCpmmAction::CreatePool(create) => write!(
+                f,
+                "创建CPMM池: 池={}, token0={}, token1={}, LP={}, 金库={}/{}, 初始token0数量={}, 初始token1数量={}, 开放时间={}, 创建者={}",
+                create.pool,
+                create.token_0_mint,
+                create.token_1_mint,
+                create.lp_mint,
+                create.token_0_vault,
+                create.token_1_vault,
+                create.init_amount_0,
+                create.init_amount_1,
+                create.open_time,
+                create.creator
+            ),
+            RaydiumCpmmAction::Swap(swap) if swap.exact_in => write!(
+                f,
+                "兑换: 池={}, 用户={}, {} -> {}, 输入数量={}, 最小输出数量={}, 金库={} -> {}",
+                swap.pool, swap.user, swap.input_mint, swap.output_mint, swap.amount_in, swap.amount_out, swap.input_vault, swap.output_vault
+            ),
+            RaydiumCpmmAction::Swap(swap) => write!(
+                f,
+                "兑换: 池={}, 用户={}, {} -> {}, 最大输入数量={}, 输出数量={}, 金库={} -> {}",
+                swap.pool, swap.user, swap.input_mint, swap.output_mint, swap.amount_in, swap.amount_out, swap.input_vault, swap.output_vault
+            ),
+        }
+    }
+}
+
+// Initialize参数结构体
+#[derive(BorshDeserialize, Debug)]
+struct InitializeArgs {
+    init_amount_0: u64,
+    init_amount_1: u64,
+    open_time: u64,
+}
+
+// swap_base_input为 amount_in, minimum_amount_out；swap_base_output为 max_amount_in, amount_out
+#[derive(BorshDeserialize, Debug)]
+struct SwapArgs {
+    first: u64,
+    second: u64,
+}
+
+/// 检查指令是否匹配给定的discriminator
+fn is_instruction_match(data: &[u8], discriminator: &[u8; 8]) -> bool {
+    data.len() >= 8 && data[..8] == discriminator[..]
+}
+
+/// 解析单个Raydium CPMM指令
+pub fn parse_raydium_cpmm_instruction(transaction: &VersionedTransaction, instruction_index: usize) -> Option<ParsedRaydiumCpmmInstruction> {
+    let message = &transaction.message;
+    let instruction = message.instructions().get(instruction_index)?;
+    let program_id = instruction.program_id(message.static_account_keys());
+
+    // 检查是否是Raydium CPMM程序
+    if program_id.to_string() != RAYDIUM_CPMM_PROGRAM_ID {
+        return None;
+    }
+
+    let data = &instruction.data;
+    if data.len() < 8 {
+        return Some(ParsedRaydiumCpmmInstruction {
+            instruction_type: RaydiumCpmmInstructionType::Unknown,
+            name: "未知".to_string(),
+            params: "数据长度不足".to_string(),
+        });
+    }
+    let mut args = &data[8..];
+
+    let parsed = if is_instruction_match(data, &INITIALIZE_IX) {
+        ParsedRaydiumCpmmInstruction {
+            instruction_type: RaydiumCpmmInstructionType::Initialize,
+            name: "Initialize".to_string(),
+            params: match InitializeArgs::deserialize(&mut args) {
+                Ok(args) => format!(
+                    "创建池: 初始token0数量={}, 初始token1数量={}, 开放时间={}",
+                    args.init_amount_0, args.init_amount_1, args.open_time
+                ),
+                Err(_) => "创建池 (无法解析参数)".to_string(),
+            },
+        }
+    } else if is_instruction_match(data, &SWAP_BASE_INPUT_IX) {
+        ParsedRaydiumCpmmInstruction {
+            instruction_type: RaydiumCpmmInstructionType::SwapBaseInput,
+            name: "SwapBaseInput".to_string(),
+            params: match SwapArgs::deserialize(&mut args) {
+                Ok(args) => format!("精确输入兑换: 输入数量={}, 最小输出数量={}", args.first, args.second),
+                Err(_) => "精确输入兑换 (无法解析参数)".to_string(),
+            },
+        }
+    } else if is_instruction_match(data, &SWAP_BASE_OUTPUT_IX) {
+        ParsedRaydiumCpmmInstruction {
+            instruction_type: RaydiumCpmmInstructionType::SwapBaseOutput,
+            name: "SwapBaseOutput".to_string(),
+            params: match SwapArgs::deserialize(&mut args) {
+                Ok(args) => format!("精确输出兑换: 最大输入数量={}, 输出数量={}", args.first, args.second),
+                Err(_) => "精确输出兑换 (无法解析参数)".to_string(),
+            },
+        }
+    } else {
+        ParsedRaydiumCpmmInstruction {
+            instruction_type: RaydiumCpmmInstructionType::Unknown,
+            name: "未知Raydium CPMM指令".to_string(),
+            params: format!("未识别的discriminator: {:?}", &data[..8]),
+        }
+    };
+    Some(parsed)
+}
+
+/// 从指令中提取池地址，initialize和swap中都是第4个账户
+pub fn get_pool_from_instruction(transaction: &VersionedTransaction, instruction_index: usize) -> Option<Pubkey> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+    let instruction = message.instructions().get(instruction_index)?;
+    if instruction.program_id(static_keys).to_string() != RAYDIUM_CPMM_PROGRAM_ID {
+        return None;
+    }
+
+    let data = &instruction.data;
+    if is_instruction_match(data, &INITIALIZE_IX)
+        || is_instruction_match(data, &SWAP_BASE_INPUT_IX)
+        || is_instruction_match(data, &SWAP_BASE_OUTPUT_IX)
+    {
+        instruction_account(static_keys, &instruction.accounts, 3)
+    } else {
+        None
+    }
+}
+
+/// 从指令中提取两个代币地址: initialize为 (token0, token1)，swap为 (输入, 输出)
+pub fn get_token_mints_from_instruction(transaction: &VersionedTransaction, instruction_index: usize) -> Option<(Pubkey, Pubkey)> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+    let instruction = message.instructions().get(instruction_index)?;
+    if instruction.program_id(static_keys).to_string() != RAYDIUM_CPMM_PROGRAM_ID {
+        return None;
+    }
+
+    let data = &instruction.data;
+    let accounts = &instruction.accounts;
+    let (first, second) = if is_instruction_match(data, &INITIALIZE_IX) {
+        (4, 5)
+    } else if is_instruction_match(data, &SWAP_BASE_INPUT_IX) || is_instruction_match(data, &SWAP_BASE_OUTPUT_IX) {
+        (10, 11)
+    } else {
+        return None;
+    };
+    Some((
+        instruction_account(static_keys, accounts, first)?,
+        instruction_account(static_keys, accounts, second)?,
+    ))
+}
+
+/// 按discriminator解析交易中的Raydium CPMM指令
+///
+/// 账户顺序来自IDL: initialize为 creator, ammConfig, authority, poolState(3), token0Mint(4), token1Mint(5),
+/// lpMint(6), ..., token0Vault(10), token1Vault(11)；
+/// swap为 payer, authority, ammConfig, poolState(3), inputTokenAccount, outputTokenAccount,
+/// inputVault(6), outputVault(7), inputTokenProgram, outputTokenProgram, inputMint(10), outputMint(11)。
+pub fn decode_raydium_cpmm_actions(transaction: &VersionedTransaction) -> Vec<RaydiumCpmmAction> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+    let mut actions = Vec::new();
+
+    for instruction in message.instructions() {
+        let program_id = instruction.program_id(static_keys);
+        if program_id.to_string() != RAYDIUM_CPMM_PROGRAM_ID || instruction.data.len() < 8 {
+            continue;
+        }
+
+        let data = &instruction.data;
+        let mut args = &data[8..];
+        let accounts = &instruction.accounts;
+        let account = |position| instruction_account(static_keys, accounts, position);
+
+        if is_instruction_match(data, &INITIALIZE_IX) {
+            let (Some(creator), Some(pool), Some(token_0_mint), Some(token_1_mint), Some(lp_mint), Some(token_0_vault), Some(token_1_vault)) =
+                (account(0), account(3), account(4), account(5), account(6), account(10), account(11))
+            else {
+                continue;
+            };
+            let Ok(args) = InitializeArgs::deserialize(&mut args) else {
+                continue;
+            };
+            actions.push(RaydiumCpmmAction::CreatePool(RaydiumCpmmPoolCreate {
+                pool,
+                creator,
+                token_0_mint,
+                token_1_mint,
+                lp_mint,
+                token_0_vault,
+                token_1_vault,
+                init_amount_0: args.init_amount_0,
+                init_amount_1: args.init_amount_1,
+                open_time: args.open_time,
+            }));
+        } else if is_instruction_match(data, &SWAP_BASE_INPUT_IX) || is_instruction_match(data, &SWAP_BASE_OUTPUT_IX) {
+            let (Some(user), Some(pool), Some(input_vault), Some(output_vault), Some(input_mint), Some(output_mint)) =
+                (account(0), account(3), account(6), account(7), account(10), account(11))
+            else {
+                continue;
+            };
+            let Ok(args) = SwapArgs::deserialize(&mut args) else {
+                continue;
+            };
+            actions.push(RaydiumCpmmAction::Swap(RaydiumCpmmSwap {
+                pool,
+                user,
+                input_mint,
+                output_mint,
+                input_vault,
+                output_vault,
+                exact_in: is_instruction_match(data, &SWAP_BASE_INPUT_IX),
+                amount_in: args.first,
+                amount_out: args.second,
+            }));
+        }
+    }
+
+    actions
+}
+
+/// Raydium CPMM程序的解码器
+pub struct RaydiumCpmmDecoder;
+
+impl ProgramDecoder for RaydiumCpmmDecoder {
+    fn program_id(&self) -> Pubkey {
+        RAYDIUM_CPMM_PROGRAM_ID.parse().expect("合法的Raydium CPMM程序ID")
+    }
+
+    fn name(&self) -> &str {
+        "Raydium CPMM"
+    }
+
+    fn decode_instruction(&self, transaction: &VersionedTransaction, instruction_index: usize) -> Option<Decoded> {
+        let parsed = parse_raydium_cpmm_instruction(transaction, instruction_index)?;
+        let mut decoded = Decoded::new(parsed.name, parsed.params);
+        if let Some(pool) = get_pool_from_instruction(transaction, instruction_index) {
+            decoded = decoded.with_field("池地址", pool);
+        }
+        if let Some((first_mint, second_mint)) = get_token_mints_from_instruction(transaction, instruction_index) {
+            decoded = decoded.with_field("代币A", first_mint).with_field("代币B", second_mint);
+        }
+        Some(decoded)
+    }
+}
@@ -0,0 +1,268 @@
+use solana_sdk::{pubkey::Pubkey, transaction::VersionedTransaction};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use super::ata_parser::AtaDecoder;
+use super::compute_budget::ComputeBudgetDecoder;
+use super::jupiter_parser::JupiterDecoder;
+use super::meteora_dlmm_parser::MeteoraDlmmDecoder;
+use super::orca_whirlpool_parser::WhirlpoolDecoder;
+use super::pump_parser::PumpDecoder;
+use super::pumpamm_parser::PumpAmmDecoder;
+use super::raydium_amm_parser::RaydiumAmmDecoder;
+use super::raydium_cpmm_parser::RaydiumCpmmDecoder;
+use super::swap::SwapIntent;
+use super::system_parser::SystemDecoder;
+use super::token_parser::{TokenDecoder, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID};
+
+/// 解码器输出的统一展示模型，指令、事件和账户共用
+#[derive(Debug, Clone, Default)]
+pub struct Decoded {
+    // 指令/事件/账户类型
+    pub name: String,
+    pub params: String,
+    // 额外展示的字段，如代币Mint、池地址
+    pub fields: Vec<(String, String)>,
+}
+
+impl Decoded {
+    pub fn new(name: impl Into<String>, params: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            params: params.into(),
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn with_field(mut self, label: impl Into<String>, value: impl ToString) -> Self {
+        self.fields.push((label.into(), value.to_string()));
+        self
+    }
+}
+
+/// 单个链上程序的解码器
+///
+/// 实现这个trait并注册到 `DecoderRegistry`，交易详情中该程序的指令和快照中该程序的账户就会按它输出，
+/// `decode_swaps` 返回的兑换意图和内置解析器的一样进入状态引擎、钱包跟踪和跟单规则，
+/// 不需要修改内置的分派逻辑。
+pub trait ProgramDecoder: Send + Sync {
+    fn program_id(&self) -> Pubkey;
+
+    // 输出中显示的程序名
+    fn name(&self) -> &str;
+
+    /// 解码交易中的第 `instruction_index` 条顶层指令，调用方保证它属于本程序
+    fn decode_instruction(&self, transaction: &VersionedTransaction, instruction_index: usize) -> Option<Decoded>;
+
+    /// 从第 `instruction_index` 条顶层指令中提取兑换意图，调用方保证它属于本程序
+    ///
+    /// 内置程序的兑换已由内置解析器生成，内置解码器不实现这个方法；替换内置解码器时也不要重复返回。
+    fn decode_swaps(&self, _transaction: &VersionedTransaction, _instruction_index: usize) -> Vec<SwapIntent> {
+        Vec::new()
+    }
+
+    /// 解码程序通过日志或自调用发出的事件数据
+    fn decode_event(&self, _data: &[u8]) -> Option<Decoded> {
+        None
+    }
+
+    /// 解码属于本程序的账户数据
+    fn decode_account(&self, _data: &[u8]) -> Option<Decoded> {
+        None
+    }
+}
+
+/// 按程序ID索引的解码器注册表，管线的worker共享同一个注册表
+#[derive(Clone, Default)]
+pub struct DecoderRegistry {
+    decoders: HashMap<Pubkey, Arc<dyn ProgramDecoder>>,
+    // 通过register加入的程序ID，惰性过滤时管线需要额外扫描这些程序
+    custom: HashSet<Pubkey>,
+}
+
+impl DecoderRegistry {
+    /// 注册内置的计算预算、System、Token、Token-2022、ATA、Pump、Pump AMM、Raydium、Meteora DLMM、Orca Whirlpool和Jupiter解码器
+    pub fn with_builtin() -> Self {
+        let mut registry = Self::default();
+        registry.insert(ComputeBudgetDecoder);
+        registry.insert(SystemDecoder);
+        registry.insert(TokenDecoder::new(TOKEN_PROGRAM_ID, "Token"));
+        registry.insert(TokenDecoder::new(TOKEN_2022_PROGRAM_ID, "Token-2022"));
+        registry.insert(AtaDecoder);
+        registry.insert(PumpDecoder);
+        registry.insert(PumpAmmDecoder);
+        registry.insert(RaydiumAmmDecoder);
+        registry.insert(RaydiumCpmmDecoder);
+        registry.insert(MeteoraDlmmDecoder);
+        registry.insert(WhirlpoolDecoder);
+        registry.insert(JupiterDecoder);
+        registry
+    }
+
+    /// 注册解码器，同一程序ID已有解码器时替换，返回被替换的解码器
+    pub fn register(&mut self, decoder: impl ProgramDecoder + 'static) -> Option<Arc<dyn ProgramDecoder>> {
+        self.custom.insert(decoder.program_id());
+        self.insert(decoder)
+    }
+
+    fn insert(&mut self, decoder: impl ProgramDecoder + 'static) -> Option<Arc<dyn ProgramDecoder>> {
+        self.decoders.insert(decoder.program_id(), Arc::new(decoder))
+    }
+
+    /// 调用方注册的（非内置）解码器的程序ID
+    pub fn custom_program_ids(&self) -> impl Iterator<Item = &Pubkey> {
+        self.custom.iter()
+    }
+
+    pub fn get(&self, program_id: &Pubkey) -> Option<&dyn ProgramDecoder> {
+        self.decoders.get(program_id).map(|decoder| decoder.as_ref())
+    }
+
+    /// 按顶层指令的程序ID分派，汇总注册的解码器提取的兑换意图
+    pub fn decode_swaps(&self, transaction: &VersionedTransaction) -> Vec<SwapIntent> {
+        let static_keys = transaction.message.static_account_keys();
+        let mut swaps = Vec::new();
+        for (index, instruction) in transaction.message.instructions().iter().enumerate() {
+            let Some(program_id) = static_keys.get(instruction.program_id_index as usize) else {
+                continue;
+            };
+            if let Some(decoder) = self.get(program_id) {
+                swaps.extend(decoder.decode_swaps(transaction, index));
+            }
+        }
+        swaps
+    }
+
+    // 事件只出现在内部指令中，shredstream中只有带内部指令的录制数据或自调用的顶层指令才能解码
+    pub fn decode_event(&self, program_id: &Pubkey, data: &[u8]) -> Option<Decoded> {
+        self.get(program_id)?.decode_event(data)
+    }
+
+    pub fn decode_account(&self, owner: &Pubkey, data: &[u8]) -> Option<Decoded> {
+        self.get(owner)?.decode_account(data)
+    }
+}
+
+impl std::fmt::Debug for DecoderRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.decoders.iter().map(|(program_id, decoder)| (program_id, decoder.name())))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::latency::{ReceiveStamp, SlotClock};
+    use crate::transaction::pump_parser::{EVENT_IX_TAG, PUMP_PROGRAM_ID, TRADE_EVENT_DISCRIMINATOR};
+    use crate::transaction::swap::{SwapSide, Venue, WSOL_MINT};
+    use crate::transaction::DecodedTransaction;
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+    use solana_sdk::message::{Message, VersionedMessage};
+    use solana_sdk::signature::Signature;
+
+    // 第一个账户是池，第二个是交易者，数据是8字节的SOL输入
+    struct TestDexDecoder {
+        program_id: Pubkey,
+        mint: Pubkey,
+    }
+
+    impl ProgramDecoder for TestDexDecoder {
+        fn program_id(&self) -> Pubkey {
+            self.program_id
+        }
+
+        fn name(&self) -> &str {
+            "TestDex"
+        }
+
+        fn decode_instruction(&self, _transaction: &VersionedTransaction, _instruction_index: usize) -> Option<Decoded> {
+            Some(Decoded::new("swap", ""))
+        }
+
+        fn decode_swaps(&self, transaction: &VersionedTransaction, instruction_index: usize) -> Vec<SwapIntent> {
+            let static_keys = transaction.message.static_account_keys();
+            let instruction = &transaction.message.instructions()[instruction_index];
+            let amount = u64::from_le_bytes(instruction.data[..8].try_into().expect("8字节数量"));
+            vec![SwapIntent::new(
+                Venue::Program(self.program_id),
+                Some(static_keys[instruction.accounts[0] as usize]),
+                static_keys[instruction.accounts[1] as usize],
+                (Some(WSOL_MINT.parse().expect("合法的WSOL Mint")), Some(self.mint)),
+                true,
+                amount,
+                0,
+            )]
+        }
+    }
+
+    #[test]
+    fn custom_decoder_swaps_reach_decoded_transaction() {
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let pool = Pubkey::new_unique();
+        let trader = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &1_000_000u64.to_le_bytes(),
+            vec![AccountMeta::new(pool, false), AccountMeta::new(trader, true)],
+        );
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::new_unique()],
+            message: VersionedMessage::Legacy(Message::new(&[instruction], Some(&trader))),
+        };
+
+        let builtin_registry = DecoderRegistry::with_builtin();
+        assert_eq!(builtin_registry.custom_program_ids().count(), 0);
+        let builtin = DecodedTransaction::decode(&transaction, 1, false, &[], &builtin_registry);
+        assert!(!builtin.has_actions());
+
+        let mut registry = DecoderRegistry::with_builtin();
+        assert!(registry.register(TestDexDecoder { program_id, mint }).is_none());
+        assert_eq!(registry.custom_program_ids().collect::<Vec<_>>(), vec![&program_id]);
+        let decoded = DecodedTransaction::decode(&transaction, 1, false, &[], &registry);
+        assert!(decoded.has_actions());
+        let intents = decoded.swap_intents();
+        assert_eq!(intents.len(), 1);
+        assert_eq!(intents[0].venue, Venue::Program(program_id));
+        assert_eq!(intents[0].pool, Some(pool));
+        assert_eq!(intents[0].trader, trader);
+        assert_eq!(intents[0].side, Some(SwapSide::Buy));
+        assert_eq!(intents[0].token_mint(), Some(mint));
+        assert_eq!(intents[0].sol_amount(), Some(1_000_000));
+        assert_eq!(intents[0].latency, None);
+
+        let latency = SlotClock::new(std::time::Duration::from_millis(400)).observe(1, ReceiveStamp::now());
+        let stamped = decoded.with_latency(latency).swap_intents();
+        assert_eq!(stamped[0].latency, Some(latency));
+    }
+
+    #[test]
+    fn builtin_decoders_render_pump_events() {
+        let registry = DecoderRegistry::with_builtin();
+        let pump: Pubkey = PUMP_PROGRAM_ID.parse().expect("合法的Pump程序ID");
+        let mint = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+
+        let mut data = EVENT_IX_TAG.to_vec();
+        data.extend_from_slice(&TRADE_EVENT_DISCRIMINATOR);
+        data.extend_from_slice(mint.as_ref());
+        data.extend_from_slice(&500_000_000u64.to_le_bytes());
+        data.extend_from_slice(&1_000_000u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(user.as_ref());
+        data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+        data.extend_from_slice(&30_500_000_000u64.to_le_bytes());
+        data.extend_from_slice(&1_072_000_000_000_000u64.to_le_bytes());
+        // 之后新增的字段按前缀忽略
+        data.extend_from_slice(&[0u8; 64]);
+
+        let decoded = registry.decode_event(&pump, &data).expect("TradeEvent");
+        assert_eq!(decoded.name, "TradeEvent");
+        assert!(decoded.params.starts_with("买入: 代币数量=1000000"));
+        assert_eq!(decoded.fields[0], ("代币Mint".to_string(), mint.to_string()));
+        assert_eq!(decoded.fields[1], ("用户".to_string(), user.to_string()));
+        assert!(registry.decode_event(&Pubkey::new_unique(), &data).is_none());
+    }
+}
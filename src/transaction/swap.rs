@@ -0,0 +1,331 @@
+use solana_sdk::pubkey::Pubkey;
+use std::fmt;
+
+use super::jupiter_parser::JupiterRoute;
+use super::meteora_dlmm_parser::DlmmSwap;
+use super::orca_whirlpool_parser::WhirlpoolSwap;
+use super::pump_parser::{PumpAction, PumpTrade};
+use super::pumpamm_parser::{PoolSwap, PumpAmmAction};
+use super::raydium_amm_parser::{RaydiumAmmAction, RaydiumAmmSwap};
+use super::raydium_cpmm_parser::{RaydiumCpmmAction, RaydiumCpmmSwap};
+use super::DecodedTransaction;
+use crate::latency::EntryLatency;
+
+// Wrapped SOL的Mint，Pump曲线交易的SOL一侧也记为这个Mint
+pub const WSOL_MINT: &str = "So11111111111111111111111111111111111111112";
+
+// 兑换发生的场所
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Venue {
+    Pump,
+    PumpAmm,
+    RaydiumAmm,
+    RaydiumCpmm,
+    MeteoraDlmm,
+    OrcaWhirlpool,
+    // 经Jupiter路由，可能跨多个池
+    Jupiter,
+    // 注册表中自定义解码器对应的程序
+    Program(Pubkey),
+}
+
+impl fmt::Display for Venue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Venue::Program(program_id) => return write!(f, "程序 {}", program_id),
+            Venue::Pump => "Pump",
+            Venue::PumpAmm => "Pump AMM",
+            Venue::RaydiumAmm => "Raydium AMM v4",
+            Venue::RaydiumCpmm => "Raydium CPMM",
+            Venue::MeteoraDlmm => "Meteora DLMM",
+            Venue::OrcaWhirlpool => "Orca Whirlpool",
+            Venue::Jupiter => "Jupiter",
+        })
+    }
+}
+
+// 相对SOL的买卖方向: 用SOL换代币为买入，代币换SOL为卖出
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapSide {
+    Buy,
+    Sell,
+}
+
+impl fmt::Display for SwapSide {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SwapSide::Buy => "买入",
+            SwapSide::Sell => "卖出",
+        })
+    }
+}
+
+/// 各DEX兑换指令统一后的交易意图
+///
+/// 指令参数只给出一侧的精确数量和另一侧的限价，成交数量要看交易结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapIntent {
+    pub venue: Venue,
+    // Pump为曲线账户，Jupiter路由没有单一的池
+    pub pool: Option<Pubkey>,
+    pub trader: Pubkey,
+    // 两侧都不是SOL或Mint未知时为None
+    pub side: Option<SwapSide>,
+    pub mint_in: Option<Pubkey>,
+    pub mint_out: Option<Pubkey>,
+    // 为真时amount是精确输入、limit_amount是最小输出，否则amount是精确输出、limit_amount是最大输入
+    pub exact_in: bool,
+    pub amount: u64,
+    pub limit_amount: u64,
+    // 所在Entry批次的接收延迟，由DecodedTransaction::swap_intents填入
+    pub latency: Option<EntryLatency>,
+}
+
+impl SwapIntent {
+    /// 按两侧Mint推断买卖方向，自定义解码器用它构造兑换意图
+    pub fn new(
+        venue: Venue,
+        pool: Option<Pubkey>,
+        trader: Pubkey,
+        (mint_in, mint_out): (Option<Pubkey>, Option<Pubkey>),
+        exact_in: bool,
+        amount: u64,
+        limit_amount: u64,
+    ) -> Self {
+        Self {
+            venue,
+            pool,
+            trader,
+            side: side_of(mint_in, mint_out),
+            mint_in,
+            mint_out,
+            exact_in,
+            amount,
+            limit_amount,
+            latency: None,
+        }
+    }
+
+    /// 买入或卖出的非SOL代币
+    pub fn token_mint(&self) -> Option<Pubkey> {
+        match self.side? {
+            SwapSide::Buy => self.mint_out,
+            SwapSide::Sell => self.mint_in,
+        }
+    }
+
+    /// SOL一侧的数量(lamports): 买入时为输入的精确值或上限，卖出时为输出的精确值或下限
+    pub fn sol_amount(&self) -> Option<u64> {
+        match (self.side?, self.exact_in) {
+            (SwapSide::Buy, true) | (SwapSide::Sell, false) => Some(self.amount),
+            (SwapSide::Buy, false) | (SwapSide::Sell, true) => Some(self.limit_amount),
+        }
+    }
+
+    /// `sol_amount` 是否为限价(买入的最大输入或卖出的最小输出)而不是精确值
+    pub fn sol_amount_is_limit(&self) -> bool {
+        self.exact_in == (self.side == Some(SwapSide::Sell))
+    }
+
+    /// 代币一侧的数量: 买入时为输出的精确值或下限，卖出时为输入的精确值或上限
+    pub fn token_amount(&self) -> Option<u64> {
+        match (self.side?, self.exact_in) {
+            (SwapSide::Buy, false) | (SwapSide::Sell, true) => Some(self.amount),
+            (SwapSide::Buy, true) | (SwapSide::Sell, false) => Some(self.limit_amount),
+        }
+    }
+}
+
+impl fmt::Display for SwapIntent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mint = |mint: Option<Pubkey>| mint.map(|mint| mint.to_string()).unwrap_or_else(|| "未知".to_string());
+        write!(f, "{}", self.venue)?;
+        if let Some(side) = self.side {
+            write!(f, " {}", side)?;
+        }
+        write!(f, ": 交易者={}, {} -> {}", self.trader, mint(self.mint_in), mint(self.mint_out))?;
+        if let Some(pool) = self.pool {
+            write!(f, ", 池={}", pool)?;
+        }
+        if self.exact_in {
+            write!(f, ", 输入数量={}, 最小输出数量={}", self.amount, self.limit_amount)
+        } else {
+            write!(f, ", 输出数量={}, 最大输入数量={}", self.amount, self.limit_amount)
+        }
+    }
+}
+
+fn wsol_mint() -> Pubkey {
+    WSOL_MINT.parse().expect("合法的WSOL Mint")
+}
+
+fn side_of(mint_in: Option<Pubkey>, mint_out: Option<Pubkey>) -> Option<SwapSide> {
+    let wsol = wsol_mint();
+    match (mint_in, mint_out) {
+        (Some(mint_in), _) if mint_in == wsol => Some(SwapSide::Buy),
+        (_, Some(mint_out)) if mint_out == wsol => Some(SwapSide::Sell),
+        _ => None,
+    }
+}
+
+impl From<&PumpTrade> for SwapIntent {
+    // buy为精确代币输出和最大SOL成本，sell为精确代币输入和最小SOL收益
+    fn from(trade: &PumpTrade) -> Self {
+        let (mint_in, mint_out) = if trade.is_buy {
+            (wsol_mint(), trade.mint)
+        } else {
+            (trade.mint, wsol_mint())
+        };
+        Self::new(
+            Venue::Pump,
+            Some(trade.bonding_curve),
+            trade.user,
+            (Some(mint_in), Some(mint_out)),
+            !trade.is_buy,
+            trade.token_amount,
+            trade.sol_limit,
+        )
+    }
+}
+
+impl From<&PoolSwap> for SwapIntent {
+    // Buy为精确基础代币输出和最大报价代币输入，Sell为精确基础代币输入和最小报价代币输出
+    fn from(swap: &PoolSwap) -> Self {
+        let (mint_in, mint_out) = if swap.is_buy {
+            (swap.quote_mint, swap.base_mint)
+        } else {
+            (swap.base_mint, swap.quote_mint)
+        };
+        Self::new(
+            Venue::PumpAmm,
+            Some(swap.pool),
+            swap.user,
+            (Some(mint_in), Some(mint_out)),
+            !swap.is_buy,
+            swap.base_amount,
+            swap.quote_limit,
+        )
+    }
+}
+
+impl From<&RaydiumAmmSwap> for SwapIntent {
+    fn from(swap: &RaydiumAmmSwap) -> Self {
+        let (amount, limit_amount) = if swap.exact_in {
+            (swap.amount_in, swap.amount_out)
+        } else {
+            (swap.amount_out, swap.amount_in)
+        };
+        Self::new(
+            Venue::RaydiumAmm,
+            Some(swap.pool),
+            swap.user,
+            (swap.source_mint, swap.destination_mint),
+            swap.exact_in,
+            amount,
+            limit_amount,
+        )
+    }
+}
+
+impl From<&RaydiumCpmmSwap> for SwapIntent {
+    fn from(swap: &RaydiumCpmmSwap) -> Self {
+        let (amount, limit_amount) = if swap.exact_in {
+            (swap.amount_in, swap.amount_out)
+        } else {
+            (swap.amount_out, swap.amount_in)
+        };
+        Self::new(
+            Venue::RaydiumCpmm,
+            Some(swap.pool),
+            swap.user,
+            (Some(swap.input_mint), Some(swap.output_mint)),
+            swap.exact_in,
+            amount,
+            limit_amount,
+        )
+    }
+}
+
+impl From<&DlmmSwap> for SwapIntent {
+    fn from(swap: &DlmmSwap) -> Self {
+        let (amount, limit_amount) = if swap.exact_in {
+            (swap.amount_in, swap.amount_out)
+        } else {
+            (swap.amount_out, swap.amount_in)
+        };
+        Self::new(
+            Venue::MeteoraDlmm,
+            Some(swap.pool),
+            swap.user,
+            (swap.input_mint, swap.output_mint),
+            swap.exact_in,
+            amount,
+            limit_amount,
+        )
+    }
+}
+
+impl From<&WhirlpoolSwap> for SwapIntent {
+    fn from(swap: &WhirlpoolSwap) -> Self {
+        let (amount, limit_amount) = if swap.exact_in {
+            (swap.amount_in, swap.amount_out)
+        } else {
+            (swap.amount_out, swap.amount_in)
+        };
+        Self::new(
+            Venue::OrcaWhirlpool,
+            Some(swap.pool),
+            swap.user,
+            (swap.input_mint, swap.output_mint),
+            swap.exact_in,
+            amount,
+            limit_amount,
+        )
+    }
+}
+
+impl From<&JupiterRoute> for SwapIntent {
+    fn from(route: &JupiterRoute) -> Self {
+        Self::new(
+            Venue::Jupiter,
+            None,
+            route.user,
+            (route.input_mint, route.output_mint),
+            !route.kind.is_exact_out(),
+            route.amount,
+            route.limit_amount(),
+        )
+    }
+}
+
+impl DecodedTransaction {
+    /// 交易中所有顶层兑换指令统一后的交易意图，按DEX分组、组内保持指令顺序，自定义解码器的兑换排在最后
+    pub fn swap_intents(&self) -> Vec<SwapIntent> {
+        let pump = self.pump_actions.iter().filter_map(|action| match action {
+            PumpAction::Trade(trade) => Some(SwapIntent::from(trade)),
+            _ => None,
+        });
+        let pumpamm = self.pumpamm_actions.iter().filter_map(|action| match action {
+            PumpAmmAction::Swap(swap) => Some(SwapIntent::from(swap)),
+            _ => None,
+        });
+        let raydium_amm = self.raydium_amm_actions.iter().filter_map(|action| match action {
+            RaydiumAmmAction::Swap(swap) => Some(SwapIntent::from(swap)),
+            _ => None,
+        });
+        let raydium_cpmm = self.raydium_cpmm_actions.iter().filter_map(|action| match action {
+            RaydiumCpmmAction::Swap(swap) => Some(SwapIntent::from(swap)),
+            _ => None,
+        });
+
+        pump.chain(pumpamm)
+            .chain(raydium_amm)
+            .chain(raydium_cpmm)
+            .chain(self.dlmm_swaps.iter().map(SwapIntent::from))
+            .chain(self.whirlpool_swaps.iter().map(SwapIntent::from))
+            .chain(self.jupiter_routes.iter().map(SwapIntent::from))
+            .chain(self.registry_swaps.iter().cloned())
+            .map(|intent| SwapIntent { latency: self.latency, ..intent })
+            .collect()
+    }
+}
@@ -0,0 +1,166 @@
+use solana_sdk::{
+    pubkey::Pubkey,
+    system_instruction::SystemInstruction,
+    transaction::VersionedTransaction,
+};
+use std::fmt;
+
+use super::pump_parser::lamports_to_sol_string;
+use super::instruction_account;
+use super::registry::{Decoded, ProgramDecoder};
+
+// System程序ID
+pub const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+
+// 解析后的System指令
+#[derive(Debug)]
+pub struct ParsedSystemInstruction {
+    pub name: String,
+    pub params: String,
+}
+
+// SOL转账，包括transfer_with_seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemTransfer {
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub lamports: u64,
+}
+
+// 创建账户，包括create_account_with_seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemCreateAccount {
+    pub funder: Pubkey,
+    pub account: Pubkey,
+    pub lamports: u64,
+    pub space: u64,
+    pub owner: Pubkey,
+}
+
+// 按指令序号识别出的System操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SystemAction {
+    Transfer(SystemTransfer),
+    CreateAccount(SystemCreateAccount),
+}
+
+impl SystemAction {
+    fn name(&self) -> &'static str {
+        match self {
+            SystemAction::Transfer(_) => "Transfer",
+            SystemAction::CreateAccount(_) => "CreateAccount",
+        }
+    }
+}
+
+impl fmt::Display for SystemAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SystemAction::Transfer(transfer) => write!(
+                f,
+                "SOL转账: {} -> {}, 金额={}",
+                transfer.from,
+                transfer.to,
+                lamports_to_sol_string(transfer.lamports)
+            ),
+            SystemAction::CreateAccount(create) => write!(
+                f,
+                "创建账户: 出资={}, 新账户={}, 金额={}, 空间={}字节, 所有者={}",
+                create.funder,
+                create.account,
+                lamports_to_sol_string(create.lamports),
+                create.space,
+                create.owner
+            ),
+        }
+    }
+}
+
+/// 解码单条System指令，只识别转账和创建账户
+///
+/// 账户顺序: transfer为 from, to；transfer_with_seed为 from, base, to；
+/// create_account/create_account_with_seed为 funder, newAccount。
+pub fn decode_system_instruction(static_keys: &[Pubkey], accounts: &[u8], data: &[u8]) -> Option<SystemAction> {
+    let account = |position| instruction_account(static_keys, accounts, position);
+
+    // System指令是bincode编码的枚举
+    match bincode::deserialize::<SystemInstruction>(data).ok()? {
+        SystemInstruction::Transfer { lamports } => Some(SystemAction::Transfer(SystemTransfer {
+            from: account(0)?,
+            to: account(1)?,
+            lamports,
+        })),
+        SystemInstruction::TransferWithSeed { lamports, .. } => Some(SystemAction::Transfer(SystemTransfer {
+            from: account(0)?,
+            to: account(2)?,
+            lamports,
+        })),
+        SystemInstruction::CreateAccount { lamports, space, owner }
+        | SystemInstruction::CreateAccountWithSeed { lamports, space, owner, .. } => {
+            Some(SystemAction::CreateAccount(SystemCreateAccount {
+                funder: account(0)?,
+                account: account(1)?,
+                lamports,
+                space,
+                owner,
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// 解析单个System指令
+pub fn parse_system_instruction(transaction: &VersionedTransaction, instruction_index: usize) -> Option<ParsedSystemInstruction> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+    let instruction = message.instructions().get(instruction_index)?;
+    if instruction.program_id(static_keys).to_string() != SYSTEM_PROGRAM_ID {
+        return None;
+    }
+
+    let parsed = match decode_system_instruction(static_keys, &instruction.accounts, &instruction.data) {
+        Some(action) => ParsedSystemInstruction {
+            name: action.name().to_string(),
+            params: action.to_string(),
+        },
+        None => ParsedSystemInstruction {
+            name: "其他System指令".to_string(),
+            params: match bincode::deserialize::<SystemInstruction>(&instruction.data) {
+                Ok(other) => format!("{:?}", other),
+                Err(_) => "无法解析参数".to_string(),
+            },
+        },
+    };
+    Some(parsed)
+}
+
+/// 解析交易中的所有顶层System转账和创建账户指令
+pub fn decode_system_actions(transaction: &VersionedTransaction) -> Vec<SystemAction> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+
+    message
+        .instructions()
+        .iter()
+        .filter(|instruction| instruction.program_id(static_keys).to_string() == SYSTEM_PROGRAM_ID)
+        .filter_map(|instruction| decode_system_instruction(static_keys, &instruction.accounts, &instruction.data))
+        .collect()
+}
+
+/// System程序的解码器
+pub struct SystemDecoder;
+
+impl ProgramDecoder for SystemDecoder {
+    fn program_id(&self) -> Pubkey {
+        SYSTEM_PROGRAM_ID.parse().expect("合法的System程序ID")
+    }
+
+    fn name(&self) -> &str {
+        "System"
+    }
+
+    fn decode_instruction(&self, transaction: &VersionedTransaction, instruction_index: usize) -> Option<Decoded> {
+        let parsed = parse_system_instruction(transaction, instruction_index)?;
+        Some(Decoded::new(parsed.name, parsed.params))
+    }
+}
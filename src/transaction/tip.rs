@@ -0,0 +1,34 @@
+use solana_sdk::{pubkey::Pubkey, transaction::VersionedTransaction};
+
+use super::system_parser::{decode_system_actions, SystemAction};
+
+/// 交易中转给Jito小费账户的一笔SOL
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JitoTip {
+    pub from: Pubkey,
+    pub tip_account: Pubkey,
+    pub lamports: u64,
+}
+
+/// 找出交易中转给小费账户的System transfer
+///
+/// 只能看到顶层指令，通过其他程序CPI转账的小费不可见；
+/// 账户索引指向地址查找表的转账同样无法解析。
+pub fn find_tips(transaction: &VersionedTransaction, tip_accounts: &[Pubkey]) -> Vec<JitoTip> {
+    let keys = transaction.message.static_account_keys();
+    if tip_accounts.is_empty() || !keys.iter().any(|key| tip_accounts.contains(key)) {
+        return Vec::new();
+    }
+
+    decode_system_actions(transaction)
+        .into_iter()
+        .filter_map(|action| match action {
+            SystemAction::Transfer(transfer) if tip_accounts.contains(&transfer.to) => Some(JitoTip {
+                from: transfer.from,
+                tip_account: transfer.to,
+                lamports: transfer.lamports,
+            }),
+            _ => None,
+        })
+        .collect()
+}
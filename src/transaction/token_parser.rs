@@ -0,0 +1,294 @@
+use solana_sdk::{pubkey::Pubkey, transaction::VersionedTransaction};
+use std::fmt;
+
+use super::instruction_account;
+use super::registry::{Decoded, ProgramDecoder};
+use crate::accounts::decode_token_account;
+
+// SPL Token程序，Token-2022沿用相同的基础指令布局
+pub const TOKEN_PROGRAM_ID: &str = "TokenkegQfeZyiNwAdMCGDgVxpzN5MuszNBzG2Yk6Bp";
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+
+// 指令序号(首字节)
+pub const INITIALIZE_MINT_IX: u8 = 0;
+pub const TRANSFER_IX: u8 = 3;
+pub const MINT_TO_IX: u8 = 7;
+pub const BURN_IX: u8 = 8;
+pub const CLOSE_ACCOUNT_IX: u8 = 9;
+pub const TRANSFER_CHECKED_IX: u8 = 12;
+pub const MINT_TO_CHECKED_IX: u8 = 14;
+pub const BURN_CHECKED_IX: u8 = 15;
+pub const INITIALIZE_MINT2_IX: u8 = 20;
+
+// 解析后的Token指令
+#[derive(Debug)]
+pub struct ParsedTokenInstruction {
+    pub name: String,
+    pub params: String,
+}
+
+// 代币转账，transfer_checked额外带mint和精度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenTransfer {
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub authority: Pubkey,
+    pub mint: Option<Pubkey>,
+    pub amount: u64,
+    pub decimals: Option<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenMintTo {
+    pub mint: Pubkey,
+    pub account: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenBurn {
+    pub account: Pubkey,
+    pub mint: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+}
+
+// 关闭代币账户，剩余的租金转给destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenCloseAccount {
+    pub account: Pubkey,
+    pub destination: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenInitializeMint {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub mint_authority: Pubkey,
+    pub freeze_authority: Option<Pubkey>,
+}
+
+// 按指令序号识别出的Token/Token-2022操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenAction {
+    Transfer(TokenTransfer),
+    MintTo(TokenMintTo),
+    Burn(TokenBurn),
+    CloseAccount(TokenCloseAccount),
+    InitializeMint(TokenInitializeMint),
+}
+
+impl TokenAction {
+    fn name(&self) -> &'static str {
+        match self {
+            TokenAction::Transfer(transfer) if transfer.mint.is_some() => "TransferChecked",
+            TokenAction::Transfer(_) => "Transfer",
+            TokenAction::MintTo(_) => "MintTo",
+            TokenAction::Burn(_) => "Burn",
+            TokenAction::CloseAccount(_) => "CloseAccount",
+            TokenAction::InitializeMint(_) => "InitializeMint",
+        }
+    }
+}
+
+impl fmt::Display for TokenAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenAction::Transfer(transfer) => {
+                write!(
+                    f,
+                    "代币转账: {} -> {}, 数量={}, 授权={}",
+                    transfer.source, transfer.destination, transfer.amount, transfer.authority
+                )?;
+                if let (Some(mint), Some(decimals)) = (transfer.mint, transfer.decimals) {
+                    write!(f, ", Mint={}, 精度={}", mint, decimals)?;
+                }
+                Ok(())
+            }
+            TokenAction::MintTo(mint_to) => write!(
+                f,
+                "铸造: Mint={}, 目标账户={}, 数量={}, 授权={}",
+                mint_to.mint, mint_to.account, mint_to.amount, mint_to.authority
+            ),
+            TokenAction::Burn(burn) => write!(
+                f,
+                "销毁: Mint={}, 账户={}, 数量={}, 授权={}",
+                burn.mint, burn.account, burn.amount, burn.authority
+            ),
+            TokenAction::CloseAccount(close) => write!(
+                f,
+                "关闭账户: 账户={}, 租金接收={}, 授权={}",
+                close.account, close.destination, close.authority
+            ),
+            TokenAction::InitializeMint(init) => {
+                write!(
+                    f,
+                    "初始化Mint: Mint={}, 精度={}, 铸造权限={}",
+                    init.mint, init.decimals, init.mint_authority
+                )?;
+                match init.freeze_authority {
+                    Some(freeze_authority) => write!(f, ", 冻结权限={}", freeze_authority),
+                    None => write!(f, ", 无冻结权限"),
+                }
+            }
+        }
+    }
+}
+
+/// 是否是SPL Token或Token-2022程序
+pub fn is_token_program(program_id: &Pubkey) -> bool {
+    let program_id = program_id.to_string();
+    program_id == TOKEN_PROGRAM_ID || program_id == TOKEN_2022_PROGRAM_ID
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    data.get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().expect("8字节")))
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Option<Pubkey> {
+    data.get(offset..offset + 32)
+        .map(|bytes| Pubkey::new_from_array(bytes.try_into().expect("32字节")))
+}
+
+/// 解码单条Token/Token-2022指令
+///
+/// 账户顺序: transfer为 source, destination, authority；transfer_checked为 source, mint, destination, authority；
+/// mint_to为 mint, account, authority；burn为 account, mint, authority；close_account为 account, destination, authority；
+/// initialize_mint/initialize_mint2的第一个账户为mint。多签授权的附加签名者不记录。
+pub fn decode_token_instruction(static_keys: &[Pubkey], accounts: &[u8], data: &[u8]) -> Option<TokenAction> {
+    let account = |position| instruction_account(static_keys, accounts, position);
+    let (&tag, args) = data.split_first()?;
+
+    let action = match tag {
+        TRANSFER_IX => TokenAction::Transfer(TokenTransfer {
+            source: account(0)?,
+            destination: account(1)?,
+            authority: account(2)?,
+            mint: None,
+            amount: read_u64(args, 0)?,
+            decimals: None,
+        }),
+        TRANSFER_CHECKED_IX => TokenAction::Transfer(TokenTransfer {
+            source: account(0)?,
+            destination: account(2)?,
+            authority: account(3)?,
+            mint: Some(account(1)?),
+            amount: read_u64(args, 0)?,
+            decimals: Some(*args.get(8)?),
+        }),
+        MINT_TO_IX | MINT_TO_CHECKED_IX => TokenAction::MintTo(TokenMintTo {
+            mint: account(0)?,
+            account: account(1)?,
+            authority: account(2)?,
+            amount: read_u64(args, 0)?,
+        }),
+        BURN_IX | BURN_CHECKED_IX => TokenAction::Burn(TokenBurn {
+            account: account(0)?,
+            mint: account(1)?,
+            authority: account(2)?,
+            amount: read_u64(args, 0)?,
+        }),
+        CLOSE_ACCOUNT_IX => TokenAction::CloseAccount(TokenCloseAccount {
+            account: account(0)?,
+            destination: account(1)?,
+            authority: account(2)?,
+        }),
+        INITIALIZE_MINT_IX | INITIALIZE_MINT2_IX => {
+            // decimals(u8) + mint_authority + COption<Pubkey>(1字节标记 + 32字节)
+            let freeze_authority = match args.get(33)? {
+                1 => Some(read_pubkey(args, 34)?),
+                _ => None,
+            };
+            TokenAction::InitializeMint(TokenInitializeMint {
+                mint: account(0)?,
+                decimals: *args.first()?,
+                mint_authority: read_pubkey(args, 1)?,
+                freeze_authority,
+            })
+        }
+        _ => return None,
+    };
+    Some(action)
+}
+
+/// 解析单个Token/Token-2022指令
+pub fn parse_token_instruction(transaction: &VersionedTransaction, instruction_index: usize) -> Option<ParsedTokenInstruction> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+    let instruction = message.instructions().get(instruction_index)?;
+    let program_id = instruction.program_id(static_keys);
+    if !is_token_program(program_id) {
+        return None;
+    }
+    let prefix = if program_id.to_string() == TOKEN_2022_PROGRAM_ID { "Token-2022 " } else { "" };
+
+    let parsed = match decode_token_instruction(static_keys, &instruction.accounts, &instruction.data) {
+        Some(action) => ParsedTokenInstruction {
+            name: format!("{}{}", prefix, action.name()),
+            params: action.to_string(),
+        },
+        None => ParsedTokenInstruction {
+            name: format!("{}其他Token指令", prefix),
+            params: match instruction.data.first() {
+                Some(tag) => format!("指令序号: {}", tag),
+                None => "空指令".to_string(),
+            },
+        },
+    };
+    Some(parsed)
+}
+
+/// 解析交易中的所有顶层Token/Token-2022指令
+pub fn decode_token_actions(transaction: &VersionedTransaction) -> Vec<TokenAction> {
+    let message = &transaction.message;
+    let static_keys = message.static_account_keys();
+
+    message
+        .instructions()
+        .iter()
+        .filter(|instruction| is_token_program(instruction.program_id(static_keys)))
+        .filter_map(|instruction| decode_token_instruction(static_keys, &instruction.accounts, &instruction.data))
+        .collect()
+}
+
+/// SPL Token或Token-2022程序的解码器
+pub struct TokenDecoder {
+    program_id: Pubkey,
+    name: &'static str,
+}
+
+impl TokenDecoder {
+    pub fn new(program_id: &str, name: &'static str) -> Self {
+        Self {
+            program_id: program_id.parse().expect("合法的Token程序ID"),
+            name,
+        }
+    }
+}
+
+impl ProgramDecoder for TokenDecoder {
+    fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn decode_instruction(&self, transaction: &VersionedTransaction, instruction_index: usize) -> Option<Decoded> {
+        let parsed = parse_token_instruction(transaction, instruction_index)?;
+        Some(Decoded::new(parsed.name, parsed.params))
+    }
+
+    fn decode_account(&self, data: &[u8]) -> Option<Decoded> {
+        let account = decode_token_account(data).ok()?;
+        Some(
+            Decoded::new("TokenAccount", format!("数量={}", account.amount))
+                .with_field("Mint", account.mint)
+                .with_field("所有者", account.owner),
+        )
+    }
+}